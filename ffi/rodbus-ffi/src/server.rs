@@ -545,7 +545,7 @@ pub(crate) unsafe fn server_set_decode_level(
 
 pub enum AddressFilter {
     Any,
-    WildcardIpv4(WildcardIPv4),
+    Parsed(rodbus::server::AddressFilter),
     AnyOf(std::collections::HashSet<std::net::IpAddr>),
 }
 
@@ -562,15 +562,15 @@ fn parse_address_filter(s: &str) -> Result<AddressFilter, ffi::ParamError> {
             Ok(AddressFilter::AnyOf(set))
         }
         Err(_) => {
-            // now try to parse as a wildcard
-            let wc: WildcardIPv4 = s.parse()?;
-            Ok(AddressFilter::WildcardIpv4(wc))
+            // now try to parse as a wildcard, a network or a list
+            let filter: rodbus::server::AddressFilter = s.parse()?;
+            Ok(AddressFilter::Parsed(filter))
         }
     }
 }
 
-impl From<BadIpv4Wildcard> for ffi::ParamError {
-    fn from(_: BadIpv4Wildcard) -> Self {
+impl From<BadAddressFilter> for ffi::ParamError {
+    fn from(_: BadAddressFilter) -> Self {
         ffi::ParamError::InvalidIpAddress
     }
 }
@@ -597,8 +597,8 @@ pub unsafe fn address_filter_add(
         AddressFilter::AnyOf(set) => {
             set.insert(address);
         }
-        AddressFilter::WildcardIpv4(_) => {
-            // can't add addresses to a wildcard specification
+        AddressFilter::Parsed(_) => {
+            // can't add addresses to a wildcard, network or list specification
             return Err(ffi::ParamError::InvalidIpAddress);
         }
     }
@@ -616,7 +616,7 @@ impl From<&AddressFilter> for rodbus::server::AddressFilter {
         match from {
             AddressFilter::Any => rodbus::server::AddressFilter::Any,
             AddressFilter::AnyOf(set) => rodbus::server::AddressFilter::AnyOf(set.clone()),
-            AddressFilter::WildcardIpv4(wc) => rodbus::server::AddressFilter::WildcardIpv4(*wc),
+            AddressFilter::Parsed(filter) => filter.clone(),
         }
     }
 }
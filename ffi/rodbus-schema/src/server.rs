@@ -742,8 +742,9 @@ fn define_address_filter(
         .fails_with(common.error_type.clone())?
         .doc(
             doc("Create an address filter that matches one or more IP addresses. Ipv4 or IPv6 addresses are allowed.")
-                .details("Examples: 192.168.1.26, 192.168.0.*, *.*.*.*")
-                .details("Wildcards are only supported for IPv4 addresses"),
+                .details("Examples: 192.168.1.26, 192.168.0.*, *.*.*.*, 10.0.0.0/8, fd00::/8")
+                .details("Wildcards are only supported for IPv4 addresses")
+                .details("A comma separated list of entries prefixed by 'allow' or 'deny' is evaluated in order and rejects the addresses that match no entry, e.g. 'deny 10.0.0.5, allow 10.0.0.0/8'"),
         )?
         .build()?;
 
@@ -27,10 +27,19 @@ enum Command {
     ReadDiscreteInputs(AddressRange),
     ReadHoldingRegisters(AddressRange),
     ReadInputRegisters(AddressRange),
+    ReadFifoQueue(u16),
+    ReadFileRecord(FileRecord),
+    ReadDeviceIdentification(ReadDeviceIdCode, u8),
+    ReadExceptionStatus,
+    ReportServerId,
     WriteSingleRegister(Indexed<u16>),
     WriteSingleCoil(Indexed<bool>),
     WriteMultipleCoils(WriteMultiple<bool>),
     WriteMultipleRegisters(WriteMultiple<u16>),
+    MaskWriteRegister(MaskWriteRegister),
+    ReadWriteMultipleRegisters(ReadWriteMultiple),
+    WriteFileRecord(FileRecordData),
+    CustomFunction(u8, Vec<u8>),
 }
 
 struct Args {
@@ -113,9 +122,52 @@ async fn run_command(
                 println!("index: {} value: {}", x.index, x.value)
             }
         }
+        Command::ReadFifoQueue(address) => {
+            for x in channel.read_fifo_queue(params, *address).await? {
+                println!("value: {}", x)
+            }
+        }
+        Command::ReadFileRecord(record) => {
+            for data in channel.read_file_records(params, vec![*record]).await? {
+                for (index, value) in (data.record_number..).zip(data.values) {
+                    println!("record: {} value: {}", index, value)
+                }
+            }
+        }
+        Command::ReadDeviceIdentification(code, object_id) => {
+            let reply = channel
+                .read_device_identification(params, *code, *object_id)
+                .await?;
+            println!("conformity level: {:#04X}", reply.conformity_level);
+            for x in reply.objects {
+                println!("{}", x)
+            }
+        }
+        Command::ReadExceptionStatus => {
+            let status = channel.read_exception_status(params).await?;
+            println!("status: {:#04X}", status);
+        }
+        Command::ReportServerId => {
+            for x in channel.report_server_id(params).await? {
+                println!("{:#04X}", x)
+            }
+        }
+        Command::CustomFunction(function_code, data) => {
+            for x in channel
+                .send_custom_function(params, *function_code, data.clone())
+                .await?
+            {
+                println!("{:#04X}", x)
+            }
+        }
         Command::WriteSingleRegister(arg) => {
             channel.write_single_register(params, *arg).await?;
         }
+        Command::WriteFileRecord(arg) => {
+            channel
+                .write_file_records(params, vec![arg.clone()])
+                .await?;
+        }
         Command::WriteSingleCoil(arg) => {
             channel.write_single_coil(params, *arg).await?;
         }
@@ -127,6 +179,17 @@ async fn run_command(
                 .write_multiple_registers(params, arg.clone())
                 .await?;
         }
+        Command::MaskWriteRegister(arg) => {
+            channel.mask_write_register(params, *arg).await?;
+        }
+        Command::ReadWriteMultipleRegisters(arg) => {
+            for x in channel
+                .read_write_multiple_registers(params, arg.clone())
+                .await?
+            {
+                println!("index: {} value: {}", x.index, x.value)
+            }
+        }
     }
     Ok(())
 }
@@ -201,6 +264,49 @@ fn get_command(matches: &ArgMatches) -> Result<Command, Error> {
         return Ok(Command::ReadInputRegisters(get_address_range(matches)?));
     }
 
+    if let Some(matches) = matches.subcommand_matches("rfq") {
+        return Ok(Command::ReadFifoQueue(get_index(matches)?));
+    }
+
+    if let Some(matches) = matches.subcommand_matches("rfr") {
+        let file_number = u16::from_str(matches.value_of("file").unwrap())?;
+        return Ok(Command::ReadFileRecord(FileRecord::new(
+            file_number,
+            get_start(matches)?,
+            get_quantity(matches)?,
+        )));
+    }
+
+    if let Some(matches) = matches.subcommand_matches("rdid") {
+        let code = match matches.value_of("code").unwrap() {
+            "basic" => ReadDeviceIdCode::Basic,
+            "regular" => ReadDeviceIdCode::Regular,
+            "extended" => ReadDeviceIdCode::Extended,
+            _ => ReadDeviceIdCode::Individual,
+        };
+        let object_id = u8::from_str(matches.value_of("object").unwrap())?;
+        return Ok(Command::ReadDeviceIdentification(code, object_id));
+    }
+
+    if matches.subcommand_matches("res").is_some() {
+        return Ok(Command::ReadExceptionStatus);
+    }
+
+    if matches.subcommand_matches("rsid").is_some() {
+        return Ok(Command::ReportServerId);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("cf") {
+        let function_code = u8::from_str(matches.value_of("function").unwrap())?;
+        let mut data = Vec::new();
+        if let Some(values) = matches.value_of("data") {
+            for value in values.split(',') {
+                data.push(u8::from_str(value)?);
+            }
+        }
+        return Ok(Command::CustomFunction(function_code, data));
+    }
+
     if let Some(matches) = matches.subcommand_matches("wsr") {
         return Ok(Command::WriteSingleRegister(get_indexed_register_value(
             matches,
@@ -229,6 +335,35 @@ fn get_command(matches: &ArgMatches) -> Result<Command, Error> {
         )?));
     }
 
+    if let Some(matches) = matches.subcommand_matches("mwr") {
+        let index = get_index(matches)?;
+        let and_mask = u16::from_str(matches.value_of("and_mask").unwrap())?;
+        let or_mask = u16::from_str(matches.value_of("or_mask").unwrap())?;
+        return Ok(Command::MaskWriteRegister(MaskWriteRegister::new(
+            index, and_mask, or_mask,
+        )));
+    }
+
+    if let Some(matches) = matches.subcommand_matches("wfr") {
+        let file_number = u16::from_str(matches.value_of("file").unwrap())?;
+        return Ok(Command::WriteFileRecord(FileRecordData::new(
+            file_number,
+            get_start(matches)?,
+            get_register_values(matches)?,
+        )));
+    }
+
+    if let Some(matches) = matches.subcommand_matches("rwmr") {
+        let range = get_address_range(matches)?;
+        let write_start = u16::from_str(matches.value_of("write_start").unwrap())?;
+        let values = get_register_values(matches)?;
+        return Ok(Command::ReadWriteMultipleRegisters(ReadWriteMultiple::new(
+            range,
+            write_start,
+            values,
+        )?));
+    }
+
     Err(Error::MissingSubCommand)
 }
 
@@ -342,6 +477,116 @@ fn parse_args() -> Result<Args, Error> {
                         .help("quantity of values"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("rfq")
+                .about("read FIFO queue")
+                .arg(
+                    Arg::with_name("index")
+                        .short("i")
+                        .long("index")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the address of the FIFO pointer register"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rfr")
+                .about("read file record")
+                .arg(
+                    Arg::with_name("file")
+                        .short("f")
+                        .long("file")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the file number"),
+                )
+                .arg(
+                    Arg::with_name("start")
+                        .short("s")
+                        .long("start")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the starting record number"),
+                )
+                .arg(
+                    Arg::with_name("quantity")
+                        .short("q")
+                        .long("quantity")
+                        .required(true)
+                        .takes_value(true)
+                        .help("quantity of records"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("wfr")
+                .about("write file record")
+                .arg(
+                    Arg::with_name("file")
+                        .short("f")
+                        .long("file")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the file number"),
+                )
+                .arg(
+                    Arg::with_name("start")
+                        .short("s")
+                        .long("start")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the starting record number"),
+                )
+                .arg(
+                    Arg::with_name("values")
+                        .short("v")
+                        .long("values")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the values of the records specified as a comma delimited list (e.g. 1,4,7)"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rdid")
+                .about("read device identification")
+                .arg(
+                    Arg::with_name("code")
+                        .short("c")
+                        .long("code")
+                        .takes_value(true)
+                        .possible_values(&["basic", "regular", "extended", "individual"])
+                        .default_value("basic")
+                        .help("the access type"),
+                )
+                .arg(
+                    Arg::with_name("object")
+                        .short("o")
+                        .long("object")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("the first object id to read, or the object to read for individual access"),
+                ),
+        )
+        .subcommand(SubCommand::with_name("res").about("read exception status"))
+        .subcommand(SubCommand::with_name("rsid").about("report server id"))
+        .subcommand(
+            SubCommand::with_name("cf")
+                .about("send a custom function")
+                .arg(
+                    Arg::with_name("function")
+                        .short("f")
+                        .long("function")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the custom function code"),
+                )
+                .arg(
+                    Arg::with_name("data")
+                        .short("d")
+                        .long("data")
+                        .takes_value(true)
+                        .help("the bytes following the function code, e.g. 1,2,3"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("wsc")
                 .about("write single coil")
@@ -422,6 +667,70 @@ fn parse_args() -> Result<Args, Error> {
                         .help("the values of the registers specified as a comma delimited list (e.g. 1,4,7)"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("mwr")
+                .about("mask write register")
+                .arg(
+                    Arg::with_name("index")
+                        .short("i")
+                        .long("index")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the address of the register"),
+                )
+                .arg(
+                    Arg::with_name("and_mask")
+                        .short("a")
+                        .long("and")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the AND mask applied to the register"),
+                )
+                .arg(
+                    Arg::with_name("or_mask")
+                        .short("o")
+                        .long("or")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the OR mask applied to the register"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rwmr")
+                .about("write multiple registers and then read holding registers")
+                .arg(
+                    Arg::with_name("start")
+                        .short("s")
+                        .long("start")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the starting address of the registers to read"),
+                )
+                .arg(
+                    Arg::with_name("quantity")
+                        .short("q")
+                        .long("quantity")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the number of registers to read"),
+                )
+                .arg(
+                    Arg::with_name("write_start")
+                        .short("w")
+                        .long("write-start")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the starting address of the registers to write"),
+                )
+                .arg(
+                    Arg::with_name("values")
+                        .short("v")
+                        .long("values")
+                        .required(true)
+                        .takes_value(true)
+                        .help("the values of the registers to write specified as a comma delimited list (e.g. 1,4,7)"),
+                ),
+        )
         .get_matches();
 
     let address = SocketAddr::from_str(matches.value_of("host").unwrap())?;
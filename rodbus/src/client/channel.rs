@@ -1,12 +1,32 @@
 use std::time::Duration;
 
 use crate::client::message::{Command, Promise, Request, RequestDetails, Setting};
+use crate::client::requests::custom_function::CustomFunction;
+use crate::client::requests::diagnostics::Diagnostics;
+use crate::client::requests::get_comm_event_counter::GetCommEventCounter;
+use crate::client::requests::get_comm_event_log::GetCommEventLog;
 use crate::client::requests::read_bits::ReadBits;
+use crate::client::requests::read_device_identification::ReadDeviceIdentification;
+use crate::client::requests::read_exception_status::ReadExceptionStatus;
+use crate::client::requests::read_fifo_queue::ReadFifoQueue;
+use crate::client::requests::read_file_record::ReadFileRecord;
 use crate::client::requests::read_registers::ReadRegisters;
+use crate::client::requests::read_write_multiple::{ReadWriteMultiple, ReadWriteMultipleRegisters};
+use crate::client::requests::report_server_id::ReportServerId;
+use crate::client::requests::write_file_record::WriteFileRecord;
 use crate::client::requests::write_multiple::{MultipleWriteRequest, WriteMultiple};
 use crate::client::requests::write_single::SingleWrite;
+use crate::codec::{decode_string, decode_values, register_count, ByteOrder, RegisterValue};
+use crate::device_id::{DeviceIdReply, DeviceIdentification, ReadDeviceIdCode};
+use crate::diagnostics::{
+    sub_function, CommEventCounter, CommEventLog, DiagnosticCounter, DiagnosticsRequest,
+    CLEAR_EVENT_LOG,
+};
 use crate::error::*;
-use crate::types::{AddressRange, BitIterator, Indexed, RegisterIterator, UnitId};
+use crate::file_record::{FileRecord, FileRecordData};
+use crate::types::{
+    AddressRange, BitIterator, Indexed, MaskWriteRegister, RegisterIterator, UnitId,
+};
 use crate::DecodeLevel;
 
 /// Async channel used to make requests
@@ -41,29 +61,61 @@ impl Channel {
         serial_settings: crate::serial::SerialSettings,
         max_queued_requests: usize,
         retry: Box<dyn crate::retry::RetryStrategy>,
+        custom: crate::serial::CustomFunctionLengths,
         decode: DecodeLevel,
         listener: Option<Box<dyn crate::client::Listener<crate::client::PortState>>>,
     ) -> Self {
-        let (handle, task) = Self::create_rtu_handle_and_task(
+        let (handle, task) = Self::create_serial_handle_and_task(
             path,
             serial_settings,
             max_queued_requests,
             retry,
+            crate::common::frame::FrameWriter::rtu(),
+            crate::common::frame::FramedReader::rtu_response(custom),
             decode,
             listener,
+            tracing::info_span!("Modbus-Client-RTU", "port" = ?path),
         );
         tokio::spawn(task);
         handle
     }
 
     #[cfg(feature = "serial")]
-    pub(crate) fn create_rtu_handle_and_task(
+    pub(crate) fn spawn_ascii(
         path: &str,
         serial_settings: crate::serial::SerialSettings,
         max_queued_requests: usize,
         retry: Box<dyn crate::retry::RetryStrategy>,
         decode: DecodeLevel,
         listener: Option<Box<dyn crate::client::Listener<crate::client::PortState>>>,
+    ) -> Self {
+        let (handle, task) = Self::create_serial_handle_and_task(
+            path,
+            serial_settings,
+            max_queued_requests,
+            retry,
+            crate::common::frame::FrameWriter::ascii(),
+            crate::common::frame::FramedReader::ascii(),
+            decode,
+            listener,
+            tracing::info_span!("Modbus-Client-ASCII", "port" = ?path),
+        );
+        tokio::spawn(task);
+        handle
+    }
+
+    #[cfg(feature = "serial")]
+    #[allow(clippy::too_many_arguments)]
+    fn create_serial_handle_and_task(
+        path: &str,
+        serial_settings: crate::serial::SerialSettings,
+        max_queued_requests: usize,
+        retry: Box<dyn crate::retry::RetryStrategy>,
+        writer: crate::common::frame::FrameWriter,
+        reader: crate::common::frame::FramedReader,
+        decode: DecodeLevel,
+        listener: Option<Box<dyn crate::client::Listener<crate::client::PortState>>>,
+        span: tracing::Span,
     ) -> (Self, impl std::future::Future<Output = ()>) {
         use tracing::Instrument;
 
@@ -75,11 +127,13 @@ impl Channel {
                 serial_settings,
                 rx,
                 retry,
+                writer,
+                reader,
                 decode,
                 listener.unwrap_or_else(|| crate::client::NullListener::create()),
             )
             .run()
-            .instrument(tracing::info_span!("Modbus-Client-RTU", "port" = ?path))
+            .instrument(span)
             .await;
         };
         (Channel { tx }, task)
@@ -163,6 +217,126 @@ impl Channel {
         rx.await?
     }
 
+    /// Read holding registers from the server and decode them as `count` consecutive values
+    pub async fn read_holding_values<T: RegisterValue>(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        count: u16,
+        order: ByteOrder,
+    ) -> Result<Vec<T>, RequestError> {
+        let range = AddressRange::try_from(start, register_count::<T>(count)?)?;
+        let registers = self.read_holding_registers(param, range).await?;
+        Self::decode_registers(&registers, order)
+    }
+
+    /// Read input registers from the server and decode them as `count` consecutive values
+    pub async fn read_input_values<T: RegisterValue>(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        count: u16,
+        order: ByteOrder,
+    ) -> Result<Vec<T>, RequestError> {
+        let range = AddressRange::try_from(start, register_count::<T>(count)?)?;
+        let registers = self.read_input_registers(param, range).await?;
+        Self::decode_registers(&registers, order)
+    }
+
+    /// Read `count` consecutive `f32` values from holding registers
+    pub async fn read_holding_f32(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        count: u16,
+        order: ByteOrder,
+    ) -> Result<Vec<f32>, RequestError> {
+        self.read_holding_values(param, start, count, order).await
+    }
+
+    /// Read `count` consecutive `f64` values from holding registers
+    pub async fn read_holding_f64(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        count: u16,
+        order: ByteOrder,
+    ) -> Result<Vec<f64>, RequestError> {
+        self.read_holding_values(param, start, count, order).await
+    }
+
+    /// Read `count` consecutive `i32` values from holding registers
+    pub async fn read_holding_i32(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        count: u16,
+        order: ByteOrder,
+    ) -> Result<Vec<i32>, RequestError> {
+        self.read_holding_values(param, start, count, order).await
+    }
+
+    /// Read `count` consecutive `u32` values from holding registers
+    pub async fn read_holding_u32(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        count: u16,
+        order: ByteOrder,
+    ) -> Result<Vec<u32>, RequestError> {
+        self.read_holding_values(param, start, count, order).await
+    }
+
+    /// Read `count` consecutive `i64` values from holding registers
+    pub async fn read_holding_i64(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        count: u16,
+        order: ByteOrder,
+    ) -> Result<Vec<i64>, RequestError> {
+        self.read_holding_values(param, start, count, order).await
+    }
+
+    /// Read `count` consecutive `u64` values from holding registers
+    pub async fn read_holding_u64(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        count: u16,
+        order: ByteOrder,
+    ) -> Result<Vec<u64>, RequestError> {
+        self.read_holding_values(param, start, count, order).await
+    }
+
+    /// Read an ASCII string stored two characters per register in `register_count` holding registers
+    pub async fn read_holding_string(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        register_count: u16,
+        order: ByteOrder,
+    ) -> Result<String, RequestError> {
+        let range = AddressRange::try_from(start, register_count)?;
+        let registers: Vec<u16> = self
+            .read_holding_registers(param, range)
+            .await?
+            .iter()
+            .map(|x| x.value)
+            .collect();
+        decode_string(&registers, order)
+            .map_err(|err| RequestError::BadResponse(AduParseError::InvalidValue(err)))
+    }
+
+    fn decode_registers<T: RegisterValue>(
+        registers: &[Indexed<u16>],
+        order: ByteOrder,
+    ) -> Result<Vec<T>, RequestError> {
+        let registers: Vec<u16> = registers.iter().map(|x| x.value).collect();
+        decode_values(&registers, order)
+            .map_err(|err| RequestError::BadResponse(AduParseError::InvalidValue(err)))
+    }
+
     /// Write a single coil on the server
     pub async fn write_single_coil(
         &mut self,
@@ -229,6 +403,320 @@ impl Channel {
         rx.await?
     }
 
+    /// Encode values in the specified byte order and write them to consecutive registers
+    pub async fn write_values<T: RegisterValue>(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        values: &[T],
+        order: ByteOrder,
+    ) -> Result<AddressRange, RequestError> {
+        let request = WriteMultiple::from_values(start, values, order)?;
+        self.write_multiple_registers(param, request).await
+    }
+
+    /// Encode an ASCII string into `register_count` registers and write them
+    pub async fn write_string(
+        &mut self,
+        param: RequestParam,
+        start: u16,
+        value: &str,
+        register_count: u16,
+        order: ByteOrder,
+    ) -> Result<AddressRange, RequestError> {
+        let request = WriteMultiple::from_string(start, value, register_count, order)?;
+        self.write_multiple_registers(param, request).await
+    }
+
+    /// Modify a single register on the server using an AND mask and an OR mask
+    pub async fn mask_write_register(
+        &mut self,
+        param: RequestParam,
+        request: MaskWriteRegister,
+    ) -> Result<MaskWriteRegister, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<MaskWriteRegister, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::MaskWriteRegister(SingleWrite::new(request, Promise::channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Write multiple contiguous registers and then read a range of holding registers in a single transaction
+    pub async fn read_write_multiple_registers(
+        &mut self,
+        param: RequestParam,
+        request: ReadWriteMultiple,
+    ) -> Result<Vec<Indexed<u16>>, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<Indexed<u16>>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReadWriteMultipleRegisters(ReadWriteMultipleRegisters::channel(
+                request, tx,
+            )),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the contents of a FIFO queue of registers from the server
+    pub async fn read_fifo_queue(
+        &mut self,
+        param: RequestParam,
+        address: u16,
+    ) -> Result<Vec<u16>, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<u16>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReadFifoQueue(ReadFifoQueue::new(address, Promise::channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read device identification objects from the server
+    ///
+    /// For the streaming access types, `object_id` is the first object to read (usually 0) and
+    /// additional requests are sent automatically until the server indicates that no more objects follow.
+    /// For [`ReadDeviceIdCode::Individual`], `object_id` is the single object to read.
+    pub async fn read_device_identification(
+        &mut self,
+        param: RequestParam,
+        code: ReadDeviceIdCode,
+        object_id: u8,
+    ) -> Result<DeviceIdentification, RequestError> {
+        let mut reply = self.read_device_id_once(param, code, object_id).await?;
+        let mut result = DeviceIdentification::new(reply.conformity_level);
+        loop {
+            result.objects.append(&mut reply.objects);
+            match reply.next_object_id {
+                None => return Ok(result),
+                Some(next) => {
+                    reply = self.read_device_id_once(param, code, next).await?;
+                }
+            }
+        }
+    }
+
+    async fn read_device_id_once(
+        &mut self,
+        param: RequestParam,
+        code: ReadDeviceIdCode,
+        object_id: u8,
+    ) -> Result<DeviceIdReply, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<DeviceIdReply, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReadDeviceIdentification(ReadDeviceIdentification::new(
+                code,
+                object_id,
+                Promise::channel(tx),
+            )),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the eight exception status outputs of a serial device
+    pub async fn read_exception_status(&mut self, param: RequestParam) -> Result<u8, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<u8, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReadExceptionStatus(ReadExceptionStatus::new(Promise::channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Send a value that a serial device must echo back to test the communication link
+    pub async fn return_query_data(
+        &mut self,
+        param: RequestParam,
+        data: u16,
+    ) -> Result<(), RequestError> {
+        self.diagnostics(
+            param,
+            DiagnosticsRequest::new(sub_function::RETURN_QUERY_DATA, data),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Restart the communications port of a serial device, clearing its counters and leaving listen only mode
+    ///
+    /// If `clear_log` is true, the communication event log is also cleared. No response is
+    /// returned by a device that is in listen only mode, so expect a timeout in this case.
+    pub async fn restart_communications(
+        &mut self,
+        param: RequestParam,
+        clear_log: bool,
+    ) -> Result<(), RequestError> {
+        let data = if clear_log { CLEAR_EVENT_LOG } else { 0 };
+        self.diagnostics(
+            param,
+            DiagnosticsRequest::new(sub_function::RESTART_COMMUNICATIONS, data),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Force a serial device into listen only mode
+    ///
+    /// The device never responds to this request, so it completes as soon as it has been sent.
+    /// Use [`Channel::restart_communications`] to leave listen only mode.
+    pub async fn force_listen_only_mode(
+        &mut self,
+        param: RequestParam,
+    ) -> Result<(), RequestError> {
+        self.diagnostics(
+            param,
+            DiagnosticsRequest::new(sub_function::FORCE_LISTEN_ONLY_MODE, 0),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Clear the diagnostic counters of a serial device
+    pub async fn clear_diagnostic_counters(
+        &mut self,
+        param: RequestParam,
+    ) -> Result<(), RequestError> {
+        self.diagnostics(
+            param,
+            DiagnosticsRequest::new(sub_function::CLEAR_COUNTERS, 0),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Read one of the diagnostic counters of a serial device
+    pub async fn read_diagnostic_counter(
+        &mut self,
+        param: RequestParam,
+        counter: DiagnosticCounter,
+    ) -> Result<u16, RequestError> {
+        self.diagnostics(param, DiagnosticsRequest::new(counter.sub_function(), 0))
+            .await
+    }
+
+    async fn diagnostics(
+        &mut self,
+        param: RequestParam,
+        request: DiagnosticsRequest,
+    ) -> Result<u16, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<u16, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::Diagnostics(Diagnostics::new(request, Promise::channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the status word and event counter of a serial device
+    pub async fn get_comm_event_counter(
+        &mut self,
+        param: RequestParam,
+    ) -> Result<CommEventCounter, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<CommEventCounter, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::GetCommEventCounter(GetCommEventCounter::new(Promise::channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the status word, counters and communication event log of a serial device
+    pub async fn get_comm_event_log(
+        &mut self,
+        param: RequestParam,
+    ) -> Result<CommEventLog, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<CommEventLog, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::GetCommEventLog(GetCommEventLog::new(Promise::channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read the device specific identification of a serial device
+    ///
+    /// The content is device specific but typically contains the server id, the run
+    /// indicator status and any additional data.
+    pub async fn report_server_id(&mut self, param: RequestParam) -> Result<Vec<u8>, RequestError> {
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<u8>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReportServerId(ReportServerId::new(Promise::channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Read one or more groups of records from files on the server
+    ///
+    /// All of the groups are read with a single request and the records are returned in the same order.
+    pub async fn read_file_records(
+        &mut self,
+        param: RequestParam,
+        records: Vec<FileRecord>,
+    ) -> Result<Vec<FileRecordData>, RequestError> {
+        ReadFileRecord::validate(&records)?;
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<FileRecordData>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::ReadFileRecord(ReadFileRecord::new(records, Promise::channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Write one or more groups of records to files on the server
+    ///
+    /// All of the groups are written with a single request.
+    pub async fn write_file_records(
+        &mut self,
+        param: RequestParam,
+        records: Vec<FileRecordData>,
+    ) -> Result<(), RequestError> {
+        WriteFileRecord::validate(&records)?;
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<(), RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::WriteFileRecord(WriteFileRecord::new(records, Promise::channel(tx))),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
+    /// Send a request using a function code that is not implemented by the library
+    ///
+    /// `data` contains the bytes that follow the function code and the bytes that follow
+    /// the function code in the response are returned. RTU channels must be spawned with the
+    /// length of the response to the function code.
+    pub async fn send_custom_function(
+        &mut self,
+        param: RequestParam,
+        function_code: u8,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, RequestError> {
+        let function = CustomFunction::validate(function_code, &data)?;
+        let (tx, rx) = tokio::sync::oneshot::channel::<Result<Vec<u8>, RequestError>>();
+        let request = wrap(
+            param,
+            RequestDetails::CustomFunction(CustomFunction::new(
+                function,
+                data,
+                Promise::channel(tx),
+            )),
+        );
+        self.tx.send(request).await?;
+        rx.await?
+    }
+
     /// Dynamically change the protocol decoding level of the channel
     pub async fn set_decode_level(&mut self, level: DecodeLevel) -> Result<(), Shutdown> {
         self.tx
@@ -348,6 +836,157 @@ impl CallbackSession {
         .await;
     }
 
+    /// Modify a single register on the server using an AND mask and an OR mask
+    pub async fn mask_write_register<C>(&mut self, value: MaskWriteRegister, callback: C)
+    where
+        C: FnOnce(Result<MaskWriteRegister, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::MaskWriteRegister(SingleWrite::new(value, Promise::new(callback))),
+        ))
+        .await;
+    }
+
+    /// Write multiple contiguous registers and then read a range of holding registers from the server
+    pub async fn read_write_multiple_registers<C>(&mut self, value: ReadWriteMultiple, callback: C)
+    where
+        C: FnOnce(Result<RegisterIterator, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::ReadWriteMultipleRegisters(ReadWriteMultipleRegisters::new(
+                value,
+                crate::client::requests::read_registers::Promise::new(callback),
+            )),
+        ))
+        .await;
+    }
+
+    /// Read the contents of a FIFO queue of registers from the server
+    pub async fn read_fifo_queue<C>(&mut self, address: u16, callback: C)
+    where
+        C: FnOnce(Result<Vec<u16>, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::ReadFifoQueue(ReadFifoQueue::new(address, Promise::new(callback))),
+        ))
+        .await;
+    }
+
+    /// Read the eight exception status outputs of a serial device
+    pub async fn read_exception_status<C>(&mut self, callback: C)
+    where
+        C: FnOnce(Result<u8, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::ReadExceptionStatus(ReadExceptionStatus::new(Promise::new(callback))),
+        ))
+        .await;
+    }
+
+    /// Read one of the diagnostic counters of a serial device
+    pub async fn read_diagnostic_counter<C>(&mut self, counter: DiagnosticCounter, callback: C)
+    where
+        C: FnOnce(Result<u16, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::Diagnostics(Diagnostics::new(
+                DiagnosticsRequest::new(counter.sub_function(), 0),
+                Promise::new(callback),
+            )),
+        ))
+        .await;
+    }
+
+    /// Read the status word and event counter of a serial device
+    pub async fn get_comm_event_counter<C>(&mut self, callback: C)
+    where
+        C: FnOnce(Result<CommEventCounter, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::GetCommEventCounter(GetCommEventCounter::new(Promise::new(callback))),
+        ))
+        .await;
+    }
+
+    /// Read the status word, counters and communication event log of a serial device
+    pub async fn get_comm_event_log<C>(&mut self, callback: C)
+    where
+        C: FnOnce(Result<CommEventLog, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::GetCommEventLog(GetCommEventLog::new(Promise::new(callback))),
+        ))
+        .await;
+    }
+
+    /// Read the device specific identification of a serial device
+    pub async fn report_server_id<C>(&mut self, callback: C)
+    where
+        C: FnOnce(Result<Vec<u8>, RequestError>) + Send + Sync + 'static,
+    {
+        self.send(wrap(
+            self.param,
+            RequestDetails::ReportServerId(ReportServerId::new(Promise::new(callback))),
+        ))
+        .await;
+    }
+
+    /// Read one or more groups of records from files on the server
+    pub async fn read_file_records<C>(&mut self, records: Vec<FileRecord>, callback: C)
+    where
+        C: FnOnce(Result<Vec<FileRecordData>, RequestError>) + Send + Sync + 'static,
+    {
+        let mut promise = Promise::new(callback);
+        if let Err(err) = ReadFileRecord::validate(&records) {
+            return promise.failure(err.into());
+        }
+        self.send(wrap(
+            self.param,
+            RequestDetails::ReadFileRecord(ReadFileRecord::new(records, promise)),
+        ))
+        .await;
+    }
+
+    /// Write one or more groups of records to files on the server
+    pub async fn write_file_records<C>(&mut self, records: Vec<FileRecordData>, callback: C)
+    where
+        C: FnOnce(Result<(), RequestError>) + Send + Sync + 'static,
+    {
+        let mut promise = Promise::new(callback);
+        if let Err(err) = WriteFileRecord::validate(&records) {
+            return promise.failure(err.into());
+        }
+        self.send(wrap(
+            self.param,
+            RequestDetails::WriteFileRecord(WriteFileRecord::new(records, promise)),
+        ))
+        .await;
+    }
+
+    /// Send a request using a function code that is not implemented by the library
+    pub async fn send_custom_function<C>(&mut self, function_code: u8, data: Vec<u8>, callback: C)
+    where
+        C: FnOnce(Result<Vec<u8>, RequestError>) + Send + Sync + 'static,
+    {
+        let mut promise = Promise::new(callback);
+        let function = match CustomFunction::validate(function_code, &data) {
+            Ok(x) => x,
+            Err(err) => return promise.failure(err.into()),
+        };
+        self.send(wrap(
+            self.param,
+            RequestDetails::CustomFunction(CustomFunction::new(function, data, promise)),
+        ))
+        .await;
+    }
+
     async fn read_bits<C, W>(&mut self, range: AddressRange, callback: C, wrap_req: W)
     where
         C: FnOnce(Result<BitIterator, RequestError>) + Send + Sync + 'static,
@@ -385,6 +1024,6 @@ impl CallbackSession {
     }
 }
 
-fn wrap(param: RequestParam, details: RequestDetails) -> Command {
+pub(crate) fn wrap(param: RequestParam, details: RequestDetails) -> Command {
     Command::Request(Request::new(param.id, param.response_timeout, details))
 }
@@ -0,0 +1,242 @@
+use crate::client::channel::{wrap, Channel, RequestParam};
+use crate::client::message::{Command, Promise, RequestDetails};
+use crate::client::requests::read_bits::ReadBits;
+use crate::client::requests::read_registers::ReadRegisters;
+use crate::client::requests::write_multiple::{MultipleWriteRequest, WriteMultiple};
+use crate::constants::limits;
+use crate::error::{ChunkError, RequestError};
+use crate::types::{AddressRange, Indexed};
+
+type Reply<T> = tokio::sync::oneshot::Sender<Result<T, RequestError>>;
+
+/// Controls how the requests used to process a split range are sent
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChunkMode {
+    /// Each request is queued after the response to the previous one is received.
+    /// Processing stops at the first request that fails.
+    Sequential,
+    /// All requests are queued at once so that the channel sends them back-to-back.
+    /// Every request is sent even if an earlier one fails.
+    Pipelined,
+}
+
+impl Channel {
+    /// Read coils from the server, splitting the range into as many requests as required
+    ///
+    /// The values of all the requests are returned in a single vector. If a request fails,
+    /// the returned error identifies it.
+    pub async fn read_coils_chunked(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+        mode: ChunkMode,
+    ) -> Result<Vec<Indexed<bool>>, ChunkError> {
+        let ranges = range.split(limits::MAX_READ_COILS_COUNT).collect();
+        let values = self
+            .execute_chunks(mode, ranges, |range, tx| {
+                let range = range.of_read_bits()?;
+                Ok(wrap(
+                    param,
+                    RequestDetails::ReadCoils(ReadBits::channel(range, tx)),
+                ))
+            })
+            .await?;
+        Ok(values.into_iter().flatten().collect())
+    }
+
+    /// Read discrete inputs from the server, splitting the range into as many requests as required
+    ///
+    /// See [`Channel::read_coils_chunked`] for how the results are combined.
+    pub async fn read_discrete_inputs_chunked(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+        mode: ChunkMode,
+    ) -> Result<Vec<Indexed<bool>>, ChunkError> {
+        let ranges = range.split(limits::MAX_READ_COILS_COUNT).collect();
+        let values = self
+            .execute_chunks(mode, ranges, |range, tx| {
+                let range = range.of_read_bits()?;
+                Ok(wrap(
+                    param,
+                    RequestDetails::ReadDiscreteInputs(ReadBits::channel(range, tx)),
+                ))
+            })
+            .await?;
+        Ok(values.into_iter().flatten().collect())
+    }
+
+    /// Read holding registers from the server, splitting the range into as many requests as required
+    ///
+    /// See [`Channel::read_coils_chunked`] for how the results are combined.
+    pub async fn read_holding_registers_chunked(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+        mode: ChunkMode,
+    ) -> Result<Vec<Indexed<u16>>, ChunkError> {
+        let ranges = range.split(limits::MAX_READ_REGISTERS_COUNT).collect();
+        let values = self
+            .execute_chunks(mode, ranges, |range, tx| {
+                let range = range.of_read_registers()?;
+                Ok(wrap(
+                    param,
+                    RequestDetails::ReadHoldingRegisters(ReadRegisters::channel(range, tx)),
+                ))
+            })
+            .await?;
+        Ok(values.into_iter().flatten().collect())
+    }
+
+    /// Read input registers from the server, splitting the range into as many requests as required
+    ///
+    /// See [`Channel::read_coils_chunked`] for how the results are combined.
+    pub async fn read_input_registers_chunked(
+        &mut self,
+        param: RequestParam,
+        range: AddressRange,
+        mode: ChunkMode,
+    ) -> Result<Vec<Indexed<u16>>, ChunkError> {
+        let ranges = range.split(limits::MAX_READ_REGISTERS_COUNT).collect();
+        let values = self
+            .execute_chunks(mode, ranges, |range, tx| {
+                let range = range.of_read_registers()?;
+                Ok(wrap(
+                    param,
+                    RequestDetails::ReadInputRegisters(ReadRegisters::channel(range, tx)),
+                ))
+            })
+            .await?;
+        Ok(values.into_iter().flatten().collect())
+    }
+
+    /// Write multiple coils on the server, splitting the values into as many requests as required
+    ///
+    /// If a request fails, the returned error identifies it. Requests that preceded it have
+    /// already been written.
+    pub async fn write_multiple_coils_chunked(
+        &mut self,
+        param: RequestParam,
+        request: WriteMultiple<bool>,
+        mode: ChunkMode,
+    ) -> Result<AddressRange, ChunkError> {
+        let ranges = request.range.split(limits::MAX_WRITE_COILS_COUNT).collect();
+        self.execute_chunks(mode, ranges, |range, tx| {
+            let values = Self::values_of(&request, range).to_vec();
+            Ok(wrap(
+                param,
+                RequestDetails::WriteMultipleCoils(MultipleWriteRequest::new(
+                    WriteMultiple::from(range.start, values)?,
+                    Promise::channel(tx),
+                )),
+            ))
+        })
+        .await?;
+        Ok(request.range)
+    }
+
+    /// Write multiple registers on the server, splitting the values into as many requests as required
+    ///
+    /// See [`Channel::write_multiple_coils_chunked`] for how failures are reported.
+    pub async fn write_multiple_registers_chunked(
+        &mut self,
+        param: RequestParam,
+        request: WriteMultiple<u16>,
+        mode: ChunkMode,
+    ) -> Result<AddressRange, ChunkError> {
+        let ranges = request
+            .range
+            .split(limits::MAX_WRITE_REGISTERS_COUNT)
+            .collect();
+        self.execute_chunks(mode, ranges, |range, tx| {
+            let values = Self::values_of(&request, range).to_vec();
+            Ok(wrap(
+                param,
+                RequestDetails::WriteMultipleRegisters(MultipleWriteRequest::new(
+                    WriteMultiple::from(range.start, values)?,
+                    Promise::channel(tx),
+                )),
+            ))
+        })
+        .await?;
+        Ok(request.range)
+    }
+
+    /// values of the request that are written to a sub-range of the request
+    fn values_of<T>(request: &WriteMultiple<T>, range: AddressRange) -> &[T] {
+        let offset = (range.start - request.range.start) as usize;
+        &request.values[offset..offset + range.count as usize]
+    }
+
+    async fn execute_chunks<T, F>(
+        &mut self,
+        mode: ChunkMode,
+        ranges: Vec<AddressRange>,
+        build: F,
+    ) -> Result<Vec<T>, ChunkError>
+    where
+        T: Send + 'static,
+        F: Fn(AddressRange, Reply<T>) -> Result<Command, RequestError>,
+    {
+        let fail = |index: usize, range: AddressRange, error: RequestError| ChunkError {
+            index,
+            range,
+            error,
+        };
+
+        let mut results = Vec::with_capacity(ranges.len());
+        match mode {
+            ChunkMode::Sequential => {
+                for (index, range) in ranges.into_iter().enumerate() {
+                    let (tx, rx) = tokio::sync::oneshot::channel::<Result<T, RequestError>>();
+                    let result = match build(range, tx) {
+                        Ok(request) => self.send_chunk(request, rx).await,
+                        Err(err) => Err(err),
+                    };
+                    results.push(result.map_err(|err| fail(index, range, err))?);
+                }
+            }
+            ChunkMode::Pipelined => {
+                let mut replies = Vec::with_capacity(ranges.len());
+                for (index, range) in ranges.into_iter().enumerate() {
+                    let (tx, rx) = tokio::sync::oneshot::channel::<Result<T, RequestError>>();
+                    let request = build(range, tx).map_err(|err| fail(index, range, err))?;
+                    self.tx
+                        .send(request)
+                        .await
+                        .map_err(|err| fail(index, range, err.into()))?;
+                    replies.push((range, rx));
+                }
+
+                // wait for every response, reporting the first request that failed
+                let mut first_error = None;
+                for (index, (range, rx)) in replies.into_iter().enumerate() {
+                    let result = match rx.await {
+                        Ok(x) => x,
+                        Err(err) => Err(err.into()),
+                    };
+                    match result {
+                        Ok(x) => results.push(x),
+                        Err(err) => {
+                            first_error.get_or_insert(fail(index, range, err));
+                        }
+                    }
+                }
+                if let Some(err) = first_error {
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    async fn send_chunk<T>(
+        &mut self,
+        request: Command,
+        rx: tokio::sync::oneshot::Receiver<Result<T, RequestError>>,
+    ) -> Result<T, RequestError> {
+        self.tx.send(request).await?;
+        rx.await?
+    }
+}
@@ -4,14 +4,26 @@ use crate::decode::AppDecodeLevel;
 use crate::error::AduParseError;
 use crate::error::*;
 use crate::exception::ExceptionCode;
+use crate::file_record::FileRecordDataDisplay;
 use crate::DecodeLevel;
 
+use crate::client::requests::custom_function::CustomFunction;
+use crate::client::requests::diagnostics::Diagnostics;
+use crate::client::requests::get_comm_event_counter::GetCommEventCounter;
+use crate::client::requests::get_comm_event_log::GetCommEventLog;
 use crate::client::requests::read_bits::ReadBits;
+use crate::client::requests::read_device_identification::ReadDeviceIdentification;
+use crate::client::requests::read_exception_status::ReadExceptionStatus;
+use crate::client::requests::read_fifo_queue::ReadFifoQueue;
+use crate::client::requests::read_file_record::ReadFileRecord;
 use crate::client::requests::read_registers::ReadRegisters;
+use crate::client::requests::read_write_multiple::ReadWriteMultipleRegisters;
+use crate::client::requests::report_server_id::ReportServerId;
+use crate::client::requests::write_file_record::WriteFileRecord;
 use crate::client::requests::write_multiple::MultipleWriteRequest;
 use crate::client::requests::write_single::SingleWrite;
 use crate::common::traits::Serialize;
-use crate::types::{Indexed, UnitId};
+use crate::types::{CustomFunctionDisplay, Indexed, MaskWriteRegister, UnitId};
 
 use scursor::{ReadCursor, WriteCursor};
 use std::time::Duration;
@@ -43,8 +55,20 @@ pub(crate) enum RequestDetails {
     ReadInputRegisters(ReadRegisters),
     WriteSingleCoil(SingleWrite<Indexed<bool>>),
     WriteSingleRegister(SingleWrite<Indexed<u16>>),
+    ReadExceptionStatus(ReadExceptionStatus),
+    Diagnostics(Diagnostics),
+    GetCommEventCounter(GetCommEventCounter),
+    GetCommEventLog(GetCommEventLog),
     WriteMultipleCoils(MultipleWriteRequest<bool>),
     WriteMultipleRegisters(MultipleWriteRequest<u16>),
+    ReportServerId(ReportServerId),
+    ReadFileRecord(ReadFileRecord),
+    WriteFileRecord(WriteFileRecord),
+    MaskWriteRegister(SingleWrite<MaskWriteRegister>),
+    ReadWriteMultipleRegisters(ReadWriteMultipleRegisters),
+    ReadFifoQueue(ReadFifoQueue),
+    ReadDeviceIdentification(ReadDeviceIdentification),
+    CustomFunction(CustomFunction),
 }
 
 impl Request {
@@ -127,8 +151,37 @@ impl RequestDetails {
             RequestDetails::ReadInputRegisters(_) => FunctionCode::ReadInputRegisters,
             RequestDetails::WriteSingleCoil(_) => FunctionCode::WriteSingleCoil,
             RequestDetails::WriteSingleRegister(_) => FunctionCode::WriteSingleRegister,
+            RequestDetails::ReadExceptionStatus(_) => FunctionCode::ReadExceptionStatus,
+            RequestDetails::Diagnostics(_) => FunctionCode::Diagnostics,
+            RequestDetails::GetCommEventCounter(_) => FunctionCode::GetCommEventCounter,
+            RequestDetails::GetCommEventLog(_) => FunctionCode::GetCommEventLog,
             RequestDetails::WriteMultipleCoils(_) => FunctionCode::WriteMultipleCoils,
             RequestDetails::WriteMultipleRegisters(_) => FunctionCode::WriteMultipleRegisters,
+            RequestDetails::ReportServerId(_) => FunctionCode::ReportServerId,
+            RequestDetails::ReadFileRecord(_) => FunctionCode::ReadFileRecord,
+            RequestDetails::WriteFileRecord(_) => FunctionCode::WriteFileRecord,
+            RequestDetails::MaskWriteRegister(_) => FunctionCode::MaskWriteRegister,
+            RequestDetails::ReadWriteMultipleRegisters(_) => {
+                FunctionCode::ReadWriteMultipleRegisters
+            }
+            RequestDetails::ReadFifoQueue(_) => FunctionCode::ReadFifoQueue,
+            RequestDetails::ReadDeviceIdentification(_) => FunctionCode::ReadDeviceIdentification,
+            RequestDetails::CustomFunction(x) => x.function,
+        }
+    }
+
+    /// true unless the server never responds to the request, e.g. when forcing listen only mode
+    pub(crate) fn expects_response(&self) -> bool {
+        match self {
+            RequestDetails::Diagnostics(x) => x.expects_response(),
+            _ => true,
+        }
+    }
+
+    /// complete a request to which the server never responds after it has been sent
+    pub(crate) fn complete_without_response(&mut self) {
+        if let RequestDetails::Diagnostics(x) = self {
+            x.complete_without_response()
         }
     }
 
@@ -140,8 +193,20 @@ impl RequestDetails {
             RequestDetails::ReadInputRegisters(x) => x.failure(err),
             RequestDetails::WriteSingleCoil(x) => x.failure(err),
             RequestDetails::WriteSingleRegister(x) => x.failure(err),
+            RequestDetails::ReadExceptionStatus(x) => x.failure(err),
+            RequestDetails::Diagnostics(x) => x.failure(err),
+            RequestDetails::GetCommEventCounter(x) => x.failure(err),
+            RequestDetails::GetCommEventLog(x) => x.failure(err),
             RequestDetails::WriteMultipleCoils(x) => x.failure(err),
             RequestDetails::WriteMultipleRegisters(x) => x.failure(err),
+            RequestDetails::ReportServerId(x) => x.failure(err),
+            RequestDetails::ReadFileRecord(x) => x.failure(err),
+            RequestDetails::WriteFileRecord(x) => x.failure(err),
+            RequestDetails::MaskWriteRegister(x) => x.failure(err),
+            RequestDetails::ReadWriteMultipleRegisters(x) => x.failure(err),
+            RequestDetails::ReadFifoQueue(x) => x.failure(err),
+            RequestDetails::ReadDeviceIdentification(x) => x.failure(err),
+            RequestDetails::CustomFunction(x) => x.failure(err),
         }
     }
 
@@ -158,10 +223,26 @@ impl RequestDetails {
             RequestDetails::ReadInputRegisters(x) => x.handle_response(cursor, function, decode),
             RequestDetails::WriteSingleCoil(x) => x.handle_response(cursor, function, decode),
             RequestDetails::WriteSingleRegister(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::ReadExceptionStatus(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::Diagnostics(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::GetCommEventCounter(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::GetCommEventLog(x) => x.handle_response(cursor, function, decode),
             RequestDetails::WriteMultipleCoils(x) => x.handle_response(cursor, function, decode),
             RequestDetails::WriteMultipleRegisters(x) => {
                 x.handle_response(cursor, function, decode)
             }
+            RequestDetails::ReportServerId(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::ReadFileRecord(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::WriteFileRecord(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::MaskWriteRegister(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::ReadWriteMultipleRegisters(x) => {
+                x.handle_response(cursor, function, decode)
+            }
+            RequestDetails::ReadFifoQueue(x) => x.handle_response(cursor, function, decode),
+            RequestDetails::ReadDeviceIdentification(x) => {
+                x.handle_response(cursor, function, decode)
+            }
+            RequestDetails::CustomFunction(x) => x.handle_response(cursor, function, decode),
         }
     }
 }
@@ -175,8 +256,20 @@ impl Serialize for RequestDetails {
             RequestDetails::ReadInputRegisters(x) => x.serialize(cursor),
             RequestDetails::WriteSingleCoil(x) => x.serialize(cursor),
             RequestDetails::WriteSingleRegister(x) => x.serialize(cursor),
+            RequestDetails::ReadExceptionStatus(x) => x.serialize(cursor),
+            RequestDetails::Diagnostics(x) => x.serialize(cursor),
+            RequestDetails::GetCommEventCounter(x) => x.serialize(cursor),
+            RequestDetails::GetCommEventLog(x) => x.serialize(cursor),
             RequestDetails::WriteMultipleCoils(x) => x.serialize(cursor),
             RequestDetails::WriteMultipleRegisters(x) => x.serialize(cursor),
+            RequestDetails::ReportServerId(x) => x.serialize(cursor),
+            RequestDetails::ReadFileRecord(x) => x.serialize(cursor),
+            RequestDetails::WriteFileRecord(x) => x.serialize(cursor),
+            RequestDetails::MaskWriteRegister(x) => x.serialize(cursor),
+            RequestDetails::ReadWriteMultipleRegisters(x) => x.serialize(cursor),
+            RequestDetails::ReadFifoQueue(x) => x.serialize(cursor),
+            RequestDetails::ReadDeviceIdentification(x) => x.serialize(cursor),
+            RequestDetails::CustomFunction(x) => x.serialize(cursor),
         }
     }
 }
@@ -225,6 +318,26 @@ impl std::fmt::Display for RequestDetailsDisplay<'_> {
                 RequestDetails::WriteSingleRegister(details) => {
                     write!(f, "{}", details.request)?;
                 }
+                RequestDetails::ReadExceptionStatus(_) => {}
+                RequestDetails::Diagnostics(details) => {
+                    write!(f, "{}", details.request)?;
+                }
+                RequestDetails::GetCommEventCounter(_) => {}
+                RequestDetails::GetCommEventLog(_) => {}
+                RequestDetails::ReportServerId(_) => {}
+                RequestDetails::ReadFileRecord(details) => {
+                    write!(f, "count: {}", details.records.len())?;
+                    for x in &details.records {
+                        write!(f, "\n{}", x)?;
+                    }
+                }
+                RequestDetails::WriteFileRecord(details) => {
+                    write!(
+                        f,
+                        "{}",
+                        FileRecordDataDisplay::new(self.level, &details.records)
+                    )?;
+                }
                 RequestDetails::WriteMultipleCoils(details) => {
                     write!(f, "{}", details.request.range)?;
                     if self.level.data_values() {
@@ -241,6 +354,39 @@ impl std::fmt::Display for RequestDetailsDisplay<'_> {
                         }
                     }
                 }
+                RequestDetails::MaskWriteRegister(details) => {
+                    write!(f, "{}", details.request)?;
+                }
+                RequestDetails::ReadWriteMultipleRegisters(details) => {
+                    write!(
+                        f,
+                        "read: {} write: {}",
+                        details.request.read_range.get(),
+                        details.request.write.range
+                    )?;
+                    if self.level.data_values() {
+                        for x in details.request.write.iter() {
+                            write!(f, "\n{}", x)?;
+                        }
+                    }
+                }
+                RequestDetails::ReadFifoQueue(details) => {
+                    write!(f, "ptr: {:#06X}", details.address)?;
+                }
+                RequestDetails::ReadDeviceIdentification(details) => {
+                    write!(
+                        f,
+                        "code: {} object: {:#04X}",
+                        details.code, details.object_id
+                    )?;
+                }
+                RequestDetails::CustomFunction(details) => {
+                    write!(
+                        f,
+                        "{}",
+                        CustomFunctionDisplay::new(self.level, &details.data)
+                    )?;
+                }
             }
         }
 
@@ -4,13 +4,18 @@ use crate::decode::DecodeLevel;
 
 /// persistent communication channel such as a TCP connection
 pub(crate) mod channel;
+pub(crate) mod chunked;
 pub(crate) mod listener;
 pub(crate) mod message;
+pub(crate) mod poller;
 pub(crate) mod requests;
 pub(crate) mod task;
 
 pub use crate::client::channel::*;
+pub use crate::client::chunked::*;
 pub use crate::client::listener::*;
+pub use crate::client::poller::*;
+pub use crate::client::requests::read_write_multiple::ReadWriteMultiple;
 pub use crate::client::requests::write_multiple::WriteMultiple;
 pub use crate::retry::*;
 
@@ -64,6 +69,29 @@ impl HostAddr {
         }
     }
 
+    /// open a UDP socket that sends datagrams to and only receives datagrams from the host
+    pub(crate) async fn connect_udp(&self) -> std::io::Result<tokio::net::UdpSocket> {
+        let addr = match &self.addr {
+            HostType::Dns(x) => tokio::net::lookup_host((x.as_str(), self.port))
+                .await?
+                .next()
+                .ok_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("unable to resolve {}", x),
+                    )
+                })?,
+            HostType::IpAddr(x) => SocketAddr::new(*x, self.port),
+        };
+        let local: SocketAddr = match addr {
+            SocketAddr::V4(_) => (std::net::Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (std::net::Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = tokio::net::UdpSocket::bind(local).await?;
+        socket.connect(addr).await?;
+        Ok(socket)
+    }
+
     pub(crate) async fn connect(&self) -> std::io::Result<tokio::net::TcpStream> {
         match &self.addr {
             HostType::Dns(x) => tokio::net::TcpStream::connect((x.as_str(), self.port)).await,
@@ -95,6 +123,113 @@ pub fn spawn_tcp_client_task(
         host,
         max_queued_requests,
         retry,
+        crate::common::frame::TcpFraming::Mbap,
+        1,
+        decode,
+        listener.unwrap_or_else(|| NullListener::create()),
+    )
+}
+
+/// Spawns a channel task onto the runtime that maintains a TCP connection and processes
+/// requests, allowing multiple requests to wait for a response at the same time. The task
+/// completes when the returned channel handle is dropped.
+///
+/// Requests are sent as soon as they are queued until `max_in_flight` requests are waiting
+/// for a response. Responses are matched to requests using the transaction id of the MBAP
+/// header, so the server may answer them in any order. Each request times out independently
+/// and a response that doesn't match a pending request is logged and discarded.
+///
+/// Some devices discard requests that arrive before the previous one was answered. Use
+/// [`spawn_tcp_client_task`] to communicate with them.
+///
+/// * `host` - Address/port of the remote server. Can be a IP address or name on which to perform DNS resolution.
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `max_in_flight` - The maximum number of requests waiting for a response
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `decode` - Decode log level
+/// * `listener` - Optional callback to monitor the TCP connection state
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+pub fn spawn_pipelined_tcp_client_task(
+    host: HostAddr,
+    max_queued_requests: usize,
+    max_in_flight: usize,
+    retry: Box<dyn RetryStrategy>,
+    decode: DecodeLevel,
+    listener: Option<Box<dyn Listener<ClientState>>>,
+) -> Channel {
+    crate::tcp::client::spawn_tcp_channel(
+        host,
+        max_queued_requests,
+        retry,
+        crate::common::frame::TcpFraming::Mbap,
+        max_in_flight,
+        decode,
+        listener.unwrap_or_else(|| NullListener::create()),
+    )
+}
+
+/// Spawns a channel task onto the runtime that exchanges requests and responses with a server
+/// using Modbus over UDP. The task completes when the returned channel handle is dropped.
+///
+/// Each request is sent in a single datagram using the MBAP header and responses are matched
+/// to requests using the transaction id. Since datagrams may be lost, a request is sent again
+/// when no response is received within the response timeout, so a request that is never
+/// answered fails after `1 + retransmissions` times the response timeout.
+///
+/// * `host` - Address/port of the remote server. Can be a IP address or name on which to perform DNS resolution.
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when a socket is opened again after a failure
+/// * `retransmissions` - Number of times a request is sent again when no response is received
+/// * `decode` - Decode log level
+/// * `listener` - Optional callback to monitor the state of the socket
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+pub fn spawn_udp_client_task(
+    host: HostAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn RetryStrategy>,
+    retransmissions: usize,
+    decode: DecodeLevel,
+    listener: Option<Box<dyn Listener<ClientState>>>,
+) -> Channel {
+    crate::udp::client::spawn_udp_channel(
+        host,
+        max_queued_requests,
+        retry,
+        retransmissions,
+        decode,
+        listener.unwrap_or_else(|| NullListener::create()),
+    )
+}
+
+/// Spawns a channel task onto the runtime that maintains a TCP connection and processes
+/// requests using RTU framing. The task completes when the returned channel handle is dropped.
+///
+/// Each request is sent as an RTU frame including its CRC instead of using the MBAP header.
+/// This is the framing used by serial-to-Ethernet converters in "transparent" mode.
+///
+/// * `host` - Address/port of the remote server. Can be a IP address or name on which to perform DNS resolution.
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `decode` - Decode log level
+/// * `listener` - Optional callback to monitor the TCP connection state
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(feature = "serial")]
+pub fn spawn_rtu_over_tcp_client_task(
+    host: HostAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn RetryStrategy>,
+    decode: DecodeLevel,
+    listener: Option<Box<dyn Listener<ClientState>>>,
+) -> Channel {
+    crate::tcp::client::spawn_tcp_channel(
+        host,
+        max_queued_requests,
+        retry,
+        crate::common::frame::TcpFraming::Rtu,
+        1,
         decode,
         listener.unwrap_or_else(|| NullListener::create()),
     )
@@ -123,8 +258,77 @@ pub fn spawn_rtu_client_task(
     retry: Box<dyn RetryStrategy>,
     decode: DecodeLevel,
     listener: Option<Box<dyn Listener<PortState>>>,
+) -> Channel {
+    spawn_rtu_client_task_with_custom_functions(
+        path,
+        serial_settings,
+        max_queued_requests,
+        retry,
+        crate::serial::CustomFunctionLengths::default(),
+        decode,
+        listener,
+    )
+}
+
+/// Spawns a channel task onto the runtime that opens a serial port and processes
+/// requests, including requests that use custom function codes. The task completes
+/// when the returned channel handle is dropped.
+///
+/// * `path` - Path to the serial device. Generally `/dev/tty0` on Linux and `COM1` on Windows.
+/// * `serial_settings` = Serial port settings
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when opening the serial port is retried on failure
+/// * `custom` - Lengths of the responses to custom function codes
+/// * `decode` - Decode log level
+/// * `listener` - Optional callback to monitor the state of the serial port
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(feature = "serial")]
+pub fn spawn_rtu_client_task_with_custom_functions(
+    path: &str,
+    serial_settings: crate::serial::SerialSettings,
+    max_queued_requests: usize,
+    retry: Box<dyn RetryStrategy>,
+    custom: crate::serial::CustomFunctionLengths,
+    decode: DecodeLevel,
+    listener: Option<Box<dyn Listener<PortState>>>,
 ) -> Channel {
     Channel::spawn_rtu(
+        path,
+        serial_settings,
+        max_queued_requests,
+        retry,
+        custom,
+        decode,
+        listener,
+    )
+}
+
+/// Spawns a channel task onto the runtime that opens a serial port and processes
+/// requests using Modbus ASCII framing. The task completes when the returned channel handle
+/// is dropped.
+///
+/// The channel uses the provided [`RetryStrategy`] to pause between failed attempts to open the
+/// serial port or after the serial port fails.
+///
+/// * `path` - Path to the serial device. Generally `/dev/tty0` on Linux and `COM1` on Windows.
+/// * `serial_settings` = Serial port settings
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when opening the serial port is retried on failure
+/// * `decode` - Decode log level
+/// * `listener` - Optional callback to monitor the state of the serial port
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(feature = "serial")]
+pub fn spawn_ascii_client_task(
+    path: &str,
+    serial_settings: crate::serial::SerialSettings,
+    max_queued_requests: usize,
+    retry: Box<dyn RetryStrategy>,
+    decode: DecodeLevel,
+    listener: Option<Box<dyn Listener<PortState>>>,
+) -> Channel {
+    Channel::spawn_ascii(
         path,
         serial_settings,
         max_queued_requests,
@@ -162,6 +366,80 @@ pub fn spawn_tls_client_task(
         max_queued_requests,
         retry,
         tls_config,
+        crate::common::frame::TcpFraming::Mbap,
+        1,
+        decode,
+        listener.unwrap_or_else(|| NullListener::create()),
+    )
+}
+
+/// Spawns a channel task onto the runtime that maintains a TLS connection and processes
+/// requests, allowing multiple requests to wait for a response at the same time. The task
+/// completes when the returned channel handle is dropped.
+///
+/// See [`spawn_pipelined_tcp_client_task`] for how requests are processed.
+///
+/// * `host` - Address/port of the remote server. Can be a IP address or name on which to perform DNS resolution.
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `max_in_flight` - The maximum number of requests waiting for a response
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `tls_config` - TLS configuration
+/// * `decode` - Decode log level
+/// * `listener` - Optional callback to monitor the TLS connection state
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(feature = "tls")]
+pub fn spawn_pipelined_tls_client_task(
+    host: HostAddr,
+    max_queued_requests: usize,
+    max_in_flight: usize,
+    retry: Box<dyn RetryStrategy>,
+    tls_config: TlsClientConfig,
+    decode: DecodeLevel,
+    listener: Option<Box<dyn Listener<ClientState>>>,
+) -> Channel {
+    spawn_tls_channel(
+        host,
+        max_queued_requests,
+        retry,
+        tls_config,
+        crate::common::frame::TcpFraming::Mbap,
+        max_in_flight,
+        decode,
+        listener.unwrap_or_else(|| NullListener::create()),
+    )
+}
+
+/// Spawns a channel task onto the runtime that maintains a TLS connection and processes
+/// requests using RTU framing. The task completes when the returned channel handle
+/// is dropped.
+///
+/// See [`spawn_rtu_over_tcp_client_task`] for a description of the framing.
+///
+/// * `host` - Address/port of the remote server. Can be a IP address or name on which to perform DNS resolution.
+/// * `max_queued_requests` - The maximum size of the request queue
+/// * `retry` - A boxed trait object that controls when the connection is retried on failure
+/// * `tls_config` - TLS configuration
+/// * `decode` - Decode log level
+/// * `listener` - Optional callback to monitor the TLS connection state
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(all(feature = "tls", feature = "serial"))]
+pub fn spawn_rtu_over_tls_client_task(
+    host: HostAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn RetryStrategy>,
+    tls_config: TlsClientConfig,
+    decode: DecodeLevel,
+    listener: Option<Box<dyn Listener<ClientState>>>,
+) -> Channel {
+    spawn_tls_channel(
+        host,
+        max_queued_requests,
+        retry,
+        tls_config,
+        crate::common::frame::TcpFraming::Rtu,
+        1,
         decode,
         listener.unwrap_or_else(|| NullListener::create()),
     )
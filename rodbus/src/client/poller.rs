@@ -0,0 +1,605 @@
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+
+use crate::client::channel::{Channel, RequestParam};
+use crate::client::listener::{ClientState, Listener};
+use crate::constants::limits;
+use crate::error::{InvalidRange, RequestError, Shutdown};
+use crate::types::{AddressRange, Indexed, UnitId};
+use crate::MaybeAsync;
+
+/// Type of the values read by a scan
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScanType {
+    /// Read coils
+    Coils,
+    /// Read discrete inputs
+    DiscreteInputs,
+    /// Read holding registers
+    HoldingRegisters,
+    /// Read input registers
+    InputRegisters,
+}
+
+impl ScanType {
+    /// maximum number of values that can be read in a single request
+    fn max_count(self) -> u16 {
+        match self {
+            ScanType::Coils | ScanType::DiscreteInputs => limits::MAX_READ_COILS_COUNT,
+            ScanType::HoldingRegisters | ScanType::InputRegisters => {
+                limits::MAX_READ_REGISTERS_COUNT
+            }
+        }
+    }
+}
+
+/// Values read by a scan
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScanValues {
+    /// Values of coils or discrete inputs
+    Bits(Vec<Indexed<bool>>),
+    /// Values of holding or input registers
+    Registers(Vec<Indexed<u16>>),
+}
+
+impl ScanValues {
+    /// values of a sub-range of the values that were read
+    fn slice(&self, read: AddressRange, range: AddressRange) -> Self {
+        let offset = (range.start - read.start) as usize;
+        let span = offset..offset + range.count as usize;
+        match self {
+            ScanValues::Bits(x) => ScanValues::Bits(x[span].to_vec()),
+            ScanValues::Registers(x) => ScanValues::Registers(x[span].to_vec()),
+        }
+    }
+}
+
+/// Identifies a scan group added to a [`Poller`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScanGroupId(usize);
+
+/// Result of reading one of the ranges of a scan group
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScanResult {
+    /// Group that contains the range
+    pub group: ScanGroupId,
+    /// Unit that was read
+    pub unit_id: UnitId,
+    /// Type of the values
+    pub scan_type: ScanType,
+    /// Range as it was added to the group
+    pub range: AddressRange,
+    /// Values of the range or the error that prevented reading them
+    pub result: Result<ScanValues, RequestError>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct ScanRange {
+    unit_id: UnitId,
+    scan_type: ScanType,
+    range: AddressRange,
+}
+
+/// Ranges that are read periodically at the same rate
+#[derive(Clone, Debug)]
+pub struct ScanGroup {
+    period: Duration,
+    priority: u8,
+    ranges: Vec<ScanRange>,
+}
+
+impl ScanGroup {
+    /// Create an empty group that is read every `period`
+    ///
+    /// When several groups are due at the same time, the groups with a higher `priority`
+    /// are read first.
+    pub fn new(period: Duration, priority: u8) -> Self {
+        Self {
+            period,
+            priority,
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Add a range of values to read from a unit
+    ///
+    /// Adjacent and overlapping ranges of the same unit and type are read using a single
+    /// request when they fit, but a result is still reported for each range that was added.
+    pub fn add(
+        &mut self,
+        unit_id: UnitId,
+        scan_type: ScanType,
+        range: AddressRange,
+    ) -> Result<(), InvalidRange> {
+        let range = range.limited_count(scan_type.max_count())?;
+        self.ranges.push(ScanRange {
+            unit_id,
+            scan_type,
+            range,
+        });
+        Ok(())
+    }
+}
+
+/// Settings shared by all the scan groups of a [`Poller`]
+#[derive(Copy, Clone, Debug)]
+pub struct PollerConfig {
+    /// Response timeout of each request
+    pub response_timeout: Duration,
+    /// Maximum random delay added to each scan so that groups with the same period
+    /// do not all fall due at the same instant
+    pub max_jitter: Duration,
+}
+
+impl PollerConfig {
+    /// Create a new configuration
+    pub fn new(response_timeout: Duration, max_jitter: Duration) -> Self {
+        Self {
+            response_timeout,
+            max_jitter,
+        }
+    }
+}
+
+/// Periodically reads the ranges of one or more [`ScanGroup`] using a [`Channel`]
+///
+/// Groups are read from a task spawned by [`Poller::spawn`] or [`Poller::spawn_stream`].
+/// When a listener created by [`Poller::client_listener`] is passed to the channel,
+/// scans are skipped while the channel is not connected and every group is read as soon
+/// as the connection is established.
+pub struct Poller {
+    config: PollerConfig,
+    groups: Vec<ScanGroup>,
+    state: Option<watch::Receiver<ClientState>>,
+}
+
+impl Poller {
+    /// Create a poller without any scan groups
+    pub fn new(config: PollerConfig) -> Self {
+        Self {
+            config,
+            groups: Vec::new(),
+            state: None,
+        }
+    }
+
+    /// Add a group of ranges to read
+    pub fn add_group(&mut self, group: ScanGroup) -> ScanGroupId {
+        self.groups.push(group);
+        ScanGroupId(self.groups.len() - 1)
+    }
+
+    /// Create the listener to pass when spawning the channel so that the poller
+    /// tracks the state of the connection
+    ///
+    /// The state is forwarded to `inner` if one is provided.
+    pub fn client_listener(
+        &mut self,
+        inner: Option<Box<dyn Listener<ClientState>>>,
+    ) -> Box<dyn Listener<ClientState>> {
+        let (tx, rx) = watch::channel(ClientState::Disabled);
+        self.state = Some(rx);
+        Box::new(StateListener { tx, inner })
+    }
+
+    /// Spawn a task that reads the groups using the channel and informs the listener
+    /// of each result. The task completes when the returned handle is dropped.
+    ///
+    /// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+    pub fn spawn(self, channel: Channel, listener: Box<dyn Listener<ScanResult>>) -> PollerHandle {
+        let (tx, rx) = mpsc::channel(16);
+        let task = PollerTask::new(self, channel, listener, rx);
+        tokio::spawn(task.run());
+        PollerHandle { tx }
+    }
+
+    /// Spawn a task that reads the groups using the channel and sends each result to the
+    /// returned receiver, buffering up to `capacity` results. The task completes when the
+    /// returned handle is dropped.
+    ///
+    /// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+    pub fn spawn_stream(
+        self,
+        channel: Channel,
+        capacity: usize,
+    ) -> (PollerHandle, mpsc::Receiver<ScanResult>) {
+        let (tx, rx) = mpsc::channel(capacity);
+        let handle = self.spawn(channel, Box::new(StreamListener { tx }));
+        (handle, rx)
+    }
+}
+
+/// Handle used to control the task spawned by a [`Poller`]
+///
+/// The task completes when the handle is dropped.
+#[derive(Debug)]
+pub struct PollerHandle {
+    tx: mpsc::Sender<ScanGroupId>,
+}
+
+impl PollerHandle {
+    /// Read a group as soon as possible instead of waiting for its period to elapse
+    pub async fn scan_now(&self, group: ScanGroupId) -> Result<(), Shutdown> {
+        self.tx.send(group).await.map_err(|_| Shutdown)
+    }
+}
+
+struct StateListener {
+    tx: watch::Sender<ClientState>,
+    inner: Option<Box<dyn Listener<ClientState>>>,
+}
+
+impl Listener<ClientState> for StateListener {
+    fn update(&mut self, value: ClientState) -> MaybeAsync<()> {
+        // the poller may already have been dropped
+        let _ = self.tx.send(value);
+        match &mut self.inner {
+            Some(x) => x.update(value),
+            None => MaybeAsync::ready(()),
+        }
+    }
+}
+
+struct StreamListener {
+    tx: mpsc::Sender<ScanResult>,
+}
+
+impl Listener<ScanResult> for StreamListener {
+    fn update(&mut self, value: ScanResult) -> MaybeAsync<()> {
+        let tx = self.tx.clone();
+        MaybeAsync::asynchronous(async move {
+            // the receiver may already have been dropped
+            let _ = tx.send(value).await;
+        })
+    }
+}
+
+/// Single request that reads one or more of the ranges of a group
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct MergedRead {
+    unit_id: UnitId,
+    scan_type: ScanType,
+    range: AddressRange,
+    members: Vec<AddressRange>,
+}
+
+/// combine adjacent and overlapping ranges of the same unit and type into as few
+/// requests as possible without exceeding the maximum count of each request
+fn merge(ranges: &[ScanRange]) -> Vec<MergedRead> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_by_key(|x| (x.unit_id, x.scan_type, x.range.start));
+
+    let mut reads: Vec<MergedRead> = Vec::new();
+    for x in sorted {
+        let start = x.range.start as u32;
+        let end = start + x.range.count as u32;
+        if let Some(last) = reads.last_mut() {
+            let last_start = last.range.start as u32;
+            let last_end = last_start + last.range.count as u32;
+            let merged_end = std::cmp::max(end, last_end);
+            if last.unit_id == x.unit_id
+                && last.scan_type == x.scan_type
+                && start <= last_end
+                && merged_end - last_start <= x.scan_type.max_count() as u32
+            {
+                last.range.count = (merged_end - last_start) as u16;
+                last.members.push(x.range);
+                continue;
+            }
+        }
+        reads.push(MergedRead {
+            unit_id: x.unit_id,
+            scan_type: x.scan_type,
+            range: x.range,
+            members: vec![x.range],
+        });
+    }
+    reads
+}
+
+/// pseudo-random delays in the range [0, max]
+struct Jitter {
+    max: Duration,
+    state: u64,
+}
+
+impl Jitter {
+    fn new(max: Duration) -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|x| x.as_nanos() as u64)
+            .unwrap_or_default();
+        Self {
+            max,
+            // xorshift requires a non-zero state
+            state: seed | 1,
+        }
+    }
+
+    fn next(&mut self) -> Duration {
+        if self.max.is_zero() {
+            return Duration::ZERO;
+        }
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        let max = self.max.as_nanos().min(u64::MAX as u128) as u64;
+        Duration::from_nanos(self.state % max.saturating_add(1))
+    }
+}
+
+struct GroupState {
+    period: Duration,
+    priority: u8,
+    reads: Vec<MergedRead>,
+    /// when the group is due, excluding the jitter
+    base: Instant,
+    /// when the group is due, including the jitter
+    next: Instant,
+}
+
+struct PollerTask {
+    channel: Channel,
+    response_timeout: Duration,
+    jitter: Jitter,
+    groups: Vec<GroupState>,
+    state: Option<watch::Receiver<ClientState>>,
+    listener: Box<dyn Listener<ScanResult>>,
+    commands: mpsc::Receiver<ScanGroupId>,
+}
+
+impl PollerTask {
+    fn new(
+        poller: Poller,
+        channel: Channel,
+        listener: Box<dyn Listener<ScanResult>>,
+        commands: mpsc::Receiver<ScanGroupId>,
+    ) -> Self {
+        let now = Instant::now();
+        let mut jitter = Jitter::new(poller.config.max_jitter);
+        let groups = poller
+            .groups
+            .iter()
+            .map(|group| GroupState {
+                period: group.period,
+                priority: group.priority,
+                reads: merge(&group.ranges),
+                base: now,
+                next: now + jitter.next(),
+            })
+            .collect();
+
+        Self {
+            channel,
+            response_timeout: poller.config.response_timeout,
+            jitter,
+            groups,
+            state: poller.state,
+            listener,
+            commands,
+        }
+    }
+
+    async fn run(mut self) {
+        loop {
+            let next = self.groups.iter().map(|x| x.next).min();
+            tokio::select! {
+                command = self.commands.recv() => {
+                    match command {
+                        Some(id) => self.demand(id),
+                        // the handle was dropped
+                        None => return,
+                    }
+                }
+                _ = Self::state_changed(&mut self.state) => {
+                    if self.is_connected() {
+                        for index in 0..self.groups.len() {
+                            self.demand(ScanGroupId(index));
+                        }
+                    }
+                }
+                _ = Self::sleep_until(next) => {
+                    self.scan_due_groups().await;
+                }
+            }
+        }
+    }
+
+    async fn state_changed(state: &mut Option<watch::Receiver<ClientState>>) {
+        if let Some(rx) = state {
+            if rx.changed().await.is_ok() {
+                return;
+            }
+        }
+        // not tracking the state or the channel was dropped
+        std::future::pending().await
+    }
+
+    async fn sleep_until(next: Option<Instant>) {
+        match next {
+            Some(x) => tokio::time::sleep_until(x).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        match &self.state {
+            Some(x) => *x.borrow() == ClientState::Connected,
+            None => true,
+        }
+    }
+
+    fn demand(&mut self, id: ScanGroupId) {
+        let now = Instant::now();
+        if let Some(group) = self.groups.get_mut(id.0) {
+            group.base = now;
+            group.next = now;
+        }
+    }
+
+    async fn scan_due_groups(&mut self) {
+        let now = Instant::now();
+        let mut due: Vec<usize> = (0..self.groups.len())
+            .filter(|x| self.groups[*x].next <= now)
+            .collect();
+        due.sort_by_key(|x| {
+            let group = &self.groups[*x];
+            (std::cmp::Reverse(group.priority), group.next)
+        });
+
+        for index in due {
+            if self.is_connected() {
+                self.scan(index).await;
+            } else {
+                tracing::debug!("skipping scan group {} while not connected", index);
+            }
+
+            let jitter = self.jitter.next();
+            let group = &mut self.groups[index];
+            group.base += group.period;
+            // don't try to catch up on the scans that were missed
+            let now = Instant::now();
+            if group.base < now {
+                group.base = now + group.period;
+            }
+            group.next = group.base + jitter;
+        }
+    }
+
+    async fn scan(&mut self, index: usize) {
+        for read in self.groups[index].reads.clone() {
+            let param = RequestParam::new(read.unit_id, self.response_timeout);
+            let result = match read.scan_type {
+                ScanType::Coils => self
+                    .channel
+                    .read_coils(param, read.range)
+                    .await
+                    .map(ScanValues::Bits),
+                ScanType::DiscreteInputs => self
+                    .channel
+                    .read_discrete_inputs(param, read.range)
+                    .await
+                    .map(ScanValues::Bits),
+                ScanType::HoldingRegisters => self
+                    .channel
+                    .read_holding_registers(param, read.range)
+                    .await
+                    .map(ScanValues::Registers),
+                ScanType::InputRegisters => self
+                    .channel
+                    .read_input_registers(param, read.range)
+                    .await
+                    .map(ScanValues::Registers),
+            };
+
+            for range in read.members {
+                let result = ScanResult {
+                    group: ScanGroupId(index),
+                    unit_id: read.unit_id,
+                    scan_type: read.scan_type,
+                    range,
+                    result: result
+                        .as_ref()
+                        .map(|x| x.slice(read.range, range))
+                        .map_err(|err| *err),
+                };
+                self.listener.update(result).get().await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::range;
+
+    fn scan(unit_id: u8, scan_type: ScanType, start: u16, count: u16) -> ScanRange {
+        ScanRange {
+            unit_id: UnitId::new(unit_id),
+            scan_type,
+            range: range(start, count),
+        }
+    }
+
+    #[test]
+    fn merges_adjacent_and_overlapping_ranges() {
+        let reads = merge(&[
+            scan(1, ScanType::HoldingRegisters, 10, 5),
+            scan(1, ScanType::HoldingRegisters, 0, 10),
+            scan(1, ScanType::HoldingRegisters, 12, 2),
+            scan(1, ScanType::HoldingRegisters, 20, 1),
+        ]);
+        assert_eq!(
+            reads,
+            vec![
+                MergedRead {
+                    unit_id: UnitId::new(1),
+                    scan_type: ScanType::HoldingRegisters,
+                    range: range(0, 15),
+                    members: vec![range(0, 10), range(10, 5), range(12, 2)],
+                },
+                MergedRead {
+                    unit_id: UnitId::new(1),
+                    scan_type: ScanType::HoldingRegisters,
+                    range: range(20, 1),
+                    members: vec![range(20, 1)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn does_not_merge_different_units_or_types() {
+        let reads = merge(&[
+            scan(1, ScanType::Coils, 0, 8),
+            scan(2, ScanType::Coils, 8, 8),
+            scan(1, ScanType::DiscreteInputs, 8, 8),
+        ]);
+        assert_eq!(reads.len(), 3);
+    }
+
+    #[test]
+    fn does_not_merge_beyond_the_maximum_count() {
+        let reads = merge(&[
+            scan(1, ScanType::InputRegisters, 0, 100),
+            scan(1, ScanType::InputRegisters, 100, 26),
+        ]);
+        assert_eq!(
+            reads.iter().map(|x| x.range).collect::<Vec<_>>(),
+            vec![range(0, 100), range(100, 26)]
+        );
+    }
+
+    #[test]
+    fn slices_merged_values() {
+        let values = ScanValues::Registers((5..10).map(|x| Indexed::new(x, x)).collect());
+        assert_eq!(
+            values.slice(range(5, 5), range(7, 2)),
+            ScanValues::Registers(vec![Indexed::new(7, 7), Indexed::new(8, 8)])
+        );
+    }
+
+    #[test]
+    fn rejects_ranges_too_large_for_a_single_request() {
+        let mut group = ScanGroup::new(Duration::from_secs(1), 0);
+        assert_eq!(
+            group.add(UnitId::new(1), ScanType::HoldingRegisters, range(0, 126)),
+            Err(InvalidRange::CountTooLargeForType(126, 125))
+        );
+        assert!(group
+            .add(UnitId::new(1), ScanType::Coils, range(0, 126))
+            .is_ok());
+    }
+
+    #[test]
+    fn jitter_is_bounded() {
+        let mut jitter = Jitter::new(Duration::from_millis(10));
+        for _ in 0..100 {
+            assert!(jitter.next() <= Duration::from_millis(10));
+        }
+        assert_eq!(Jitter::new(Duration::ZERO).next(), Duration::ZERO);
+    }
+}
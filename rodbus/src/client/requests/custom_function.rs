@@ -0,0 +1,94 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::decode::AppDecodeLevel;
+use crate::error::{InvalidRequest, RequestError};
+use crate::types::CustomFunctionDisplay;
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct CustomFunction {
+    pub(crate) function: FunctionCode,
+    pub(crate) data: Vec<u8>,
+    promise: Promise<Vec<u8>>,
+}
+
+impl CustomFunction {
+    pub(crate) fn new(function: FunctionCode, data: Vec<u8>, promise: Promise<Vec<u8>>) -> Self {
+        Self {
+            function,
+            data,
+            promise,
+        }
+    }
+
+    /// check that the function code is not implemented by the library and that the data fits in a frame
+    pub(crate) fn validate(function_code: u8, data: &[u8]) -> Result<FunctionCode, InvalidRequest> {
+        let function = FunctionCode::custom(function_code)
+            .ok_or(InvalidRequest::InvalidCustomFunctionCode(function_code))?;
+        let max = crate::constants::limits::MAX_CUSTOM_FUNCTION_DATA_LENGTH;
+        if data.len() > max {
+            let count = u16::try_from(data.len())
+                .map_err(|_| InvalidRequest::CountTooBigForU16(data.len()))?;
+            return Err(InvalidRequest::CountTooBigForType(count, max as u16));
+        }
+        Ok(function)
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_bytes(&self.data)?;
+        Ok(())
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        let data = cursor.read_bytes(cursor.remaining())?.to_vec();
+
+        if decode.enabled() {
+            tracing::info!(
+                "PDU RX - {} {}",
+                function,
+                CustomFunctionDisplay::new(decode, &data)
+            );
+        }
+
+        self.promise.success(data);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_unimplemented_function_codes() {
+        assert_eq!(
+            CustomFunction::validate(0x41, &[0x01, 0x02]),
+            Ok(FunctionCode::Custom(0x41))
+        );
+    }
+
+    #[test]
+    fn rejects_implemented_function_codes() {
+        assert_eq!(
+            CustomFunction::validate(0x03, &[]),
+            Err(InvalidRequest::InvalidCustomFunctionCode(0x03))
+        );
+    }
+
+    #[test]
+    fn rejects_data_that_does_not_fit_in_a_frame() {
+        assert_eq!(
+            CustomFunction::validate(0x64, &[0; 253]),
+            Err(InvalidRequest::CountTooBigForType(253, 252))
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::common::traits::{Parse, Serialize};
+use crate::decode::AppDecodeLevel;
+use crate::diagnostics::{sub_function, DiagnosticsRequest};
+use crate::error::{AduParseError, RequestError};
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct Diagnostics {
+    pub(crate) request: DiagnosticsRequest,
+    promise: Promise<u16>,
+}
+
+impl Diagnostics {
+    pub(crate) fn new(request: DiagnosticsRequest, promise: Promise<u16>) -> Self {
+        Self { request, promise }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.request.serialize(cursor)
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    /// the server never responds when entering listen only mode
+    pub(crate) fn expects_response(&self) -> bool {
+        self.request.sub_function != sub_function::FORCE_LISTEN_ONLY_MODE
+    }
+
+    pub(crate) fn complete_without_response(&mut self) {
+        self.promise.success(self.request.data)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        let response = self.parse_all(&mut cursor)?;
+
+        if decode.enabled() {
+            tracing::info!("PDU RX - {} {}", function, response);
+        }
+
+        self.promise.success(response.data);
+        Ok(())
+    }
+
+    fn parse_all(&self, cursor: &mut ReadCursor) -> Result<DiagnosticsRequest, RequestError> {
+        let response = DiagnosticsRequest::parse(cursor)?;
+        cursor.expect_empty()?;
+
+        if response.sub_function != self.request.sub_function
+            || (self.request.is_echo() && response.data != self.request.data)
+        {
+            return Err(AduParseError::ReplyEchoMismatch.into());
+        }
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(sub_function: u16, data: u16) -> Diagnostics {
+        Diagnostics::new(
+            DiagnosticsRequest::new(sub_function, data),
+            Promise::new(|_| {}),
+        )
+    }
+
+    #[test]
+    fn parses_counter_response() {
+        let request = create(sub_function::BUS_MESSAGE_COUNT, 0);
+        let mut cursor = ReadCursor::new(&[0x00, 0x0B, 0x01, 0x02]);
+        assert_eq!(
+            request.parse_all(&mut cursor),
+            Ok(DiagnosticsRequest::new(
+                sub_function::BUS_MESSAGE_COUNT,
+                0x0102
+            ))
+        );
+    }
+
+    #[test]
+    fn fails_when_query_data_is_not_echoed() {
+        let request = create(sub_function::RETURN_QUERY_DATA, 0xA537);
+        let mut cursor = ReadCursor::new(&[0x00, 0x00, 0xA5, 0x38]);
+        assert_eq!(
+            request.parse_all(&mut cursor),
+            Err(AduParseError::ReplyEchoMismatch.into())
+        );
+    }
+
+    #[test]
+    fn fails_when_sub_function_does_not_match() {
+        let request = create(sub_function::BUS_MESSAGE_COUNT, 0);
+        let mut cursor = ReadCursor::new(&[0x00, 0x0C, 0x00, 0x00]);
+        assert_eq!(
+            request.parse_all(&mut cursor),
+            Err(AduParseError::ReplyEchoMismatch.into())
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::common::traits::Parse;
+use crate::decode::AppDecodeLevel;
+use crate::diagnostics::CommEventCounter;
+use crate::error::RequestError;
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct GetCommEventCounter {
+    promise: Promise<CommEventCounter>,
+}
+
+impl GetCommEventCounter {
+    pub(crate) fn new(promise: Promise<CommEventCounter>) -> Self {
+        Self { promise }
+    }
+
+    pub(crate) fn serialize(&self, _cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        Ok(())
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        let counter = CommEventCounter::parse(&mut cursor)?;
+        cursor.expect_empty()?;
+
+        if decode.enabled() {
+            tracing::info!("PDU RX - {} {}", function, counter);
+        }
+
+        self.promise.success(counter);
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::decode::AppDecodeLevel;
+use crate::diagnostics::{CommEventLog, CommEventLogDisplay};
+use crate::error::RequestError;
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct GetCommEventLog {
+    promise: Promise<CommEventLog>,
+}
+
+impl GetCommEventLog {
+    pub(crate) fn new(promise: Promise<CommEventLog>) -> Self {
+        Self { promise }
+    }
+
+    pub(crate) fn serialize(&self, _cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        Ok(())
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        let log = CommEventLog::parse(&mut cursor)?;
+
+        if decode.enabled() {
+            tracing::info!(
+                "PDU RX - {} {}",
+                function,
+                CommEventLogDisplay::new(decode, &log)
+            );
+        }
+
+        self.promise.success(log);
+        Ok(())
+    }
+}
@@ -1,4 +1,15 @@
+pub(crate) mod custom_function;
+pub(crate) mod diagnostics;
+pub(crate) mod get_comm_event_counter;
+pub(crate) mod get_comm_event_log;
 pub(crate) mod read_bits;
+pub(crate) mod read_device_identification;
+pub(crate) mod read_exception_status;
+pub(crate) mod read_fifo_queue;
+pub(crate) mod read_file_record;
 pub(crate) mod read_registers;
+pub(crate) mod read_write_multiple;
+pub(crate) mod report_server_id;
+pub(crate) mod write_file_record;
 pub(crate) mod write_multiple;
 pub(crate) mod write_single;
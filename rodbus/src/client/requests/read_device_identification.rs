@@ -0,0 +1,61 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::decode::AppDecodeLevel;
+use crate::device_id::{DeviceIdReply, DeviceIdReplyDisplay, ReadDeviceIdCode};
+use crate::error::{AduParseError, RequestError};
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct ReadDeviceIdentification {
+    pub(crate) code: ReadDeviceIdCode,
+    pub(crate) object_id: u8,
+    promise: Promise<DeviceIdReply>,
+}
+
+impl ReadDeviceIdentification {
+    pub(crate) fn new(
+        code: ReadDeviceIdCode,
+        object_id: u8,
+        promise: Promise<DeviceIdReply>,
+    ) -> Self {
+        Self {
+            code,
+            object_id,
+            promise,
+        }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u8(crate::device_id::MEI_TYPE_READ_DEVICE_ID)?;
+        cursor.write_u8(self.code.get_value())?;
+        cursor.write_u8(self.object_id)?;
+        Ok(())
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        let reply = DeviceIdReply::parse(&mut cursor)?;
+        if reply.code != self.code {
+            return Err(AduParseError::ReplyEchoMismatch.into());
+        }
+
+        if decode.enabled() {
+            tracing::info!(
+                "PDU RX - {} {}",
+                function,
+                DeviceIdReplyDisplay::new(decode, &reply)
+            );
+        }
+
+        self.promise.success(reply);
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::decode::AppDecodeLevel;
+use crate::error::RequestError;
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct ReadExceptionStatus {
+    promise: Promise<u8>,
+}
+
+impl ReadExceptionStatus {
+    pub(crate) fn new(promise: Promise<u8>) -> Self {
+        Self { promise }
+    }
+
+    pub(crate) fn serialize(&self, _cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        Ok(())
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        let status = cursor.read_u8()?;
+        cursor.expect_empty()?;
+
+        if decode.enabled() {
+            tracing::info!("PDU RX - {} status: {:#04X}", function, status);
+        }
+
+        self.promise.success(status);
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::decode::AppDecodeLevel;
+use crate::error::{AduParseError, RequestError};
+use crate::types::FifoQueueDisplay;
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct ReadFifoQueue {
+    pub(crate) address: u16,
+    promise: Promise<Vec<u16>>,
+}
+
+impl ReadFifoQueue {
+    pub(crate) fn new(address: u16, promise: Promise<Vec<u16>>) -> Self {
+        Self { address, promise }
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u16_be(self.address)?;
+        Ok(())
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        let values = Self::parse_all(&mut cursor)?;
+
+        if decode.enabled() {
+            tracing::info!(
+                "PDU RX - {} {}",
+                function,
+                FifoQueueDisplay::new(decode, &values)
+            );
+        }
+
+        self.promise.success(values);
+        Ok(())
+    }
+
+    fn parse_all(cursor: &mut ReadCursor) -> Result<Vec<u16>, RequestError> {
+        let byte_count = cursor.read_u16_be()? as usize;
+        if byte_count != cursor.remaining() {
+            return Err(AduParseError::InsufficientBytesForByteCount(
+                byte_count,
+                cursor.remaining(),
+            )
+            .into());
+        }
+
+        let count = cursor.read_u16_be()?;
+        if count > crate::constants::limits::MAX_FIFO_COUNT {
+            return Err(AduParseError::FifoCountTooLarge(count).into());
+        }
+
+        let mut values = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            values.push(cursor.read_u16_be()?);
+        }
+        cursor.expect_empty()?;
+
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fifo_response() {
+        let mut cursor = ReadCursor::new(&[0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84]);
+        assert_eq!(
+            ReadFifoQueue::parse_all(&mut cursor),
+            Ok(vec![0x01B8, 0x1284])
+        );
+    }
+
+    #[test]
+    fn fails_when_byte_count_does_not_match() {
+        let mut cursor = ReadCursor::new(&[0x00, 0x08, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84]);
+        assert_eq!(
+            ReadFifoQueue::parse_all(&mut cursor),
+            Err(AduParseError::InsufficientBytesForByteCount(8, 6).into())
+        );
+    }
+
+    #[test]
+    fn fails_when_fifo_count_too_large() {
+        let mut cursor = ReadCursor::new(&[0x00, 0x02, 0x00, 0x20]);
+        assert_eq!(
+            ReadFifoQueue::parse_all(&mut cursor),
+            Err(AduParseError::FifoCountTooLarge(0x20).into())
+        );
+    }
+}
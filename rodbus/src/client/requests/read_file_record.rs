@@ -0,0 +1,176 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::decode::AppDecodeLevel;
+use crate::error::{AduParseError, InvalidRange, InvalidRequest, RequestError};
+use crate::file_record::{FileRecord, FileRecordData, FileRecordDataDisplay};
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct ReadFileRecord {
+    pub(crate) records: Vec<FileRecord>,
+    promise: Promise<Vec<FileRecordData>>,
+}
+
+impl ReadFileRecord {
+    pub(crate) fn new(records: Vec<FileRecord>, promise: Promise<Vec<FileRecordData>>) -> Self {
+        Self { records, promise }
+    }
+
+    /// check that every sub-request is valid and that both the request and the response fit in a frame
+    pub(crate) fn validate(records: &[FileRecord]) -> Result<(), InvalidRequest> {
+        if records.is_empty() {
+            return Err(InvalidRequest::BadRange(InvalidRange::CountOfZero));
+        }
+
+        for record in records {
+            record.range()?;
+        }
+
+        let max = crate::constants::limits::MAX_READ_FILE_RECORD_LENGTH;
+        let request_length = records.len() * crate::file_record::SUB_REQUEST_HEADER_LENGTH;
+        let response_length: usize = records.iter().map(|x| x.response_length()).sum();
+        let length = request_length.max(response_length);
+        if length > max {
+            let length =
+                u16::try_from(length).map_err(|_| InvalidRequest::CountTooBigForU16(length))?;
+            return Err(InvalidRequest::CountTooBigForType(length, max as u16));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        let byte_count = self.records.len() * crate::file_record::SUB_REQUEST_HEADER_LENGTH;
+        cursor.write_u8(byte_count as u8)?;
+        for record in &self.records {
+            cursor.write_u8(crate::file_record::REFERENCE_TYPE)?;
+            cursor.write_u16_be(record.file_number)?;
+            cursor.write_u16_be(record.record_number)?;
+            cursor.write_u16_be(record.record_length)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        let records = Self::parse_all(&self.records, &mut cursor)?;
+
+        if decode.enabled() {
+            tracing::info!(
+                "PDU RX - {} {}",
+                function,
+                FileRecordDataDisplay::new(decode, &records)
+            );
+        }
+
+        self.promise.success(records);
+        Ok(())
+    }
+
+    fn parse_all(
+        requested: &[FileRecord],
+        cursor: &mut ReadCursor,
+    ) -> Result<Vec<FileRecordData>, RequestError> {
+        let byte_count = cursor.read_u8()? as usize;
+        if byte_count != cursor.remaining() {
+            return Err(AduParseError::InsufficientBytesForByteCount(
+                byte_count,
+                cursor.remaining(),
+            )
+            .into());
+        }
+
+        // sub-responses are returned in the order of the sub-requests
+        let mut records = Vec::with_capacity(requested.len());
+        for record in requested {
+            let length = cursor.read_u8()? as usize;
+            if length != record.response_length() - 1 {
+                return Err(AduParseError::ReplyEchoMismatch.into());
+            }
+            let reference_type = cursor.read_u8()?;
+            if reference_type != crate::file_record::REFERENCE_TYPE {
+                return Err(AduParseError::UnknownReferenceType(reference_type).into());
+            }
+            let mut values = Vec::with_capacity(record.record_length as usize);
+            for _ in 0..record.record_length {
+                values.push(cursor.read_u16_be()?);
+            }
+            records.push(FileRecordData::new(
+                record.file_number,
+                record.record_number,
+                values,
+            ));
+        }
+        cursor.expect_empty()?;
+
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sub_responses_in_request_order() {
+        let requested = [FileRecord::new(4, 1, 2), FileRecord::new(3, 9, 1)];
+        let mut cursor = ReadCursor::new(&[
+            0x0A, 0x05, 0x06, 0x0D, 0xFE, 0x00, 0x20, 0x03, 0x06, 0x33, 0xCD,
+        ]);
+        assert_eq!(
+            ReadFileRecord::parse_all(&requested, &mut cursor),
+            Ok(vec![
+                FileRecordData::new(4, 1, vec![0x0DFE, 0x0020]),
+                FileRecordData::new(3, 9, vec![0x33CD]),
+            ])
+        );
+    }
+
+    #[test]
+    fn fails_when_sub_response_length_does_not_match() {
+        let requested = [FileRecord::new(4, 1, 2)];
+        let mut cursor = ReadCursor::new(&[0x04, 0x03, 0x06, 0x0D, 0xFE]);
+        assert_eq!(
+            ReadFileRecord::parse_all(&requested, &mut cursor),
+            Err(AduParseError::ReplyEchoMismatch.into())
+        );
+    }
+
+    #[test]
+    fn fails_when_response_has_extra_sub_responses() {
+        let requested = [FileRecord::new(4, 1, 1)];
+        let mut cursor = ReadCursor::new(&[0x08, 0x03, 0x06, 0x0D, 0xFE, 0x03, 0x06, 0x00, 0x20]);
+        assert_eq!(
+            ReadFileRecord::parse_all(&requested, &mut cursor),
+            Err(AduParseError::TrailingBytes(4).into())
+        );
+    }
+
+    #[test]
+    fn validates_sub_requests() {
+        assert_eq!(
+            ReadFileRecord::validate(&[]),
+            Err(InvalidRequest::BadRange(InvalidRange::CountOfZero))
+        );
+        assert_eq!(
+            ReadFileRecord::validate(&[FileRecord::new(1, 0x2710, 1)]),
+            Err(InvalidRequest::InvalidFileRecordNumber(0x2710))
+        );
+        assert_eq!(
+            ReadFileRecord::validate(&[FileRecord::new(1, 0, 0x7A)]),
+            Err(InvalidRequest::CountTooBigForType(0xF6, 0xF5))
+        );
+        assert_eq!(
+            ReadFileRecord::validate(&[FileRecord::new(1, 0, 0x79)]),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::client::requests::read_registers::Promise;
+use crate::client::requests::write_multiple::WriteMultiple;
+use crate::common::function::FunctionCode;
+use crate::common::traits::Serialize;
+use crate::decode::AppDecodeLevel;
+use crate::error::{InvalidRequest, RequestError};
+use crate::types::{
+    AddressRange, Indexed, ReadRegistersRange, RegisterIterator, RegisterIteratorDisplay,
+};
+
+use scursor::{ReadCursor, WriteCursor};
+
+/// Range of registers to read and the values to write beforehand
+///
+/// Used when making read/write multiple registers requests
+#[derive(Debug, Clone)]
+pub struct ReadWriteMultiple {
+    /// range of holding registers to read
+    pub(crate) read_range: ReadRegistersRange,
+    /// registers to write before the read is performed
+    pub(crate) write: WriteMultiple<u16>,
+}
+
+impl ReadWriteMultiple {
+    /// Create a new request from the range to read, the starting address of the write and the values to write
+    pub fn new(
+        read_range: AddressRange,
+        write_start: u16,
+        values: Vec<u16>,
+    ) -> Result<Self, InvalidRequest> {
+        let read_range = read_range.of_read_registers()?;
+        let write = WriteMultiple::from(write_start, values)?;
+        write
+            .range
+            .limited_count(crate::constants::limits::MAX_READ_WRITE_WRITE_REGISTERS_COUNT)?;
+        Ok(Self { read_range, write })
+    }
+
+    /// Range of holding registers that will be read
+    pub fn read_range(&self) -> AddressRange {
+        self.read_range.get()
+    }
+
+    /// Range of holding registers that will be written
+    pub fn write_range(&self) -> AddressRange {
+        self.write.range
+    }
+}
+
+pub(crate) struct ReadWriteMultipleRegisters {
+    pub(crate) request: ReadWriteMultiple,
+    promise: Promise,
+}
+
+impl ReadWriteMultipleRegisters {
+    pub(crate) fn new(request: ReadWriteMultiple, promise: Promise) -> Self {
+        Self { request, promise }
+    }
+
+    pub(crate) fn channel(
+        request: ReadWriteMultiple,
+        tx: tokio::sync::oneshot::Sender<Result<Vec<Indexed<u16>>, RequestError>>,
+    ) -> Self {
+        Self::new(
+            request,
+            Promise::new(|x: Result<RegisterIterator, RequestError>| {
+                let _ = tx.send(x.map(|x| x.collect()));
+            }),
+        )
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.request.serialize(cursor)
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        // there's a byte-count here that we don't actually need
+        cursor.read_u8()?;
+        // the rest is the sequence of registers that were read
+        let response = RegisterIterator::parse_all(self.request.read_range.get(), &mut cursor)?;
+
+        if decode.enabled() {
+            tracing::info!(
+                "PDU RX - {} {}",
+                function,
+                RegisterIteratorDisplay::new(decode, response)
+            );
+        }
+
+        self.promise.success(response);
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::decode::AppDecodeLevel;
+use crate::diagnostics::ServerIdDisplay;
+use crate::error::{AduParseError, RequestError};
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct ReportServerId {
+    promise: Promise<Vec<u8>>,
+}
+
+impl ReportServerId {
+    pub(crate) fn new(promise: Promise<Vec<u8>>) -> Self {
+        Self { promise }
+    }
+
+    pub(crate) fn serialize(&self, _cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        Ok(())
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        let data = Self::parse_all(&mut cursor)?;
+
+        if decode.enabled() {
+            tracing::info!(
+                "PDU RX - {} {}",
+                function,
+                ServerIdDisplay::new(decode, &data)
+            );
+        }
+
+        self.promise.success(data);
+        Ok(())
+    }
+
+    fn parse_all(cursor: &mut ReadCursor) -> Result<Vec<u8>, RequestError> {
+        let byte_count = cursor.read_u8()? as usize;
+        if byte_count != cursor.remaining() {
+            return Err(AduParseError::InsufficientBytesForByteCount(
+                byte_count,
+                cursor.remaining(),
+            )
+            .into());
+        }
+
+        Ok(cursor.read_bytes(byte_count)?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_server_id() {
+        let mut cursor = ReadCursor::new(&[0x03, 0x2A, 0xFF, 0x01]);
+        assert_eq!(
+            ReportServerId::parse_all(&mut cursor),
+            Ok(vec![0x2A, 0xFF, 0x01])
+        );
+    }
+
+    #[test]
+    fn fails_when_byte_count_does_not_match() {
+        let mut cursor = ReadCursor::new(&[0x03, 0x2A, 0xFF]);
+        assert_eq!(
+            ReportServerId::parse_all(&mut cursor),
+            Err(AduParseError::InsufficientBytesForByteCount(3, 2).into())
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use crate::client::message::Promise;
+use crate::common::function::FunctionCode;
+use crate::decode::AppDecodeLevel;
+use crate::error::{AduParseError, InvalidRange, InvalidRequest, RequestError};
+use crate::file_record::{FileRecordData, WriteFileRecords};
+
+use scursor::{ReadCursor, WriteCursor};
+
+pub(crate) struct WriteFileRecord {
+    pub(crate) records: Vec<FileRecordData>,
+    promise: Promise<()>,
+}
+
+impl WriteFileRecord {
+    pub(crate) fn new(records: Vec<FileRecordData>, promise: Promise<()>) -> Self {
+        Self { records, promise }
+    }
+
+    /// check that every sub-request is valid and that the request fits in a frame
+    pub(crate) fn validate(records: &[FileRecordData]) -> Result<(), InvalidRequest> {
+        if records.is_empty() {
+            return Err(InvalidRequest::BadRange(InvalidRange::CountOfZero));
+        }
+
+        for record in records {
+            record.record()?.range()?;
+        }
+
+        let max = crate::constants::limits::MAX_WRITE_FILE_RECORD_LENGTH;
+        let length: usize = records.iter().map(|x| x.request_length()).sum();
+        if length > max {
+            let length =
+                u16::try_from(length).map_err(|_| InvalidRequest::CountTooBigForU16(length))?;
+            return Err(InvalidRequest::CountTooBigForType(length, max as u16));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        let byte_count: usize = self.records.iter().map(|x| x.request_length()).sum();
+        cursor.write_u8(byte_count as u8)?;
+        for record in &self.records {
+            cursor.write_u8(crate::file_record::REFERENCE_TYPE)?;
+            cursor.write_u16_be(record.file_number)?;
+            cursor.write_u16_be(record.record_number)?;
+            cursor.write_u16_be(record.values.len() as u16)?;
+            for value in &record.values {
+                cursor.write_u16_be(*value)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn failure(&mut self, err: RequestError) {
+        self.promise.failure(err)
+    }
+
+    pub(crate) fn handle_response(
+        &mut self,
+        mut cursor: ReadCursor,
+        function: FunctionCode,
+        decode: AppDecodeLevel,
+    ) -> Result<(), RequestError> {
+        // the response is an echo of the request
+        let echo = WriteFileRecords::parse(&mut cursor)?;
+        if !echo.matches(&self.records) {
+            return Err(AduParseError::ReplyEchoMismatch.into());
+        }
+
+        if decode.enabled() {
+            tracing::info!(
+                "PDU RX - {} {}",
+                function,
+                crate::file_record::WriteFileRecordsDisplay::new(decode, echo)
+            );
+        }
+
+        self.promise.success(());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_sub_requests() {
+        assert_eq!(
+            WriteFileRecord::validate(&[]),
+            Err(InvalidRequest::BadRange(InvalidRange::CountOfZero))
+        );
+        assert_eq!(
+            WriteFileRecord::validate(&[FileRecordData::new(1, 0, vec![])]),
+            Err(InvalidRequest::BadRange(InvalidRange::CountOfZero))
+        );
+        assert_eq!(
+            WriteFileRecord::validate(&[FileRecordData::new(1, 0, vec![0; 0x7B])]),
+            Err(InvalidRequest::CountTooBigForType(0xFD, 0xFB))
+        );
+        assert_eq!(
+            WriteFileRecord::validate(&[FileRecordData::new(1, 0, vec![0; 0x7A])]),
+            Ok(())
+        );
+    }
+}
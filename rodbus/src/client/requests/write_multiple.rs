@@ -1,4 +1,5 @@
 use crate::client::message::Promise;
+use crate::codec::{encode_string, encode_values, ByteOrder, RegisterValue};
 use crate::common::function::FunctionCode;
 use crate::common::traits::{Parse, Serialize};
 use crate::decode::AppDecodeLevel;
@@ -42,6 +43,29 @@ impl<T> WriteMultiple<T> {
     }
 }
 
+impl WriteMultiple<u16> {
+    /// Create registers by encoding typed values in the specified byte order
+    pub fn from_values<T: RegisterValue>(
+        start: u16,
+        values: &[T],
+        order: ByteOrder,
+    ) -> Result<Self, InvalidRequest> {
+        Self::from(start, encode_values(values, order))
+    }
+
+    /// Create registers by encoding an ASCII string into `register_count` registers
+    pub fn from_string(
+        start: u16,
+        value: &str,
+        register_count: u16,
+        order: ByteOrder,
+    ) -> Result<Self, InvalidRequest> {
+        let registers =
+            encode_string(value, register_count, order).map_err(InvalidRequest::InvalidValue)?;
+        Self::from(start, registers)
+    }
+}
+
 impl<'a, T> WriteMultipleIterator<'a, T> {
     fn new(range: AddressRange, iter: std::slice::Iter<'a, T>) -> Self {
         Self {
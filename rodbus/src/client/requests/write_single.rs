@@ -5,7 +5,7 @@ use crate::common::function::FunctionCode;
 use crate::decode::AppDecodeLevel;
 use crate::error::AduParseError;
 use crate::error::RequestError;
-use crate::types::{coil_from_u16, coil_to_u16, Indexed};
+use crate::types::{coil_from_u16, coil_to_u16, Indexed, MaskWriteRegister};
 
 use scursor::{ReadCursor, WriteCursor};
 
@@ -92,3 +92,20 @@ impl SingleWriteOperation for Indexed<u16> {
         Ok(Indexed::new(cursor.read_u16_be()?, cursor.read_u16_be()?))
     }
 }
+
+impl SingleWriteOperation for MaskWriteRegister {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u16_be(self.address)?;
+        cursor.write_u16_be(self.and_mask)?;
+        cursor.write_u16_be(self.or_mask)?;
+        Ok(())
+    }
+
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        Ok(MaskWriteRegister::new(
+            cursor.read_u16_be()?,
+            cursor.read_u16_be()?,
+            cursor.read_u16_be()?,
+        ))
+    }
+}
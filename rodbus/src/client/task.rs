@@ -6,7 +6,7 @@ use crate::common::phys::PhysLayer;
 use tokio::time::Instant;
 
 use crate::client::message::{Command, Request, Setting};
-use crate::common::frame::{FrameHeader, FrameWriter, FramedReader, TxId};
+use crate::common::frame::{Frame, FrameHeader, FrameWriter, FramedReader, TxId};
 use crate::error::*;
 use crate::DecodeLevel;
 
@@ -68,6 +68,17 @@ pub(crate) struct ClientLoop {
     tx_id: TxId,
     decode: DecodeLevel,
     enabled: bool,
+    /// number of times a request is sent again when no response is received in time
+    retransmissions: usize,
+    /// maximum number of requests that may be waiting for a response
+    max_in_flight: usize,
+}
+
+/// request that was sent and is waiting for a response
+struct InFlight {
+    tx_id: TxId,
+    deadline: Instant,
+    request: Request,
 }
 
 impl ClientLoop {
@@ -84,9 +95,25 @@ impl ClientLoop {
             tx_id: TxId::default(),
             decode,
             enabled: false,
+            retransmissions: 0,
+            max_in_flight: 1,
         }
     }
 
+    /// Send each request again up to `count` times if a response isn't received in time.
+    /// Only useful on transports that may silently drop frames like UDP.
+    pub(crate) fn with_retransmissions(mut self, count: usize) -> Self {
+        self.retransmissions = count;
+        self
+    }
+
+    /// Allow up to `count` requests to wait for a response at the same time. Responses are
+    /// matched to requests using the transaction id, so this requires MBAP framing.
+    pub(crate) fn with_max_in_flight(mut self, count: usize) -> Self {
+        self.max_in_flight = count.max(1);
+        self
+    }
+
     pub(crate) fn is_enabled(&self) -> bool {
         self.enabled
     }
@@ -117,6 +144,10 @@ impl ClientLoop {
     }
 
     pub(crate) async fn run(&mut self, io: &mut PhysLayer) -> SessionError {
+        if self.max_in_flight > 1 {
+            return self.run_pipelined(io).await;
+        }
+
         loop {
             tokio::select! {
                 frame = self.reader.next_frame(io, self.decode) => {
@@ -147,6 +178,134 @@ impl ClientLoop {
         }
     }
 
+    async fn run_pipelined(&mut self, io: &mut PhysLayer) -> SessionError {
+        let mut in_flight: Vec<InFlight> = Vec::with_capacity(self.max_in_flight);
+
+        let (session_error, request_error) = loop {
+            let next_deadline = in_flight.iter().map(|x| x.deadline).min();
+            let result = tokio::select! {
+                frame = self.reader.next_frame(io, self.decode) => {
+                    match frame {
+                        Ok(frame) => self.complete_in_flight(&mut in_flight, frame),
+                        Err(err) => Err(err),
+                    }
+                }
+                _ = tokio::time::sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {
+                    Self::expire_in_flight(&mut in_flight);
+                    Ok(())
+                }
+                cmd = self.rx.recv(), if in_flight.len() < self.max_in_flight => {
+                    match cmd {
+                        // other side has closed the request channel
+                        None => break (SessionError::Shutdown, RequestError::Shutdown),
+                        Some(Command::Setting(setting)) => {
+                            self.change_setting(setting);
+                            if !self.enabled {
+                                break (SessionError::Disabled, RequestError::NoConnection);
+                            }
+                            Ok(())
+                        }
+                        Some(Command::Request(request)) => {
+                            self.send_in_flight(io, &mut in_flight, request).await
+                        }
+                    }
+                }
+            };
+
+            if let Err(err) = result {
+                if let Some(session_error) = SessionError::from(&err) {
+                    tracing::warn!("{}", session_error);
+                    break (session_error, err);
+                }
+            }
+        };
+
+        // responses can't be received once the session ends
+        for mut x in in_flight {
+            x.request.details.fail(request_error);
+        }
+
+        session_error
+    }
+
+    async fn send_in_flight(
+        &mut self,
+        io: &mut PhysLayer,
+        in_flight: &mut Vec<InFlight>,
+        mut request: Request,
+    ) -> Result<(), RequestError> {
+        let tx_id = self.tx_id.next();
+        let result = self
+            .write_request(io, &mut request, tx_id)
+            .instrument(tracing::info_span!("Transaction", tx_id = %tx_id))
+            .await;
+
+        if let Err(err) = result {
+            tracing::warn!("request error: {}", err);
+            request.details.fail(err);
+            return Err(err);
+        }
+
+        if request.details.expects_response() {
+            in_flight.push(InFlight {
+                tx_id,
+                deadline: Instant::now() + request.timeout,
+                request,
+            });
+        } else {
+            request.details.complete_without_response();
+        }
+
+        Ok(())
+    }
+
+    fn complete_in_flight(
+        &mut self,
+        in_flight: &mut Vec<InFlight>,
+        frame: Frame,
+    ) -> Result<(), RequestError> {
+        let position = frame
+            .header
+            .tx_id
+            .and_then(|tx_id| in_flight.iter().position(|x| x.tx_id == tx_id));
+
+        let mut x = match position {
+            Some(x) => in_flight.remove(x),
+            None => {
+                // most likely the response to a request that already timed out
+                tracing::warn!(
+                    "dropping response with unknown transaction id: {:?}",
+                    frame.header.tx_id
+                );
+                return Ok(());
+            }
+        };
+
+        let _span = tracing::info_span!("Transaction", tx_id = %x.tx_id).entered();
+        if let Err(err) = x.request.handle_response(frame.payload(), self.decode.app) {
+            tracing::warn!("request error: {}", err);
+            x.request.details.fail(err);
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    fn expire_in_flight(in_flight: &mut Vec<InFlight>) {
+        let now = Instant::now();
+        let mut i = 0;
+        while i < in_flight.len() {
+            if in_flight[i].deadline <= now {
+                let mut x = in_flight.remove(i);
+                let _span = tracing::info_span!("Transaction", tx_id = %x.tx_id).entered();
+                tracing::warn!("request error: {}", RequestError::ResponseTimeout);
+                x.request.details.fail(RequestError::ResponseTimeout);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
     async fn run_one_request(
         &mut self,
         io: &mut PhysLayer,
@@ -179,6 +338,39 @@ impl ClientLoop {
         io: &mut PhysLayer,
         request: &mut Request,
         tx_id: TxId,
+    ) -> Result<(), RequestError> {
+        let mut attempt = 0;
+        let response = loop {
+            self.write_request(io, request, tx_id).await?;
+
+            if !request.details.expects_response() {
+                request.details.complete_without_response();
+                return Ok(());
+            }
+
+            match self.wait_for_response(io, tx_id, request.timeout).await {
+                Err(RequestError::ResponseTimeout) if attempt < self.retransmissions => {
+                    attempt += 1;
+                    tracing::warn!(
+                        "response timeout, sending the request again ({} of {})",
+                        attempt,
+                        self.retransmissions
+                    );
+                }
+                result => break result?,
+            }
+        };
+
+        // once we have a response, handle it. This may complete a promise
+        // successfully or bubble up an error
+        request.handle_response(response.payload(), self.decode.app)
+    }
+
+    async fn write_request(
+        &mut self,
+        io: &mut PhysLayer,
+        request: &mut Request,
+        tx_id: TxId,
     ) -> Result<(), RequestError> {
         let bytes = self.writer.format_request(
             FrameHeader::new_tcp_header(request.id, tx_id),
@@ -188,11 +380,19 @@ impl ClientLoop {
         )?;
 
         io.write(bytes, self.decode.physical).await?;
+        Ok(())
+    }
 
-        let deadline = Instant::now() + request.timeout;
+    async fn wait_for_response(
+        &mut self,
+        io: &mut PhysLayer,
+        tx_id: TxId,
+        timeout: Duration,
+    ) -> Result<Frame, RequestError> {
+        let deadline = Instant::now() + timeout;
 
         // loop until we get a response with the correct tx id or we timeout
-        let response = loop {
+        loop {
             let frame = tokio::select! {
                 _ = tokio::time::sleep_until(deadline) => {
                     return Err(RequestError::ResponseTimeout);
@@ -210,12 +410,8 @@ impl ClientLoop {
                 }
             }
 
-            break frame;
-        };
-
-        // once we have a response, handle it. This may complete a promise
-        // successfully or bubble up an error
-        request.handle_response(response.payload(), self.decode.app)
+            return Ok(frame);
+        }
     }
 
     pub(crate) fn change_setting(&mut self, setting: Setting) {
@@ -296,15 +492,25 @@ mod tests {
         Channel,
         tokio::task::JoinHandle<SessionError>,
         sfio_tokio_mock_io::Handle,
+    ) {
+        spawn_client_loop_with(|x| x)
+    }
+
+    fn spawn_client_loop_with(
+        configure: fn(ClientLoop) -> ClientLoop,
+    ) -> (
+        Channel,
+        tokio::task::JoinHandle<SessionError>,
+        sfio_tokio_mock_io::Handle,
     ) {
         let (tx, rx) = tokio::sync::mpsc::channel(16);
         let (mock, io_handle) = sfio_tokio_mock_io::mock();
-        let mut client_loop = ClientLoop::new(
+        let mut client_loop = configure(ClientLoop::new(
             rx,
             FrameWriter::tcp(),
             FramedReader::tcp(),
             DecodeLevel::default().application(AppDecodeLevel::DataValues),
-        );
+        ));
         let join_handle = tokio::spawn(async move {
             let mut phys = PhysLayer::new_mock(mock);
             client_loop.run(&mut phys).await
@@ -314,11 +520,18 @@ mod tests {
     }
 
     fn get_framed_adu<T>(function: FunctionCode, payload: &T) -> Vec<u8>
+    where
+        T: Serialize + Loggable + Sized,
+    {
+        get_framed_adu_with_tx_id(function, payload, TxId::new(0))
+    }
+
+    fn get_framed_adu_with_tx_id<T>(function: FunctionCode, payload: &T, tx_id: TxId) -> Vec<u8>
     where
         T: Serialize + Loggable + Sized,
     {
         let mut fmt = FrameWriter::tcp();
-        let header = FrameHeader::new_tcp_header(UnitId::new(1), TxId::new(0));
+        let header = FrameHeader::new_tcp_header(UnitId::new(1), tx_id);
         let bytes = fmt
             .format_request(header, function, payload, DecodeLevel::nothing())
             .unwrap();
@@ -379,6 +592,39 @@ mod tests {
         assert_eq!(result, Err(RequestError::ResponseTimeout));
     }
 
+    #[tokio::test]
+    async fn retransmits_request_when_no_response() {
+        let (mut channel, _task, mut io) = spawn_client_loop_with(|x| x.with_retransmissions(1));
+
+        let range = AddressRange::try_from(7, 2).unwrap();
+        let request = get_framed_adu(FunctionCode::ReadCoils, &range);
+        let response = get_framed_adu(
+            FunctionCode::ReadCoils,
+            &BitWriter::new(ReadBitsRange { inner: range }, |_| Ok(true)),
+        );
+
+        let coils = tokio::spawn(async move {
+            channel
+                .read_coils(
+                    RequestParam::new(UnitId::new(1), Duration::from_secs(5)),
+                    range,
+                )
+                .await
+        });
+
+        assert_eq!(io.next_event().await, Event::Write(request.clone()));
+
+        // the first attempt times out and the same request is sent again
+        tokio::time::pause();
+        assert_eq!(io.next_event().await, Event::Write(request));
+        io.read(&response);
+
+        assert_eq!(
+            coils.await.unwrap().unwrap(),
+            vec![Indexed::new(7, true), Indexed::new(8, true)]
+        );
+    }
+
     #[tokio::test]
     async fn returns_shutdown_when_task_dropped() {
         let (mut channel, task, mut io) = spawn_client_loop();
@@ -449,4 +695,101 @@ mod tests {
             vec![Indexed::new(7, true), Indexed::new(8, false)]
         );
     }
+
+    fn read_coils_adus(range: AddressRange, value: bool, tx_id: u16) -> (Vec<u8>, Vec<u8>) {
+        let tx_id = TxId::new(tx_id);
+        let request = get_framed_adu_with_tx_id(FunctionCode::ReadCoils, &range, tx_id);
+        let response = get_framed_adu_with_tx_id(
+            FunctionCode::ReadCoils,
+            &BitWriter::new(ReadBitsRange { inner: range }, |_| Ok(value)),
+            tx_id,
+        );
+        (request, response)
+    }
+
+    fn spawn_read_coils(
+        channel: &Channel,
+        range: AddressRange,
+        timeout: Duration,
+    ) -> tokio::task::JoinHandle<Result<Vec<Indexed<bool>>, RequestError>> {
+        let mut channel = channel.clone();
+        tokio::spawn(async move {
+            channel
+                .read_coils(RequestParam::new(UnitId::new(1), timeout), range)
+                .await
+        })
+    }
+
+    #[tokio::test]
+    async fn pipelined_responses_are_matched_by_transaction_id() {
+        let (channel, _task, mut io) = spawn_client_loop_with(|x| x.with_max_in_flight(2));
+
+        let first_range = AddressRange::try_from(7, 1).unwrap();
+        let second_range = AddressRange::try_from(9, 1).unwrap();
+        let (first_request, first_response) = read_coils_adus(first_range, true, 0);
+        let (second_request, second_response) = read_coils_adus(second_range, false, 1);
+
+        let first = spawn_read_coils(&channel, first_range, Duration::from_secs(1));
+        assert_eq!(io.next_event().await, Event::Write(first_request));
+        let second = spawn_read_coils(&channel, second_range, Duration::from_secs(1));
+        assert_eq!(io.next_event().await, Event::Write(second_request));
+
+        // answer in the opposite order
+        io.read(&second_response);
+        io.read(&first_response);
+
+        assert_eq!(second.await.unwrap().unwrap(), vec![Indexed::new(9, false)]);
+        assert_eq!(first.await.unwrap().unwrap(), vec![Indexed::new(7, true)]);
+    }
+
+    #[tokio::test]
+    async fn pipelined_requests_are_limited_by_window() {
+        let (channel, _task, mut io) = spawn_client_loop_with(|x| x.with_max_in_flight(2));
+
+        let range = AddressRange::try_from(7, 1).unwrap();
+        let (first_request, first_response) = read_coils_adus(range, true, 0);
+        let (second_request, _) = read_coils_adus(range, true, 1);
+        let (third_request, _) = read_coils_adus(range, true, 2);
+
+        let first = spawn_read_coils(&channel, range, Duration::from_secs(1));
+        assert_eq!(io.next_event().await, Event::Write(first_request));
+        let _second = spawn_read_coils(&channel, range, Duration::from_secs(1));
+        assert_eq!(io.next_event().await, Event::Write(second_request));
+        let _third = spawn_read_coils(&channel, range, Duration::from_secs(1));
+
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(io.pop_event(), None);
+
+        // a response opens the window for the third request
+        io.read(&first_response);
+        assert_eq!(io.next_event().await, Event::Read);
+        assert_eq!(io.next_event().await, Event::Write(third_request));
+        assert_eq!(first.await.unwrap().unwrap(), vec![Indexed::new(7, true)]);
+    }
+
+    #[tokio::test]
+    async fn pipelined_requests_time_out_independently() {
+        let (channel, task, mut io) = spawn_client_loop_with(|x| x.with_max_in_flight(2));
+
+        let range = AddressRange::try_from(7, 1).unwrap();
+        let (first_request, first_response) = read_coils_adus(range, true, 0);
+        let (second_request, second_response) = read_coils_adus(range, false, 1);
+
+        let first = spawn_read_coils(&channel, range, Duration::from_secs(1));
+        assert_eq!(io.next_event().await, Event::Write(first_request));
+        let second = spawn_read_coils(&channel, range, Duration::from_secs(5));
+        assert_eq!(io.next_event().await, Event::Write(second_request));
+
+        tokio::time::pause();
+        tokio::time::advance(Duration::from_secs(2)).await;
+        assert_eq!(first.await.unwrap(), Err(RequestError::ResponseTimeout));
+
+        // the late response is discarded without affecting the session
+        io.read(&first_response);
+        io.read(&second_response);
+        assert_eq!(second.await.unwrap().unwrap(), vec![Indexed::new(7, false)]);
+        assert!(!task.is_finished());
+    }
 }
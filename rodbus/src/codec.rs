@@ -0,0 +1,306 @@
+use crate::error::{CodecError, InvalidRequest};
+
+/// Order in which the bytes of a multi-register value are transmitted
+///
+/// The letters name the bytes of a 32-bit value from the most significant (A) to the least
+/// significant (D). For 64-bit values the same swaps are applied to all four registers, e.g.
+/// [`ByteOrder::Cdab`] transmits the least significant register first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// Big-endian registers in big-endian order, the order defined by the Modbus specification
+    Abcd,
+    /// Big-endian registers in little-endian order ("word swap")
+    Cdab,
+    /// Little-endian registers in big-endian order ("byte swap")
+    Badc,
+    /// Little-endian registers in little-endian order ("byte and word swap")
+    Dcba,
+}
+
+impl ByteOrder {
+    fn swap_words(self) -> bool {
+        matches!(self, ByteOrder::Cdab | ByteOrder::Dcba)
+    }
+
+    fn swap_bytes(self) -> bool {
+        matches!(self, ByteOrder::Badc | ByteOrder::Dcba)
+    }
+
+    fn register(self, high: u8, low: u8) -> u16 {
+        if self.swap_bytes() {
+            u16::from_be_bytes([low, high])
+        } else {
+            u16::from_be_bytes([high, low])
+        }
+    }
+
+    fn bytes(self, register: u16) -> [u8; 2] {
+        if self.swap_bytes() {
+            register.to_le_bytes()
+        } else {
+            register.to_be_bytes()
+        }
+    }
+
+    /// convert the big-endian bytes of a value into registers
+    fn to_registers(self, bytes: &[u8]) -> Vec<u16> {
+        let mut registers: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|x| self.register(x[0], x[1]))
+            .collect();
+        if self.swap_words() {
+            registers.reverse();
+        }
+        registers
+    }
+
+    /// convert registers into the big-endian bytes of a value
+    fn to_bytes<const N: usize>(self, registers: &[u16]) -> [u8; N] {
+        let mut bytes = [0; N];
+        for (i, chunk) in bytes.chunks_exact_mut(2).enumerate() {
+            let register = if self.swap_words() {
+                registers[registers.len() - 1 - i]
+            } else {
+                registers[i]
+            };
+            chunk.copy_from_slice(&self.bytes(register));
+        }
+        bytes
+    }
+}
+
+impl std::fmt::Display for ByteOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ByteOrder::Abcd => f.write_str("ABCD"),
+            ByteOrder::Cdab => f.write_str("CDAB"),
+            ByteOrder::Badc => f.write_str("BADC"),
+            ByteOrder::Dcba => f.write_str("DCBA"),
+        }
+    }
+}
+
+/// Value that is transmitted using a fixed number of consecutive registers
+pub trait RegisterValue: Sized + Copy + Send + 'static {
+    /// Number of registers used to transmit the value
+    const REGISTER_COUNT: u16;
+
+    /// Decode the value from exactly [`RegisterValue::REGISTER_COUNT`] registers
+    fn decode(registers: &[u16], order: ByteOrder) -> Result<Self, CodecError>;
+
+    /// Encode the value into [`RegisterValue::REGISTER_COUNT`] registers
+    fn encode(self, order: ByteOrder) -> Vec<u16>;
+}
+
+macro_rules! impl_register_value {
+    ($type:ty, $count:expr) => {
+        impl RegisterValue for $type {
+            const REGISTER_COUNT: u16 = $count;
+
+            fn decode(registers: &[u16], order: ByteOrder) -> Result<Self, CodecError> {
+                if registers.len() != Self::REGISTER_COUNT as usize {
+                    return Err(CodecError::IncompleteValue(
+                        registers.len(),
+                        Self::REGISTER_COUNT,
+                    ));
+                }
+                Ok(<$type>::from_be_bytes(order.to_bytes(registers)))
+            }
+
+            fn encode(self, order: ByteOrder) -> Vec<u16> {
+                order.to_registers(&self.to_be_bytes())
+            }
+        }
+    };
+}
+
+impl_register_value!(u32, 2);
+impl_register_value!(i32, 2);
+impl_register_value!(f32, 2);
+impl_register_value!(u64, 4);
+impl_register_value!(i64, 4);
+impl_register_value!(f64, 4);
+
+/// number of registers used by `count` values of type `T`
+pub(crate) fn register_count<T: RegisterValue>(count: u16) -> Result<u16, InvalidRequest> {
+    let total = count as usize * T::REGISTER_COUNT as usize;
+    u16::try_from(total).map_err(|_| InvalidRequest::CountTooBigForU16(total))
+}
+
+/// Decode consecutive values from registers
+///
+/// Fails if the number of registers is not a multiple of the registers used by each value.
+pub fn decode_values<T: RegisterValue>(
+    registers: &[u16],
+    order: ByteOrder,
+) -> Result<Vec<T>, CodecError> {
+    let chunks = registers.chunks_exact(T::REGISTER_COUNT as usize);
+    if !chunks.remainder().is_empty() {
+        return Err(CodecError::IncompleteValue(
+            registers.len(),
+            T::REGISTER_COUNT,
+        ));
+    }
+    chunks.map(|x| T::decode(x, order)).collect()
+}
+
+/// Encode values into consecutive registers
+pub fn encode_values<T: RegisterValue>(values: &[T], order: ByteOrder) -> Vec<u16> {
+    values.iter().flat_map(|x| x.encode(order)).collect()
+}
+
+/// Decode an ASCII string stored two characters per register
+///
+/// Only the byte swap of the order applies to strings, the characters are always stored in
+/// ascending register order. The string ends at the first NUL character.
+pub fn decode_string(registers: &[u16], order: ByteOrder) -> Result<String, CodecError> {
+    let mut value = String::with_capacity(2 * registers.len());
+    for byte in registers.iter().flat_map(|x| order.bytes(*x)) {
+        if byte == 0 {
+            break;
+        }
+        if !byte.is_ascii() {
+            return Err(CodecError::NonAscii(byte));
+        }
+        value.push(byte as char);
+    }
+    Ok(value)
+}
+
+/// Encode an ASCII string into `register_count` registers, two characters per register
+///
+/// Unused characters are filled with NUL. See [`decode_string`] for how the order applies.
+pub fn encode_string(
+    value: &str,
+    register_count: u16,
+    order: ByteOrder,
+) -> Result<Vec<u16>, CodecError> {
+    if let Some(x) = value.bytes().find(|x| !x.is_ascii()) {
+        return Err(CodecError::NonAscii(x));
+    }
+    if value.len() > 2 * register_count as usize {
+        return Err(CodecError::StringTooLong(value.len(), register_count));
+    }
+
+    let mut bytes = value.as_bytes().to_vec();
+    bytes.resize(2 * register_count as usize, 0);
+    Ok(bytes
+        .chunks_exact(2)
+        .map(|x| order.register(x[0], x[1]))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORDERS: [ByteOrder; 4] = [
+        ByteOrder::Abcd,
+        ByteOrder::Cdab,
+        ByteOrder::Badc,
+        ByteOrder::Dcba,
+    ];
+
+    #[test]
+    fn encodes_32_bit_values_in_each_order() {
+        assert_eq!(0x11223344u32.encode(ByteOrder::Abcd), vec![0x1122, 0x3344]);
+        assert_eq!(0x11223344u32.encode(ByteOrder::Cdab), vec![0x3344, 0x1122]);
+        assert_eq!(0x11223344u32.encode(ByteOrder::Badc), vec![0x2211, 0x4433]);
+        assert_eq!(0x11223344u32.encode(ByteOrder::Dcba), vec![0x4433, 0x2211]);
+    }
+
+    #[test]
+    fn encodes_64_bit_values_in_each_order() {
+        let value = 0x1122334455667788u64;
+        assert_eq!(
+            value.encode(ByteOrder::Abcd),
+            vec![0x1122, 0x3344, 0x5566, 0x7788]
+        );
+        assert_eq!(
+            value.encode(ByteOrder::Cdab),
+            vec![0x7788, 0x5566, 0x3344, 0x1122]
+        );
+        assert_eq!(
+            value.encode(ByteOrder::Badc),
+            vec![0x2211, 0x4433, 0x6655, 0x8877]
+        );
+        assert_eq!(
+            value.encode(ByteOrder::Dcba),
+            vec![0x8877, 0x6655, 0x4433, 0x2211]
+        );
+    }
+
+    #[test]
+    fn decodes_what_was_encoded() {
+        for order in ORDERS {
+            assert_eq!(f32::decode(&1.5f32.encode(order), order), Ok(1.5));
+            assert_eq!(i32::decode(&(-2i32).encode(order), order), Ok(-2));
+            assert_eq!(f64::decode(&(-0.25f64).encode(order), order), Ok(-0.25));
+            assert_eq!(i64::decode(&i64::MIN.encode(order), order), Ok(i64::MIN));
+        }
+    }
+
+    #[test]
+    fn decodes_well_known_float() {
+        // 123.456 as an IEEE-754 single is 0x42F6E979
+        assert_eq!(f32::decode(&[0xE979, 0x42F6], ByteOrder::Cdab), Ok(123.456));
+    }
+
+    #[test]
+    fn fails_to_decode_incomplete_values() {
+        assert_eq!(
+            decode_values::<u32>(&[0x0001, 0x0002, 0x0003], ByteOrder::Abcd),
+            Err(CodecError::IncompleteValue(3, 2))
+        );
+        assert_eq!(
+            u64::decode(&[0x0001, 0x0002], ByteOrder::Abcd),
+            Err(CodecError::IncompleteValue(2, 4))
+        );
+    }
+
+    #[test]
+    fn encodes_and_decodes_multiple_values() {
+        let registers = encode_values(&[1u32, 2u32], ByteOrder::Cdab);
+        assert_eq!(registers, vec![0x0001, 0x0000, 0x0002, 0x0000]);
+        assert_eq!(
+            decode_values::<u32>(&registers, ByteOrder::Cdab),
+            Ok(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn encodes_strings_with_padding() {
+        assert_eq!(
+            encode_string("abc", 3, ByteOrder::Abcd),
+            Ok(vec![0x6162, 0x6300, 0x0000])
+        );
+        assert_eq!(
+            encode_string("abc", 2, ByteOrder::Badc),
+            Ok(vec![0x6261, 0x0063])
+        );
+        assert_eq!(
+            encode_string("abcde", 2, ByteOrder::Abcd),
+            Err(CodecError::StringTooLong(5, 2))
+        );
+        assert_eq!(
+            encode_string("é", 2, ByteOrder::Abcd),
+            Err(CodecError::NonAscii(0xC3))
+        );
+    }
+
+    #[test]
+    fn decodes_strings_up_to_nul() {
+        assert_eq!(
+            decode_string(&[0x6162, 0x6300, 0x6465], ByteOrder::Abcd),
+            Ok("abc".to_string())
+        );
+        assert_eq!(
+            decode_string(&[0x6261, 0x0063], ByteOrder::Dcba),
+            Ok("abc".to_string())
+        );
+        assert_eq!(
+            decode_string(&[0x61FF], ByteOrder::Abcd),
+            Err(CodecError::NonAscii(0xFF))
+        );
+    }
+}
@@ -26,6 +26,12 @@ impl ReadBuffer {
         self.begin == self.end
     }
 
+    /// discard all the bytes in the buffer
+    pub(crate) fn clear(&mut self) {
+        self.begin = 0;
+        self.end = 0;
+    }
+
     pub(crate) fn read(&mut self, count: usize) -> Result<&[u8], InternalError> {
         if self.len() < count {
             return Err(InternalError::InsufficientBytesForRead(count, self.len()));
@@ -135,8 +141,7 @@ mod tests {
         let mut phys = PhysLayer::new_mock(io);
 
         {
-            let mut task =
-                tokio_test::task::spawn(buffer.read_some(&mut phys, PhysDecodeLevel::Nothing));
+            let mut task = task::spawn(buffer.read_some(&mut phys, PhysDecodeLevel::Nothing));
             tokio_test::assert_pending!(task.poll());
         }
 
@@ -24,7 +24,10 @@ pub(crate) mod constants {
 
     #[cfg(feature = "serial")]
     const fn serial_frame_size() -> usize {
-        crate::serial::frame::constants::MAX_FRAME_LENGTH
+        max(
+            crate::serial::frame::constants::MAX_FRAME_LENGTH,
+            crate::serial::ascii::constants::MAX_FRAME_LENGTH,
+        )
     }
 
     #[cfg(not(feature = "serial"))]
@@ -174,6 +177,8 @@ impl Frame {
 pub(crate) enum FrameParser {
     #[cfg(feature = "serial")]
     Rtu(crate::serial::frame::RtuParser),
+    #[cfg(feature = "serial")]
+    Ascii(crate::serial::ascii::AsciiParser),
     Tcp(MbapParser),
 }
 
@@ -192,6 +197,8 @@ impl FrameParser {
         match self {
             #[cfg(feature = "serial")]
             FrameParser::Rtu(x) => x.parse(cursor, decode_level),
+            #[cfg(feature = "serial")]
+            FrameParser::Ascii(x) => x.parse(cursor, decode_level),
             FrameParser::Tcp(x) => x.parse(cursor, decode_level),
         }
     }
@@ -201,6 +208,8 @@ impl FrameParser {
         match self {
             #[cfg(feature = "serial")]
             FrameParser::Rtu(x) => x.reset(),
+            #[cfg(feature = "serial")]
+            FrameParser::Ascii(x) => x.reset(),
             FrameParser::Tcp(x) => x.reset(),
         }
     }
@@ -211,6 +220,9 @@ pub(crate) enum FrameType {
     #[cfg(feature = "serial")]
     // destination and CRC
     Rtu(FrameDestination, u16),
+    #[cfg(feature = "serial")]
+    // destination and LRC
+    Ascii(FrameDestination, u8),
 }
 
 pub(crate) struct FrameInfo {
@@ -233,6 +245,8 @@ enum FormatType {
     Tcp,
     #[cfg(feature = "serial")]
     Rtu,
+    #[cfg(feature = "serial")]
+    Ascii,
 }
 
 impl FormatType {
@@ -247,6 +261,21 @@ impl FormatType {
             FormatType::Tcp => crate::tcp::frame::format_mbap(cursor, header, function, body),
             #[cfg(feature = "serial")]
             FormatType::Rtu => crate::serial::frame::format_rtu_pdu(cursor, header, function, body),
+            #[cfg(feature = "serial")]
+            FormatType::Ascii => {
+                crate::serial::ascii::format_ascii_pdu(cursor, header, function, body)
+            }
+        }
+    }
+
+    /// maximum length of the frame written by [`FormatType::format`]
+    fn max_length(&self) -> usize {
+        match self {
+            FormatType::Tcp => crate::tcp::frame::constants::MAX_FRAME_LENGTH,
+            #[cfg(feature = "serial")]
+            FormatType::Rtu => crate::serial::frame::constants::MAX_FRAME_LENGTH,
+            #[cfg(feature = "serial")]
+            FormatType::Ascii => crate::serial::ascii::constants::MAX_BINARY_LENGTH,
         }
     }
 }
@@ -254,6 +283,8 @@ impl FormatType {
 pub(crate) struct FrameWriter {
     format_type: FormatType,
     buffer: [u8; constants::MAX_FRAME_LENGTH],
+    /// exception code of the last formatted frame if it was an exception response
+    exception: Option<ExceptionCode>,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -299,6 +330,7 @@ impl FrameWriter {
         Self {
             format_type,
             buffer: [0; constants::MAX_FRAME_LENGTH],
+            exception: None,
         }
     }
 
@@ -350,6 +382,7 @@ impl FrameWriter {
         };
 
         let range = self.format_generic(header, function, &ex, decode_level)?;
+        self.exception = Some(ex);
 
         Ok(&self.buffer[range])
     }
@@ -364,8 +397,11 @@ impl FrameWriter {
     where
         T: Serialize + Loggable,
     {
+        self.exception = None;
+
         let (frame_type, frame_bytes, pdu_body) = {
-            let mut cursor = WriteCursor::new(self.buffer.as_mut());
+            let max_length = self.format_type.max_length();
+            let mut cursor = WriteCursor::new(&mut self.buffer[..max_length]);
             let info = self
                 .format_type
                 .format(&mut cursor, header, function, body)?;
@@ -381,6 +417,15 @@ impl FrameWriter {
             );
         }
 
+        // ASCII frames are formatted in binary so that the PDU can be logged, then encoded
+        #[cfg(feature = "serial")]
+        let frame_bytes = match self.format_type {
+            FormatType::Ascii => {
+                crate::serial::ascii::encode_in_place(&mut self.buffer, frame_bytes.end)?
+            }
+            _ => frame_bytes,
+        };
+
         if decode_level.frame.enabled() {
             let frame_bytes = &self.buffer[frame_bytes.clone()];
             match frame_type {
@@ -402,12 +447,28 @@ impl FrameWriter {
                         )
                     );
                 }
+                #[cfg(feature = "serial")]
+                FrameType::Ascii(dest, lrc) => {
+                    tracing::info!(
+                        "ASCII TX - {}",
+                        crate::serial::ascii::AsciiDisplay::new(
+                            decode_level.frame,
+                            dest,
+                            frame_bytes,
+                            lrc
+                        )
+                    );
+                }
             }
         }
 
         Ok(frame_bytes)
     }
 
+    pub(crate) fn exception(&self) -> Option<ExceptionCode> {
+        self.exception
+    }
+
     pub(crate) fn tcp() -> Self {
         Self::new(FormatType::Tcp)
     }
@@ -416,6 +477,47 @@ impl FrameWriter {
     pub(crate) fn rtu() -> Self {
         Self::new(FormatType::Rtu)
     }
+
+    #[cfg(feature = "serial")]
+    pub(crate) fn ascii() -> Self {
+        Self::new(FormatType::Ascii)
+    }
+}
+
+/// Framing of the messages exchanged over a TCP or TLS connection
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TcpFraming {
+    /// Standard Modbus TCP framing using the MBAP header
+    Mbap,
+    /// RTU frames including the CRC, as forwarded by serial-to-Ethernet converters
+    #[cfg(feature = "serial")]
+    Rtu,
+}
+
+impl TcpFraming {
+    pub(crate) fn writer(self) -> FrameWriter {
+        match self {
+            TcpFraming::Mbap => FrameWriter::tcp(),
+            #[cfg(feature = "serial")]
+            TcpFraming::Rtu => FrameWriter::rtu(),
+        }
+    }
+
+    pub(crate) fn request_reader(self) -> FramedReader {
+        match self {
+            TcpFraming::Mbap => FramedReader::tcp(),
+            #[cfg(feature = "serial")]
+            TcpFraming::Rtu => FramedReader::rtu_request(Default::default()),
+        }
+    }
+
+    pub(crate) fn response_reader(self) -> FramedReader {
+        match self {
+            TcpFraming::Mbap => FramedReader::tcp(),
+            #[cfg(feature = "serial")]
+            TcpFraming::Rtu => FramedReader::rtu_response(Default::default()),
+        }
+    }
 }
 
 pub(crate) struct FramedReader {
@@ -429,19 +531,24 @@ impl FramedReader {
     }
 
     #[cfg(feature = "serial")]
-    pub(crate) fn rtu_request() -> Self {
+    pub(crate) fn rtu_request(custom: crate::serial::CustomFunctionLengths) -> Self {
         Self::new(FrameParser::Rtu(
-            crate::serial::frame::RtuParser::new_request_parser(),
+            crate::serial::frame::RtuParser::new_request_parser(custom),
         ))
     }
 
     #[cfg(feature = "serial")]
-    pub(crate) fn rtu_response() -> Self {
+    pub(crate) fn rtu_response(custom: crate::serial::CustomFunctionLengths) -> Self {
         Self::new(FrameParser::Rtu(
-            crate::serial::frame::RtuParser::new_response_parser(),
+            crate::serial::frame::RtuParser::new_response_parser(custom),
         ))
     }
 
+    #[cfg(feature = "serial")]
+    pub(crate) fn ascii() -> Self {
+        Self::new(FrameParser::Ascii(crate::serial::ascii::AsciiParser::new()))
+    }
+
     fn new(parser: FrameParser) -> Self {
         Self {
             parser,
@@ -456,11 +563,32 @@ impl FramedReader {
     ) -> Result<Frame, RequestError> {
         loop {
             match self.parser.parse(&mut self.buffer, decode_level.frame) {
-                Ok(Some(frame)) => return Ok(frame),
+                Ok(Some(frame)) => {
+                    if io.is_datagram() && !self.buffer.is_empty() {
+                        tracing::warn!(
+                            "discarding {} bytes that follow the frame in the datagram",
+                            self.buffer.len()
+                        );
+                        self.buffer.clear();
+                    }
+                    return Ok(frame);
+                }
                 Ok(None) => {
+                    // frames never span multiple datagrams
+                    if io.is_datagram() && !self.buffer.is_empty() {
+                        tracing::warn!(
+                            "discarding datagram with an incomplete frame of {} bytes",
+                            self.buffer.len()
+                        );
+                        self.buffer.clear();
+                        self.parser.reset();
+                    }
                     self.buffer.read_some(io, decode_level.physical).await?;
                 }
                 Err(err) => {
+                    if io.is_datagram() {
+                        self.buffer.clear();
+                    }
                     self.parser.reset();
                     return Err(err);
                 }
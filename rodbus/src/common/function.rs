@@ -7,21 +7,44 @@ mod constants {
     pub(crate) const READ_INPUT_REGISTERS: u8 = 4;
     pub(crate) const WRITE_SINGLE_COIL: u8 = 5;
     pub(crate) const WRITE_SINGLE_REGISTER: u8 = 6;
+    pub(crate) const READ_EXCEPTION_STATUS: u8 = 7;
+    pub(crate) const DIAGNOSTICS: u8 = 8;
+    pub(crate) const GET_COMM_EVENT_COUNTER: u8 = 11;
+    pub(crate) const GET_COMM_EVENT_LOG: u8 = 12;
     pub(crate) const WRITE_MULTIPLE_COILS: u8 = 15;
     pub(crate) const WRITE_MULTIPLE_REGISTERS: u8 = 16;
+    pub(crate) const REPORT_SERVER_ID: u8 = 17;
+    pub(crate) const READ_FILE_RECORD: u8 = 20;
+    pub(crate) const WRITE_FILE_RECORD: u8 = 21;
+    pub(crate) const MASK_WRITE_REGISTER: u8 = 22;
+    pub(crate) const READ_WRITE_MULTIPLE_REGISTERS: u8 = 23;
+    pub(crate) const READ_FIFO_QUEUE: u8 = 24;
+    pub(crate) const READ_DEVICE_IDENTIFICATION: u8 = 43;
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
-#[repr(u8)]
 pub(crate) enum FunctionCode {
-    ReadCoils = constants::READ_COILS,
-    ReadDiscreteInputs = constants::READ_DISCRETE_INPUTS,
-    ReadHoldingRegisters = constants::READ_HOLDING_REGISTERS,
-    ReadInputRegisters = constants::READ_INPUT_REGISTERS,
-    WriteSingleCoil = constants::WRITE_SINGLE_COIL,
-    WriteSingleRegister = constants::WRITE_SINGLE_REGISTER,
-    WriteMultipleCoils = constants::WRITE_MULTIPLE_COILS,
-    WriteMultipleRegisters = constants::WRITE_MULTIPLE_REGISTERS,
+    ReadCoils,
+    ReadDiscreteInputs,
+    ReadHoldingRegisters,
+    ReadInputRegisters,
+    WriteSingleCoil,
+    WriteSingleRegister,
+    ReadExceptionStatus,
+    Diagnostics,
+    GetCommEventCounter,
+    GetCommEventLog,
+    WriteMultipleCoils,
+    WriteMultipleRegisters,
+    ReportServerId,
+    ReadFileRecord,
+    WriteFileRecord,
+    MaskWriteRegister,
+    ReadWriteMultipleRegisters,
+    ReadFifoQueue,
+    ReadDeviceIdentification,
+    /// User defined or vendor specific function code that the library does not implement
+    Custom(u8),
 }
 
 impl Display for FunctionCode {
@@ -43,19 +66,76 @@ impl Display for FunctionCode {
             FunctionCode::WriteSingleRegister => {
                 write!(f, "WRITE SINGLE REGISTER ({:#04X})", self.get_value())
             }
+            FunctionCode::ReadExceptionStatus => {
+                write!(f, "READ EXCEPTION STATUS ({:#04X})", self.get_value())
+            }
+            FunctionCode::Diagnostics => write!(f, "DIAGNOSTICS ({:#04X})", self.get_value()),
+            FunctionCode::GetCommEventCounter => {
+                write!(f, "GET COMM EVENT COUNTER ({:#04X})", self.get_value())
+            }
+            FunctionCode::GetCommEventLog => {
+                write!(f, "GET COMM EVENT LOG ({:#04X})", self.get_value())
+            }
             FunctionCode::WriteMultipleCoils => {
                 write!(f, "WRITE MULTIPLE COILS ({:#04X})", self.get_value())
             }
             FunctionCode::WriteMultipleRegisters => {
                 write!(f, "WRITE MULTIPLE REGISTERS ({:#04X})", self.get_value())
             }
+            FunctionCode::ReportServerId => {
+                write!(f, "REPORT SERVER ID ({:#04X})", self.get_value())
+            }
+            FunctionCode::ReadFileRecord => {
+                write!(f, "READ FILE RECORD ({:#04X})", self.get_value())
+            }
+            FunctionCode::WriteFileRecord => {
+                write!(f, "WRITE FILE RECORD ({:#04X})", self.get_value())
+            }
+            FunctionCode::MaskWriteRegister => {
+                write!(f, "MASK WRITE REGISTER ({:#04X})", self.get_value())
+            }
+            FunctionCode::ReadWriteMultipleRegisters => {
+                write!(
+                    f,
+                    "READ WRITE MULTIPLE REGISTERS ({:#04X})",
+                    self.get_value()
+                )
+            }
+            FunctionCode::ReadFifoQueue => {
+                write!(f, "READ FIFO QUEUE ({:#04X})", self.get_value())
+            }
+            FunctionCode::ReadDeviceIdentification => {
+                write!(f, "READ DEVICE IDENTIFICATION ({:#04X})", self.get_value())
+            }
+            FunctionCode::Custom(_) => write!(f, "CUSTOM FUNCTION ({:#04X})", self.get_value()),
         }
     }
 }
 
 impl FunctionCode {
     pub(crate) const fn get_value(self) -> u8 {
-        self as u8
+        match self {
+            FunctionCode::ReadCoils => constants::READ_COILS,
+            FunctionCode::ReadDiscreteInputs => constants::READ_DISCRETE_INPUTS,
+            FunctionCode::ReadHoldingRegisters => constants::READ_HOLDING_REGISTERS,
+            FunctionCode::ReadInputRegisters => constants::READ_INPUT_REGISTERS,
+            FunctionCode::WriteSingleCoil => constants::WRITE_SINGLE_COIL,
+            FunctionCode::WriteSingleRegister => constants::WRITE_SINGLE_REGISTER,
+            FunctionCode::ReadExceptionStatus => constants::READ_EXCEPTION_STATUS,
+            FunctionCode::Diagnostics => constants::DIAGNOSTICS,
+            FunctionCode::GetCommEventCounter => constants::GET_COMM_EVENT_COUNTER,
+            FunctionCode::GetCommEventLog => constants::GET_COMM_EVENT_LOG,
+            FunctionCode::WriteMultipleCoils => constants::WRITE_MULTIPLE_COILS,
+            FunctionCode::WriteMultipleRegisters => constants::WRITE_MULTIPLE_REGISTERS,
+            FunctionCode::ReportServerId => constants::REPORT_SERVER_ID,
+            FunctionCode::ReadFileRecord => constants::READ_FILE_RECORD,
+            FunctionCode::WriteFileRecord => constants::WRITE_FILE_RECORD,
+            FunctionCode::MaskWriteRegister => constants::MASK_WRITE_REGISTER,
+            FunctionCode::ReadWriteMultipleRegisters => constants::READ_WRITE_MULTIPLE_REGISTERS,
+            FunctionCode::ReadFifoQueue => constants::READ_FIFO_QUEUE,
+            FunctionCode::ReadDeviceIdentification => constants::READ_DEVICE_IDENTIFICATION,
+            FunctionCode::Custom(x) => x,
+        }
     }
 
     pub(crate) const fn as_error(self) -> u8 {
@@ -70,9 +150,45 @@ impl FunctionCode {
             constants::READ_INPUT_REGISTERS => Some(FunctionCode::ReadInputRegisters),
             constants::WRITE_SINGLE_COIL => Some(FunctionCode::WriteSingleCoil),
             constants::WRITE_SINGLE_REGISTER => Some(FunctionCode::WriteSingleRegister),
+            constants::READ_EXCEPTION_STATUS => Some(FunctionCode::ReadExceptionStatus),
+            constants::DIAGNOSTICS => Some(FunctionCode::Diagnostics),
+            constants::GET_COMM_EVENT_COUNTER => Some(FunctionCode::GetCommEventCounter),
+            constants::GET_COMM_EVENT_LOG => Some(FunctionCode::GetCommEventLog),
             constants::WRITE_MULTIPLE_COILS => Some(FunctionCode::WriteMultipleCoils),
             constants::WRITE_MULTIPLE_REGISTERS => Some(FunctionCode::WriteMultipleRegisters),
+            constants::REPORT_SERVER_ID => Some(FunctionCode::ReportServerId),
+            constants::READ_FILE_RECORD => Some(FunctionCode::ReadFileRecord),
+            constants::WRITE_FILE_RECORD => Some(FunctionCode::WriteFileRecord),
+            constants::MASK_WRITE_REGISTER => Some(FunctionCode::MaskWriteRegister),
+            constants::READ_WRITE_MULTIPLE_REGISTERS => {
+                Some(FunctionCode::ReadWriteMultipleRegisters)
+            }
+            constants::READ_FIFO_QUEUE => Some(FunctionCode::ReadFifoQueue),
+            constants::READ_DEVICE_IDENTIFICATION => Some(FunctionCode::ReadDeviceIdentification),
             _ => None,
         }
     }
+
+    /// function codes that are not implemented by the library may be used as custom functions,
+    /// excluding zero and the values that indicate an exception
+    pub(crate) fn custom(value: u8) -> Option<Self> {
+        if value == 0 || value & 0x80 != 0 || Self::get(value).is_some() {
+            return None;
+        }
+        Some(FunctionCode::Custom(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unimplemented_function_codes_are_custom() {
+        assert_eq!(FunctionCode::custom(0x41), Some(FunctionCode::Custom(0x41)));
+        assert_eq!(FunctionCode::custom(0x64), Some(FunctionCode::Custom(0x64)));
+        assert_eq!(FunctionCode::custom(0x00), None);
+        assert_eq!(FunctionCode::custom(0x03), None);
+        assert_eq!(FunctionCode::custom(0xC1), None);
+    }
 }
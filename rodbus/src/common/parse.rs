@@ -1,6 +1,7 @@
 use crate::common::traits::Parse;
+use crate::diagnostics::{CommEventCounter, DiagnosticsRequest};
 use crate::error::*;
-use crate::types::{coil_from_u16, AddressRange, Indexed};
+use crate::types::{coil_from_u16, AddressRange, Indexed, MaskWriteRegister};
 
 use scursor::ReadCursor;
 
@@ -28,6 +29,34 @@ impl Parse for Indexed<u16> {
     }
 }
 
+impl Parse for MaskWriteRegister {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        Ok(MaskWriteRegister::new(
+            cursor.read_u16_be()?,
+            cursor.read_u16_be()?,
+            cursor.read_u16_be()?,
+        ))
+    }
+}
+
+impl Parse for DiagnosticsRequest {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        Ok(DiagnosticsRequest::new(
+            cursor.read_u16_be()?,
+            cursor.read_u16_be()?,
+        ))
+    }
+}
+
+impl Parse for CommEventCounter {
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        Ok(CommEventCounter::new(
+            crate::diagnostics::parse_status(cursor)?,
+            cursor.read_u16_be()?,
+        ))
+    }
+}
+
 #[cfg(test)]
 mod coils {
     use crate::common::traits::Parse;
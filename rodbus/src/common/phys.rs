@@ -9,6 +9,9 @@ pub(crate) struct PhysLayer {
 // encapsulates all possible physical layers as an enum
 pub(crate) enum PhysLayerImpl {
     Tcp(tokio::net::TcpStream),
+    // connected socket of a client
+    Udp(tokio::net::UdpSocket),
+    UdpServer(crate::udp::server::UdpServerSocket),
     #[cfg(feature = "serial")]
     Serial(
         tokio_serial::SerialStream,
@@ -26,6 +29,8 @@ impl std::fmt::Debug for PhysLayer {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match &self.layer {
             PhysLayerImpl::Tcp(_) => f.write_str("Tcp"),
+            PhysLayerImpl::Udp(_) => f.write_str("Udp"),
+            PhysLayerImpl::UdpServer(_) => f.write_str("UdpServer"),
             #[cfg(feature = "serial")]
             PhysLayerImpl::Serial(_, _, _) => f.write_str("Serial"),
             #[cfg(feature = "tls")]
@@ -43,6 +48,18 @@ impl PhysLayer {
         }
     }
 
+    pub(crate) fn new_udp(socket: tokio::net::UdpSocket) -> Self {
+        Self {
+            layer: PhysLayerImpl::Udp(socket),
+        }
+    }
+
+    pub(crate) fn new_udp_server(socket: crate::udp::server::UdpServerSocket) -> Self {
+        Self {
+            layer: PhysLayerImpl::UdpServer(socket),
+        }
+    }
+
     #[cfg(feature = "serial")]
     pub(crate) fn new_serial(stream: tokio_serial::SerialStream) -> Self {
         let calculate_inter_character_delay = calculate_inter_character_delay(&stream);
@@ -65,6 +82,56 @@ impl PhysLayer {
         }
     }
 
+    /// kind of transport reported in the context of server requests
+    pub(crate) fn transport(&self) -> crate::server::TransportKind {
+        match &self.layer {
+            PhysLayerImpl::Tcp(_) => crate::server::TransportKind::Tcp,
+            PhysLayerImpl::Udp(_) | PhysLayerImpl::UdpServer(_) => {
+                crate::server::TransportKind::Udp
+            }
+            #[cfg(feature = "serial")]
+            PhysLayerImpl::Serial(_, _, _) => crate::server::TransportKind::Serial,
+            #[cfg(feature = "tls")]
+            PhysLayerImpl::Tls(_) => crate::server::TransportKind::Tls,
+            #[cfg(test)]
+            PhysLayerImpl::Mock(_) => crate::server::TransportKind::Tcp,
+        }
+    }
+
+    /// address of the remote end, e.g. the client of a server session
+    pub(crate) fn peer_addr(&self) -> Option<std::net::SocketAddr> {
+        match &self.layer {
+            PhysLayerImpl::Tcp(x) => x.peer_addr().ok(),
+            PhysLayerImpl::Udp(x) => x.peer_addr().ok(),
+            PhysLayerImpl::UdpServer(x) => x.peer(),
+            #[cfg(feature = "serial")]
+            PhysLayerImpl::Serial(_, _, _) => None,
+            #[cfg(feature = "tls")]
+            PhysLayerImpl::Tls(x) => x.get_ref().0.peer_addr().ok(),
+            #[cfg(test)]
+            PhysLayerImpl::Mock(_) => None,
+        }
+    }
+
+    /// change the addresses from which a UDP server accepts datagrams, returns false for other layers
+    pub(crate) fn set_address_filter(&mut self, filter: crate::server::AddressFilter) -> bool {
+        match &mut self.layer {
+            PhysLayerImpl::UdpServer(socket) => {
+                socket.set_filter(filter);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// true if each read returns a single datagram which contains a complete frame
+    pub(crate) fn is_datagram(&self) -> bool {
+        matches!(
+            self.layer,
+            PhysLayerImpl::Udp(_) | PhysLayerImpl::UdpServer(_)
+        )
+    }
+
     pub(crate) async fn read(
         &mut self,
         buffer: &mut [u8],
@@ -72,6 +139,8 @@ impl PhysLayer {
     ) -> Result<usize, std::io::Error> {
         let length = match &mut self.layer {
             PhysLayerImpl::Tcp(x) => x.read(buffer).await?,
+            PhysLayerImpl::Udp(x) => x.recv(buffer).await?,
+            PhysLayerImpl::UdpServer(x) => x.recv(buffer).await?,
             #[cfg(feature = "serial")]
             PhysLayerImpl::Serial(x, _, _) => x.read(buffer).await?,
             #[cfg(feature = "tls")]
@@ -100,6 +169,8 @@ impl PhysLayer {
 
         match &mut self.layer {
             PhysLayerImpl::Tcp(x) => x.write_all(data).await,
+            PhysLayerImpl::Udp(x) => x.send(data).await.map(|_| ()),
+            PhysLayerImpl::UdpServer(x) => x.send(data).await,
             #[cfg(feature = "serial")]
             PhysLayerImpl::Serial(x, inter_char_delay, last_activity) => {
                 // Respect inter-character delay
@@ -1,14 +1,25 @@
 use std::convert::TryFrom;
 
-use crate::client::WriteMultiple;
+use crate::client::{ReadWriteMultiple, WriteMultiple};
 use crate::common::traits::Loggable;
 use crate::common::traits::Parse;
 use crate::common::traits::Serialize;
+use crate::device_id::{DeviceIdReply, DeviceIdReplyDisplay};
+use crate::diagnostics::{
+    CommEventCounter, CommEventLog, CommEventLogDisplay, DiagnosticsRequest, ServerIdDisplay,
+};
 use crate::error::{InternalError, RequestError};
-use crate::server::response::{BitWriter, RegisterWriter};
+use crate::file_record::{
+    FileRecordData, FileRecordDataDisplay, WriteFileRecords, WriteFileRecordsDisplay,
+};
+use crate::server::response::{
+    BitWriter, CustomFunctionResponse, DeviceIdWriter, ExceptionStatus, FifoQueue,
+    FileRecordWriter, RegisterWriter, ServerId,
+};
 use crate::types::{
-    coil_from_u16, coil_to_u16, AddressRange, BitIterator, BitIteratorDisplay, Indexed,
-    RegisterIterator, RegisterIteratorDisplay,
+    coil_from_u16, coil_to_u16, AddressRange, BitIterator, BitIteratorDisplay,
+    CustomFunctionDisplay, FifoQueueDisplay, Indexed, MaskWriteRegister, RegisterIterator,
+    RegisterIteratorDisplay,
 };
 
 use scursor::{ReadCursor, WriteCursor};
@@ -134,6 +145,33 @@ impl Loggable for Indexed<u16> {
     }
 }
 
+impl Serialize for MaskWriteRegister {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u16_be(self.address)?;
+        cursor.write_u16_be(self.and_mask)?;
+        cursor.write_u16_be(self.or_mask)?;
+        Ok(())
+    }
+}
+
+impl Loggable for MaskWriteRegister {
+    fn log(
+        &self,
+        payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            let mut cursor = ReadCursor::new(payload);
+            if let Ok(value) = MaskWriteRegister::parse(&mut cursor) {
+                write!(f, "{}", value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Serialize for &[bool] {
     fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
         // how many bytes should we have?
@@ -263,6 +301,315 @@ where
     }
 }
 
+impl<T> Serialize for FileRecordWriter<'_, T>
+where
+    T: Fn(u16, u16) -> Result<u16, crate::exception::ExceptionCode>,
+{
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        // length was validated when the request was parsed
+        let length: usize = self.records.iter().map(|x| x.response_length()).sum();
+        cursor.write_u8(length as u8)?;
+
+        for record in self.records.iter() {
+            // the sub-response length includes the reference type
+            cursor.write_u8((record.response_length() - 1) as u8)?;
+            cursor.write_u8(crate::file_record::REFERENCE_TYPE)?;
+            for offset in 0..record.record_length {
+                let value = (self.getter)(record.file_number, record.record_number + offset)?;
+                cursor.write_u16_be(value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Loggable for FileRecordWriter<'_, T>
+where
+    T: Fn(u16, u16) -> Result<u16, crate::exception::ExceptionCode>,
+{
+    fn log(
+        &self,
+        payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            let mut cursor = ReadCursor::new(payload);
+            let _ = cursor.read_u8(); // ignore the byte count
+
+            let mut records = Vec::new();
+            for record in self.records.iter() {
+                // ignore the sub-response length and reference type
+                if cursor.read_u16_be().is_err() {
+                    return Ok(());
+                }
+                let mut values = Vec::with_capacity(record.record_length as usize);
+                for _ in 0..record.record_length {
+                    match cursor.read_u16_be() {
+                        Ok(x) => values.push(x),
+                        Err(_) => return Ok(()),
+                    }
+                }
+                records.push(FileRecordData::new(
+                    record.file_number,
+                    record.record_number,
+                    values,
+                ));
+            }
+
+            write!(f, "{}", FileRecordDataDisplay::new(level, &records))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for WriteFileRecords<'_> {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u8(self.bytes.len() as u8)?;
+        cursor.write_bytes(self.bytes)?;
+        Ok(())
+    }
+}
+
+impl Loggable for WriteFileRecords<'_> {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", WriteFileRecordsDisplay::new(level, *self))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for FifoQueue {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        // the byte count includes the 2 bytes of the FIFO count
+        let num_bytes = 2 + calc_bytes_for_registers(self.values.len())? as u16;
+        cursor.write_u16_be(num_bytes)?;
+        cursor.write_u16_be(self.values.len() as u16)?;
+
+        for value in &self.values {
+            cursor.write_u16_be(*value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Loggable for FifoQueue {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", FifoQueueDisplay::new(level, &self.values))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for DeviceIdWriter<'_> {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u8(crate::device_id::MEI_TYPE_READ_DEVICE_ID)?;
+        cursor.write_u8(self.code.get_value())?;
+        cursor.write_u8(self.store.conformity_level())?;
+        match self.next_object_id {
+            Some(next) => {
+                cursor.write_u8(crate::device_id::MORE_FOLLOWS)?;
+                cursor.write_u8(next)?;
+            }
+            None => {
+                cursor.write_u8(crate::device_id::NO_MORE_FOLLOWS)?;
+                cursor.write_u8(0)?;
+            }
+        }
+
+        // the objects were sized to fit in the response when the writer was created
+        let count = self.store.range(self.first, self.last).count();
+        cursor.write_u8(count as u8)?;
+        for (id, value) in self.store.range(self.first, self.last) {
+            cursor.write_u8(id)?;
+            cursor.write_u8(value.len() as u8)?;
+            cursor.write_bytes(value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Loggable for DeviceIdWriter<'_> {
+    fn log(
+        &self,
+        payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            let mut cursor = ReadCursor::new(payload);
+            if let Ok(reply) = DeviceIdReply::parse(&mut cursor) {
+                write!(f, "{}", DeviceIdReplyDisplay::new(level, &reply))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for DiagnosticsRequest {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u16_be(self.sub_function)?;
+        cursor.write_u16_be(self.data)?;
+        Ok(())
+    }
+}
+
+impl Loggable for DiagnosticsRequest {
+    fn log(
+        &self,
+        payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            let mut cursor = ReadCursor::new(payload);
+            if let Ok(value) = DiagnosticsRequest::parse(&mut cursor) {
+                write!(f, "{}", value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for ExceptionStatus {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u8(self.value)?;
+        Ok(())
+    }
+}
+
+impl Loggable for ExceptionStatus {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "status: {:#04X}", self.value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for CommEventCounter {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_u16_be(crate::diagnostics::status_to_u16(self.busy))?;
+        cursor.write_u16_be(self.event_count)?;
+        Ok(())
+    }
+}
+
+impl Loggable for CommEventCounter {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", self)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for CommEventLog {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        // the byte count includes the status, event count and message count
+        let num_bytes = 6 + self.events.len();
+        cursor.write_u8(num_bytes as u8)?;
+        cursor.write_u16_be(crate::diagnostics::status_to_u16(self.busy))?;
+        cursor.write_u16_be(self.event_count)?;
+        cursor.write_u16_be(self.message_count)?;
+        cursor.write_bytes(&self.events)?;
+        Ok(())
+    }
+}
+
+impl Loggable for CommEventLog {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", CommEventLogDisplay::new(level, self))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for ServerId {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        // the length was validated when the response was created
+        cursor.write_u8(self.data.len() as u8)?;
+        cursor.write_bytes(&self.data)?;
+        Ok(())
+    }
+}
+
+impl Loggable for ServerId {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", ServerIdDisplay::new(level, &self.data))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Serialize for CustomFunctionResponse {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        cursor.write_bytes(&self.data)?;
+        Ok(())
+    }
+}
+
+impl Loggable for CustomFunctionResponse {
+    fn log(
+        &self,
+        _payload: &[u8],
+        level: crate::decode::AppDecodeLevel,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        if level.data_headers() {
+            write!(f, "{}", CustomFunctionDisplay::new(level, &self.data))?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Serialize for &[u16] {
     fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
         let num_bytes = calc_bytes_for_registers(self.len())?;
@@ -290,6 +637,13 @@ impl Serialize for WriteMultiple<u16> {
     }
 }
 
+impl Serialize for ReadWriteMultiple {
+    fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        self.read_range.get().serialize(cursor)?;
+        self.write.serialize(cursor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
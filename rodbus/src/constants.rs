@@ -16,6 +16,72 @@ pub mod limits {
     pub const MAX_WRITE_COILS_COUNT: u16 = 0x07B0;
     /// Maximum count allowed in a `write multiple registers` request
     pub const MAX_WRITE_REGISTERS_COUNT: u16 = 0x007B;
+    /// Maximum count of values returned in a `read FIFO queue` response
+    pub const MAX_FIFO_COUNT: u16 = 0x001F;
+    /// Maximum count of registers written in a `read/write multiple registers` request
+    pub const MAX_READ_WRITE_WRITE_REGISTERS_COUNT: u16 = 0x0079;
+    /// Maximum length of a single object in a `read device identification` response
+    pub const MAX_DEVICE_ID_OBJECT_LENGTH: usize = 0xF4;
+    /// Maximum number of events returned in a `get comm event log` response
+    pub const MAX_COMM_EVENT_LOG_COUNT: usize = 0x40;
+    /// Maximum length of the data returned in a `report server id` response
+    pub const MAX_REPORT_SERVER_ID_LENGTH: usize = 0xFB;
+    /// Maximum length of the data that follows the function code of a custom function
+    pub const MAX_CUSTOM_FUNCTION_DATA_LENGTH: usize = 0xFC;
+    /// Maximum record number that may be referenced in a `read/write file record` request
+    pub const MAX_FILE_RECORD_NUMBER: u16 = 0x270F;
+    /// Maximum length of the sub-responses returned in a `read file record` response
+    pub const MAX_READ_FILE_RECORD_LENGTH: usize = 0xF5;
+    /// Maximum length of the sub-requests sent in a `write file record` request
+    pub const MAX_WRITE_FILE_RECORD_LENGTH: usize = 0xFB;
+}
+
+/// Object ids defined for `read device identification` requests
+pub mod device_id {
+    /// Vendor name (basic, mandatory)
+    pub const VENDOR_NAME: u8 = 0x00;
+    /// Product code (basic, mandatory)
+    pub const PRODUCT_CODE: u8 = 0x01;
+    /// Major and minor revision (basic, mandatory)
+    pub const MAJOR_MINOR_REVISION: u8 = 0x02;
+    /// Vendor URL (regular, optional)
+    pub const VENDOR_URL: u8 = 0x03;
+    /// Product name (regular, optional)
+    pub const PRODUCT_NAME: u8 = 0x04;
+    /// Model name (regular, optional)
+    pub const MODEL_NAME: u8 = 0x05;
+    /// User application name (regular, optional)
+    pub const USER_APPLICATION_NAME: u8 = 0x06;
+    /// First object id of the private extended objects
+    pub const FIRST_EXTENDED: u8 = 0x80;
+}
+
+/// Event bytes returned in a `get comm event log` response
+pub mod comm_event {
+    /// Set in every event stored when a request is received
+    pub const RECEIVE: u8 = 0x80;
+    /// Receive event flag: a communication error (e.g. CRC failure) was detected
+    pub const RECEIVE_COMMUNICATION_ERROR: u8 = 0x02;
+    /// Receive event flag: the device was in listen only mode
+    pub const RECEIVE_LISTEN_ONLY_MODE: u8 = 0x20;
+    /// Receive event flag: the request was a broadcast
+    pub const RECEIVE_BROADCAST: u8 = 0x40;
+    /// Set in every event stored when the device finishes processing a request
+    pub const SEND: u8 = 0x40;
+    /// Send event flag: a read exception (codes 1 to 3) was returned
+    pub const SEND_READ_EXCEPTION: u8 = 0x01;
+    /// Send event flag: a server abort exception (code 4) was returned
+    pub const SEND_ABORT_EXCEPTION: u8 = 0x02;
+    /// Send event flag: a busy exception (codes 5 and 6) was returned
+    pub const SEND_BUSY_EXCEPTION: u8 = 0x04;
+    /// Send event flag: a negative acknowledge exception (code 7) was returned
+    pub const SEND_NAK_EXCEPTION: u8 = 0x08;
+    /// Send event flag: no response was sent because the device was in listen only mode
+    pub const SEND_LISTEN_ONLY_MODE: u8 = 0x20;
+    /// The device entered listen only mode
+    pub const ENTERED_LISTEN_ONLY_MODE: u8 = 0x04;
+    /// The communications port was restarted
+    pub const COMMUNICATION_RESTART: u8 = 0x00;
 }
 
 /// Modbus exception codes
@@ -0,0 +1,260 @@
+use crate::decode::AppDecodeLevel;
+use crate::error::{AduParseError, RequestError};
+
+use scursor::ReadCursor;
+
+/// MEI type of the read device identification request
+pub(crate) const MEI_TYPE_READ_DEVICE_ID: u8 = 0x0E;
+
+/// value of the "more follows" field when more objects are available
+pub(crate) const MORE_FOLLOWS: u8 = 0xFF;
+/// value of the "more follows" field when no more objects are available
+pub(crate) const NO_MORE_FOLLOWS: u8 = 0x00;
+
+/// Access type used when reading device identification objects
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReadDeviceIdCode {
+    /// Stream the basic objects (vendor name, product code and revision)
+    Basic,
+    /// Stream the basic and regular objects
+    Regular,
+    /// Stream the basic, regular and extended objects
+    Extended,
+    /// Read a single object
+    Individual,
+}
+
+impl ReadDeviceIdCode {
+    pub(crate) fn get_value(self) -> u8 {
+        match self {
+            ReadDeviceIdCode::Basic => 0x01,
+            ReadDeviceIdCode::Regular => 0x02,
+            ReadDeviceIdCode::Extended => 0x03,
+            ReadDeviceIdCode::Individual => 0x04,
+        }
+    }
+
+    pub(crate) fn get(value: u8) -> Option<Self> {
+        match value {
+            0x01 => Some(ReadDeviceIdCode::Basic),
+            0x02 => Some(ReadDeviceIdCode::Regular),
+            0x03 => Some(ReadDeviceIdCode::Extended),
+            0x04 => Some(ReadDeviceIdCode::Individual),
+            _ => None,
+        }
+    }
+
+    /// last object id that is streamed using this access type
+    pub(crate) fn last_object_id(self) -> u8 {
+        match self {
+            ReadDeviceIdCode::Basic => crate::constants::device_id::MAJOR_MINOR_REVISION,
+            ReadDeviceIdCode::Regular => crate::constants::device_id::FIRST_EXTENDED - 1,
+            ReadDeviceIdCode::Extended => u8::MAX,
+            ReadDeviceIdCode::Individual => u8::MAX,
+        }
+    }
+
+    pub(crate) fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        let value = cursor.read_u8()?;
+        Self::get(value).ok_or_else(|| AduParseError::UnknownReadDeviceIdCode(value).into())
+    }
+}
+
+impl std::fmt::Display for ReadDeviceIdCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadDeviceIdCode::Basic => f.write_str("basic"),
+            ReadDeviceIdCode::Regular => f.write_str("regular"),
+            ReadDeviceIdCode::Extended => f.write_str("extended"),
+            ReadDeviceIdCode::Individual => f.write_str("individual"),
+        }
+    }
+}
+
+/// Device identification object
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceIdObject {
+    /// Id of the object
+    pub id: u8,
+    /// Raw value of the object
+    pub value: Vec<u8>,
+}
+
+impl DeviceIdObject {
+    /// Create a new object from its id and value
+    pub fn new(id: u8, value: Vec<u8>) -> Self {
+        Self { id, value }
+    }
+
+    /// Interpret the value as a string if it contains valid UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.value).ok()
+    }
+}
+
+impl std::fmt::Display for DeviceIdObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "id: {:#04X} value: {}",
+            self.id,
+            String::from_utf8_lossy(&self.value)
+        )
+    }
+}
+
+/// Device identification objects read from a server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceIdentification {
+    /// Conformity level reported by the server
+    pub conformity_level: u8,
+    /// Objects in the order they were received
+    pub objects: Vec<DeviceIdObject>,
+}
+
+impl DeviceIdentification {
+    pub(crate) fn new(conformity_level: u8) -> Self {
+        Self {
+            conformity_level,
+            objects: Vec::new(),
+        }
+    }
+
+    /// Retrieve an object by its id
+    pub fn get(&self, id: u8) -> Option<&DeviceIdObject> {
+        self.objects.iter().find(|x| x.id == id)
+    }
+}
+
+/// Objects returned in a single read device identification response
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DeviceIdReply {
+    pub(crate) code: ReadDeviceIdCode,
+    pub(crate) conformity_level: u8,
+    /// id of the object that starts the next request, if more objects follow
+    pub(crate) next_object_id: Option<u8>,
+    pub(crate) objects: Vec<DeviceIdObject>,
+}
+
+impl DeviceIdReply {
+    /// parse a response, the function code has already been consumed
+    pub(crate) fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        let mei_type = cursor.read_u8()?;
+        if mei_type != MEI_TYPE_READ_DEVICE_ID {
+            return Err(AduParseError::UnknownMeiType(mei_type).into());
+        }
+        let code = ReadDeviceIdCode::parse(cursor)?;
+        let conformity_level = cursor.read_u8()?;
+        let more_follows = match cursor.read_u8()? {
+            MORE_FOLLOWS => true,
+            NO_MORE_FOLLOWS => false,
+            x => return Err(AduParseError::UnknownMoreFollows(x).into()),
+        };
+        let next_object_id = cursor.read_u8()?;
+        let count = cursor.read_u8()?;
+
+        let mut objects = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let id = cursor.read_u8()?;
+            let length = cursor.read_u8()?;
+            let value = cursor.read_bytes(length as usize)?;
+            objects.push(DeviceIdObject::new(id, value.to_vec()));
+        }
+        cursor.expect_empty()?;
+
+        // individual access never continues
+        let next_object_id = if more_follows && code != ReadDeviceIdCode::Individual {
+            // the next object must come after the objects already received or we'd loop forever
+            match objects.last() {
+                Some(last) if next_object_id > last.id => Some(next_object_id),
+                _ => return Err(AduParseError::InvalidNextObjectId(next_object_id).into()),
+            }
+        } else {
+            None
+        };
+
+        Ok(Self {
+            code,
+            conformity_level,
+            next_object_id,
+            objects,
+        })
+    }
+}
+
+pub(crate) struct DeviceIdReplyDisplay<'a> {
+    reply: &'a DeviceIdReply,
+    level: AppDecodeLevel,
+}
+
+impl<'a> DeviceIdReplyDisplay<'a> {
+    pub(crate) fn new(level: AppDecodeLevel, reply: &'a DeviceIdReply) -> Self {
+        Self { reply, level }
+    }
+}
+
+impl std::fmt::Display for DeviceIdReplyDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "code: {} conformity: {:#04X} count: {}",
+            self.reply.code,
+            self.reply.conformity_level,
+            self.reply.objects.len()
+        )?;
+
+        if let Some(next) = self.reply.next_object_id {
+            write!(f, " next: {:#04X}", next)?;
+        }
+
+        if self.level.data_values() {
+            for x in &self.reply.objects {
+                write!(f, "\n{}", x)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_reply_with_more_follows() {
+        let mut cursor = ReadCursor::new(&[
+            0x0E, 0x01, 0x81, 0xFF, 0x02, 0x02, 0x00, 0x02, b'A', b'B', 0x01, 0x01, b'C',
+        ]);
+        assert_eq!(
+            DeviceIdReply::parse(&mut cursor),
+            Ok(DeviceIdReply {
+                code: ReadDeviceIdCode::Basic,
+                conformity_level: 0x81,
+                next_object_id: Some(0x02),
+                objects: vec![
+                    DeviceIdObject::new(0x00, b"AB".to_vec()),
+                    DeviceIdObject::new(0x01, b"C".to_vec()),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn fails_when_next_object_id_does_not_progress() {
+        let mut cursor = ReadCursor::new(&[0x0E, 0x01, 0x81, 0xFF, 0x00, 0x01, 0x00, 0x01, b'A']);
+        assert_eq!(
+            DeviceIdReply::parse(&mut cursor),
+            Err(AduParseError::InvalidNextObjectId(0x00).into())
+        );
+    }
+
+    #[test]
+    fn fails_on_unknown_mei_type() {
+        let mut cursor = ReadCursor::new(&[0x0D, 0x01, 0x81, 0x00, 0x00, 0x00]);
+        assert_eq!(
+            DeviceIdReply::parse(&mut cursor),
+            Err(AduParseError::UnknownMeiType(0x0D).into())
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use crate::decode::AppDecodeLevel;
+use crate::error::{AduParseError, RequestError};
+
+use scursor::ReadCursor;
+
+/// Sub-function codes of the diagnostics function
+pub(crate) mod sub_function {
+    pub(crate) const RETURN_QUERY_DATA: u16 = 0x00;
+    pub(crate) const RESTART_COMMUNICATIONS: u16 = 0x01;
+    pub(crate) const FORCE_LISTEN_ONLY_MODE: u16 = 0x04;
+    pub(crate) const CLEAR_COUNTERS: u16 = 0x0A;
+    pub(crate) const BUS_MESSAGE_COUNT: u16 = 0x0B;
+    pub(crate) const BUS_COMMUNICATION_ERROR_COUNT: u16 = 0x0C;
+    pub(crate) const BUS_EXCEPTION_ERROR_COUNT: u16 = 0x0D;
+    pub(crate) const SERVER_MESSAGE_COUNT: u16 = 0x0E;
+    pub(crate) const SERVER_NO_RESPONSE_COUNT: u16 = 0x0F;
+}
+
+/// data of a restart communications request that also clears the event log
+pub(crate) const CLEAR_EVENT_LOG: u16 = 0xFF00;
+
+/// status word when the device is still processing a previous command
+pub(crate) const STATUS_BUSY: u16 = 0xFFFF;
+/// status word when the device is ready
+pub(crate) const STATUS_READY: u16 = 0x0000;
+
+pub(crate) fn status_to_u16(busy: bool) -> u16 {
+    if busy {
+        STATUS_BUSY
+    } else {
+        STATUS_READY
+    }
+}
+
+pub(crate) fn parse_status(cursor: &mut ReadCursor) -> Result<bool, RequestError> {
+    match cursor.read_u16_be()? {
+        STATUS_BUSY => Ok(true),
+        STATUS_READY => Ok(false),
+        x => Err(AduParseError::UnknownCommStatus(x).into()),
+    }
+}
+
+/// Counters that can be read using the diagnostics function
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiagnosticCounter {
+    /// Messages detected on the bus, regardless of their destination
+    BusMessage,
+    /// Messages with a CRC error detected on the bus
+    BusCommunicationError,
+    /// Exception responses returned by the device
+    BusExceptionError,
+    /// Messages addressed to the device, including broadcasts
+    ServerMessage,
+    /// Messages addressed to the device for which no response was returned
+    ServerNoResponse,
+}
+
+impl DiagnosticCounter {
+    pub(crate) fn sub_function(self) -> u16 {
+        match self {
+            DiagnosticCounter::BusMessage => sub_function::BUS_MESSAGE_COUNT,
+            DiagnosticCounter::BusCommunicationError => sub_function::BUS_COMMUNICATION_ERROR_COUNT,
+            DiagnosticCounter::BusExceptionError => sub_function::BUS_EXCEPTION_ERROR_COUNT,
+            DiagnosticCounter::ServerMessage => sub_function::SERVER_MESSAGE_COUNT,
+            DiagnosticCounter::ServerNoResponse => sub_function::SERVER_NO_RESPONSE_COUNT,
+        }
+    }
+
+    pub(crate) fn get(sub_function: u16) -> Option<Self> {
+        match sub_function {
+            sub_function::BUS_MESSAGE_COUNT => Some(DiagnosticCounter::BusMessage),
+            sub_function::BUS_COMMUNICATION_ERROR_COUNT => {
+                Some(DiagnosticCounter::BusCommunicationError)
+            }
+            sub_function::BUS_EXCEPTION_ERROR_COUNT => Some(DiagnosticCounter::BusExceptionError),
+            sub_function::SERVER_MESSAGE_COUNT => Some(DiagnosticCounter::ServerMessage),
+            sub_function::SERVER_NO_RESPONSE_COUNT => Some(DiagnosticCounter::ServerNoResponse),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticCounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiagnosticCounter::BusMessage => f.write_str("bus message"),
+            DiagnosticCounter::BusCommunicationError => f.write_str("bus communication error"),
+            DiagnosticCounter::BusExceptionError => f.write_str("bus exception error"),
+            DiagnosticCounter::ServerMessage => f.write_str("server message"),
+            DiagnosticCounter::ServerNoResponse => f.write_str("server no response"),
+        }
+    }
+}
+
+/// Body of a diagnostics request, which the response echoes or answers with a counter value
+///
+/// Only a single data word is supported so that the RTU framing remains fixed length.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DiagnosticsRequest {
+    pub(crate) sub_function: u16,
+    pub(crate) data: u16,
+}
+
+impl DiagnosticsRequest {
+    pub(crate) fn new(sub_function: u16, data: u16) -> Self {
+        Self { sub_function, data }
+    }
+
+    /// true if the sub-function only reads information from the device
+    pub(crate) fn is_read_only(&self) -> bool {
+        self.sub_function == sub_function::RETURN_QUERY_DATA
+            || DiagnosticCounter::get(self.sub_function).is_some()
+    }
+
+    /// true if a response to this sub-function echoes the entire request
+    pub(crate) fn is_echo(&self) -> bool {
+        DiagnosticCounter::get(self.sub_function).is_none()
+    }
+}
+
+impl std::fmt::Display for DiagnosticsRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "sub-function: {:#06X} data: {:#06X}",
+            self.sub_function, self.data
+        )
+    }
+}
+
+/// Status and event counter returned by the device
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CommEventCounter {
+    /// True if the device is still processing a previous command
+    pub busy: bool,
+    /// Number of requests that completed successfully
+    pub event_count: u16,
+}
+
+impl CommEventCounter {
+    /// Create a new counter value
+    pub fn new(busy: bool, event_count: u16) -> Self {
+        Self { busy, event_count }
+    }
+}
+
+impl std::fmt::Display for CommEventCounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "busy: {} events: {}", self.busy, self.event_count)
+    }
+}
+
+/// Status, counters and recent communication events returned by the device
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommEventLog {
+    /// True if the device is still processing a previous command
+    pub busy: bool,
+    /// Number of requests that completed successfully
+    pub event_count: u16,
+    /// Number of messages detected on the bus
+    pub message_count: u16,
+    /// Event bytes with the most recent event first, see [`crate::constants::comm_event`]
+    pub events: Vec<u8>,
+}
+
+impl CommEventLog {
+    /// status, event count and message count
+    const HEADER_LENGTH: usize = 6;
+
+    /// parse a response, the function code has already been consumed
+    pub(crate) fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        let byte_count = cursor.read_u8()? as usize;
+        if byte_count != cursor.remaining() || byte_count < Self::HEADER_LENGTH {
+            return Err(AduParseError::InsufficientBytesForByteCount(
+                byte_count,
+                cursor.remaining(),
+            )
+            .into());
+        }
+
+        let count = byte_count - Self::HEADER_LENGTH;
+        if count > crate::constants::limits::MAX_COMM_EVENT_LOG_COUNT {
+            return Err(AduParseError::CommEventLogTooLarge(count).into());
+        }
+
+        let busy = parse_status(cursor)?;
+        let event_count = cursor.read_u16_be()?;
+        let message_count = cursor.read_u16_be()?;
+        let events = cursor.read_bytes(count)?.to_vec();
+        cursor.expect_empty()?;
+
+        Ok(Self {
+            busy,
+            event_count,
+            message_count,
+            events,
+        })
+    }
+}
+
+pub(crate) struct CommEventLogDisplay<'a> {
+    log: &'a CommEventLog,
+    level: AppDecodeLevel,
+}
+
+impl<'a> CommEventLogDisplay<'a> {
+    pub(crate) fn new(level: AppDecodeLevel, log: &'a CommEventLog) -> Self {
+        Self { log, level }
+    }
+}
+
+impl std::fmt::Display for CommEventLogDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "busy: {} events: {} messages: {} count: {}",
+            self.log.busy,
+            self.log.event_count,
+            self.log.message_count,
+            self.log.events.len()
+        )?;
+
+        if self.level.data_values() {
+            for x in &self.log.events {
+                write!(f, "\n{:#04X}", x)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct ServerIdDisplay<'a> {
+    data: &'a [u8],
+    level: AppDecodeLevel,
+}
+
+impl<'a> ServerIdDisplay<'a> {
+    pub(crate) fn new(level: AppDecodeLevel, data: &'a [u8]) -> Self {
+        Self { data, level }
+    }
+}
+
+impl std::fmt::Display for ServerIdDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "count: {}", self.data.len())?;
+
+        if self.level.data_values() {
+            crate::common::phys::format_bytes(f, self.data)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_comm_event_log() {
+        let mut cursor = ReadCursor::new(&[0x08, 0x00, 0x00, 0x01, 0x08, 0x01, 0x21, 0x20, 0x00]);
+        assert_eq!(
+            CommEventLog::parse(&mut cursor),
+            Ok(CommEventLog {
+                busy: false,
+                event_count: 0x0108,
+                message_count: 0x0121,
+                events: vec![0x20, 0x00],
+            })
+        );
+    }
+
+    #[test]
+    fn fails_when_comm_event_log_byte_count_too_small() {
+        let mut cursor = ReadCursor::new(&[0x04, 0x00, 0x00, 0x01, 0x08]);
+        assert_eq!(
+            CommEventLog::parse(&mut cursor),
+            Err(AduParseError::InsufficientBytesForByteCount(4, 4).into())
+        );
+    }
+
+    #[test]
+    fn fails_on_unknown_comm_status() {
+        let mut cursor = ReadCursor::new(&[0x06, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(
+            CommEventLog::parse(&mut cursor),
+            Err(AduParseError::UnknownCommStatus(0x0001).into())
+        );
+    }
+}
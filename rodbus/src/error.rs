@@ -122,6 +122,12 @@ impl<T> From<tokio::sync::mpsc::error::SendError<T>> for Shutdown {
     }
 }
 
+impl From<tokio::sync::oneshot::error::RecvError> for Shutdown {
+    fn from(_: tokio::sync::oneshot::error::RecvError) -> Self {
+        Shutdown
+    }
+}
+
 impl From<tokio::sync::oneshot::error::RecvError> for RequestError {
     fn from(_: tokio::sync::oneshot::error::RecvError) -> Self {
         RequestError::Shutdown
@@ -217,6 +223,12 @@ pub enum FrameParseError {
     UnknownFunctionCode(u8),
     /// RTU CRC validation failed
     CrcValidationFailure(u16, u16), // received CRC, expected CRC
+    /// ASCII frame contains a character that is not a hexadecimal digit
+    InvalidAsciiCharacter(u8),
+    /// ASCII frame contains an odd number of hexadecimal digits or too few to contain a PDU
+    InvalidAsciiLength(usize),
+    /// ASCII LRC validation failed
+    LrcValidationFailure(u8, u8), // received LRC, expected LRC
 }
 
 impl std::error::Error for FrameParseError {}
@@ -245,6 +257,23 @@ impl std::fmt::Display for FrameParseError {
                     received, expected
                 )
             }
+            FrameParseError::InvalidAsciiCharacter(x) => {
+                write!(f, "Received ASCII frame with invalid character {:#04X}", x)
+            }
+            FrameParseError::InvalidAsciiLength(x) => {
+                write!(
+                    f,
+                    "Received ASCII frame with an invalid number of characters ({})",
+                    x
+                )
+            }
+            FrameParseError::LrcValidationFailure(received, expected) => {
+                write!(
+                    f,
+                    "Received incorrect LRC value {:#04X}, expected {:#04X}",
+                    received, expected
+                )
+            }
         }
     }
 }
@@ -264,6 +293,24 @@ pub enum AduParseError {
     UnknownResponseFunction(u8, u8, u8), // actual, expected, expected error
     /// Bad value for the coil state
     UnknownCoilState(u16),
+    /// FIFO count exceeds the maximum allowed by the specification
+    FifoCountTooLarge(u16),
+    /// Unknown MEI type in an encapsulated interface transport PDU
+    UnknownMeiType(u8),
+    /// Unknown read device id code
+    UnknownReadDeviceIdCode(u8),
+    /// Value of the "more follows" field is neither 0x00 nor 0xFF
+    UnknownMoreFollows(u8),
+    /// Next object id does not come after the objects already received
+    InvalidNextObjectId(u8),
+    /// Status word of a comm event response is neither 0x0000 nor 0xFFFF
+    UnknownCommStatus(u16),
+    /// Comm event log contains more events than allowed by the specification
+    CommEventLogTooLarge(usize),
+    /// Reference type of a file record sub-request or sub-response is not 0x06
+    UnknownReferenceType(u8),
+    /// Registers could not be decoded into the requested type
+    InvalidValue(CodecError),
 }
 
 impl std::error::Error for AduParseError {}
@@ -293,6 +340,43 @@ impl std::fmt::Display for AduParseError {
                 "received coil state with unspecified value: 0x{:04X}",
                 value
             ),
+            AduParseError::FifoCountTooLarge(count) => write!(
+                f,
+                "FIFO count ({}) exceeds the maximum of {}",
+                count,
+                crate::constants::limits::MAX_FIFO_COUNT
+            ),
+            AduParseError::UnknownMeiType(value) => {
+                write!(f, "received unknown MEI type: {:#04X}", value)
+            }
+            AduParseError::UnknownReadDeviceIdCode(value) => {
+                write!(f, "received unknown read device id code: {:#04X}", value)
+            }
+            AduParseError::UnknownMoreFollows(value) => {
+                write!(f, "received unknown more follows value: {:#04X}", value)
+            }
+            AduParseError::InvalidNextObjectId(value) => write!(
+                f,
+                "next object id ({:#04X}) does not follow the received objects",
+                value
+            ),
+            AduParseError::UnknownCommStatus(value) => {
+                write!(f, "received unknown comm status: {:#06X}", value)
+            }
+            AduParseError::CommEventLogTooLarge(count) => write!(
+                f,
+                "comm event log contains {} events, maximum is {}",
+                count,
+                crate::constants::limits::MAX_COMM_EVENT_LOG_COUNT
+            ),
+            AduParseError::UnknownReferenceType(value) => {
+                write!(
+                    f,
+                    "received unknown file record reference type: {:#04X}",
+                    value
+                )
+            }
+            AduParseError::InvalidValue(err) => write!(f, "received invalid value: {}", err),
         }
     }
 }
@@ -306,6 +390,12 @@ pub enum InvalidRequest {
     CountTooBigForU16(usize),
     /// Count too big for specific request
     CountTooBigForType(u16, u16),
+    /// Function code is implemented by the library or is not a valid function code
+    InvalidCustomFunctionCode(u8),
+    /// File record number is larger than the maximum of 0x270F
+    InvalidFileRecordNumber(u16),
+    /// Value could not be encoded into registers
+    InvalidValue(CodecError),
 }
 
 impl std::error::Error for InvalidRequest {}
@@ -325,6 +415,80 @@ impl std::fmt::Display for InvalidRequest {
                 "the request count of {} exceeds maximum allowed count of {} for this type",
                 count, max
             ),
+            InvalidRequest::InvalidCustomFunctionCode(code) => write!(
+                f,
+                "function code {:#04X} cannot be used as a custom function",
+                code
+            ),
+            InvalidRequest::InvalidFileRecordNumber(number) => write!(
+                f,
+                "file record number {:#06X} exceeds the maximum of {:#06X}",
+                number,
+                crate::constants::limits::MAX_FILE_RECORD_NUMBER
+            ),
+            InvalidRequest::InvalidValue(err) => err.fmt(f),
+        }
+    }
+}
+
+/// Error returned when one of the requests used to process a split range fails
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkError {
+    /// Position of the failed request, starting at zero
+    pub index: usize,
+    /// Range of the failed request
+    pub range: crate::types::AddressRange,
+    /// Error returned by the failed request
+    pub error: RequestError,
+}
+
+impl std::error::Error for ChunkError {}
+
+impl std::fmt::Display for ChunkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "request {} ({}) failed: {}",
+            self.index, self.range, self.error
+        )
+    }
+}
+
+impl From<ChunkError> for RequestError {
+    fn from(err: ChunkError) -> Self {
+        err.error
+    }
+}
+
+/// Errors that occur when converting between registers and typed values
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Ord, Eq)]
+pub enum CodecError {
+    /// Number of registers (first) is not a multiple of the registers used by each value (second)
+    IncompleteValue(usize, u16),
+    /// String contains a character that is not ASCII
+    NonAscii(u8),
+    /// String of the specified length (first) does not fit in the number of registers (second)
+    StringTooLong(usize, u16),
+}
+
+impl std::error::Error for CodecError {}
+
+impl std::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            CodecError::IncompleteValue(count, per_value) => write!(
+                f,
+                "{} registers cannot be split into values of {} registers",
+                count, per_value
+            ),
+            CodecError::NonAscii(value) => {
+                write!(f, "string contains a non-ASCII character: {:#04X}", value)
+            }
+            CodecError::StringTooLong(length, count) => write!(
+                f,
+                "string of length {} does not fit in {} registers",
+                length, count
+            ),
         }
     }
 }
@@ -0,0 +1,345 @@
+use crate::decode::AppDecodeLevel;
+use crate::error::{AduParseError, InvalidRequest, RequestError};
+use crate::server::WriteRegisters;
+use crate::types::{AddressRange, Indexed, RegisterIterator};
+
+use scursor::ReadCursor;
+
+/// reference type of every file record sub-request
+pub(crate) const REFERENCE_TYPE: u8 = 0x06;
+/// reference type, file number, record number and record length
+pub(crate) const SUB_REQUEST_HEADER_LENGTH: usize = 7;
+/// length byte and reference type of each sub-response to a read
+pub(crate) const SUB_RESPONSE_HEADER_LENGTH: usize = 2;
+
+/// Group of consecutive records to read from a file
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FileRecord {
+    /// File number
+    pub file_number: u16,
+    /// First record to read
+    pub record_number: u16,
+    /// Number of records (registers) to read
+    pub record_length: u16,
+}
+
+impl FileRecord {
+    /// Create a new reference to a group of records
+    pub fn new(file_number: u16, record_number: u16, record_length: u16) -> Self {
+        Self {
+            file_number,
+            record_number,
+            record_length,
+        }
+    }
+
+    /// records of the file that are referenced
+    pub(crate) fn range(&self) -> Result<AddressRange, InvalidRequest> {
+        if self.record_number > crate::constants::limits::MAX_FILE_RECORD_NUMBER {
+            return Err(InvalidRequest::InvalidFileRecordNumber(self.record_number));
+        }
+        Ok(AddressRange::try_from(
+            self.record_number,
+            self.record_length,
+        )?)
+    }
+
+    /// length of the sub-response that answers a read of this group
+    pub(crate) fn response_length(&self) -> usize {
+        SUB_RESPONSE_HEADER_LENGTH + 2 * self.record_length as usize
+    }
+
+    fn parse(cursor: &mut ReadCursor) -> Result<Self, RequestError> {
+        parse_reference_type(cursor)?;
+        Ok(Self::new(
+            cursor.read_u16_be()?,
+            cursor.read_u16_be()?,
+            cursor.read_u16_be()?,
+        ))
+    }
+}
+
+impl std::fmt::Display for FileRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "file: {:#06X} record: {:#06X} count: {}",
+            self.file_number, self.record_number, self.record_length
+        )
+    }
+}
+
+/// Consecutive records read from or written to a file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileRecordData {
+    /// File number
+    pub file_number: u16,
+    /// Number of the first record
+    pub record_number: u16,
+    /// Value of each record starting at `record_number`
+    pub values: Vec<u16>,
+}
+
+impl FileRecordData {
+    /// Create a new group of records
+    pub fn new(file_number: u16, record_number: u16, values: Vec<u16>) -> Self {
+        Self {
+            file_number,
+            record_number,
+            values,
+        }
+    }
+
+    /// reference to the records without their values
+    pub(crate) fn record(&self) -> Result<FileRecord, InvalidRequest> {
+        let count = u16::try_from(self.values.len())
+            .map_err(|_| InvalidRequest::CountTooBigForU16(self.values.len()))?;
+        Ok(FileRecord::new(self.file_number, self.record_number, count))
+    }
+
+    /// length of the sub-request that writes this group
+    pub(crate) fn request_length(&self) -> usize {
+        SUB_REQUEST_HEADER_LENGTH + 2 * self.values.len()
+    }
+}
+
+fn parse_reference_type(cursor: &mut ReadCursor) -> Result<(), RequestError> {
+    match cursor.read_u8()? {
+        REFERENCE_TYPE => Ok(()),
+        x => Err(AduParseError::UnknownReferenceType(x).into()),
+    }
+}
+
+fn parse_byte_count<'a>(cursor: &mut ReadCursor<'a>) -> Result<&'a [u8], RequestError> {
+    let byte_count = cursor.read_u8()? as usize;
+    if byte_count != cursor.remaining() {
+        return Err(
+            AduParseError::InsufficientBytesForByteCount(byte_count, cursor.remaining()).into(),
+        );
+    }
+    Ok(cursor.read_bytes(byte_count)?)
+}
+
+/// Sub-requests of a read file record request, validated when parsed
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ReadFileRecords<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ReadFileRecords<'a> {
+    pub(crate) fn parse(cursor: &mut ReadCursor<'a>) -> Result<Self, RequestError> {
+        let bytes = parse_byte_count(cursor)?;
+        if bytes.is_empty() {
+            return Err(AduParseError::InsufficientBytes.into());
+        }
+
+        let mut cursor = ReadCursor::new(bytes);
+        let mut response_length = 0;
+        while !cursor.is_empty() {
+            let record = FileRecord::parse(&mut cursor)?;
+            record.range()?;
+            response_length += record.response_length();
+        }
+
+        let max = crate::constants::limits::MAX_READ_FILE_RECORD_LENGTH;
+        if response_length > max {
+            return Err(
+                InvalidRequest::CountTooBigForType(response_length as u16, max as u16).into(),
+            );
+        }
+
+        Ok(Self { bytes })
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = FileRecord> + 'a {
+        let mut cursor = ReadCursor::new(self.bytes);
+        // the sub-requests were validated when parsed
+        std::iter::from_fn(move || FileRecord::parse(&mut cursor).ok())
+    }
+}
+
+/// Sub-requests of a write file record request or its echoed response, validated when parsed
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct WriteFileRecords<'a> {
+    pub(crate) bytes: &'a [u8],
+}
+
+impl<'a> WriteFileRecords<'a> {
+    pub(crate) fn parse(cursor: &mut ReadCursor<'a>) -> Result<Self, RequestError> {
+        let bytes = parse_byte_count(cursor)?;
+        if bytes.is_empty() {
+            return Err(AduParseError::InsufficientBytes.into());
+        }
+
+        let mut cursor = ReadCursor::new(bytes);
+        while !cursor.is_empty() {
+            Self::parse_one(&mut cursor)?;
+        }
+
+        Ok(Self { bytes })
+    }
+
+    fn parse_one(cursor: &mut ReadCursor<'a>) -> Result<(u16, WriteRegisters<'a>), RequestError> {
+        let record = FileRecord::parse(cursor)?;
+        let range = record.range()?;
+        let iterator = RegisterIterator::parse(range, cursor)?;
+        Ok((record.file_number, WriteRegisters::new(range, iterator)))
+    }
+
+    /// file number and records of each sub-request
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u16, WriteRegisters<'a>)> {
+        let mut cursor = ReadCursor::new(self.bytes);
+        // the sub-requests were validated when parsed
+        std::iter::from_fn(move || Self::parse_one(&mut cursor).ok())
+    }
+
+    /// true if the sub-requests contain exactly the specified records
+    pub(crate) fn matches(&self, records: &[FileRecordData]) -> bool {
+        let mut count = 0;
+        for (file_number, values) in self.iter() {
+            let expected = match records.get(count) {
+                Some(x) => x,
+                None => return false,
+            };
+            if file_number != expected.file_number
+                || values.range.start != expected.record_number
+                || !values
+                    .iterator
+                    .map(|x| x.value)
+                    .eq(expected.values.iter().copied())
+            {
+                return false;
+            }
+            count += 1;
+        }
+        count == records.len()
+    }
+}
+
+pub(crate) struct FileRecordDataDisplay<'a> {
+    records: &'a [FileRecordData],
+    level: AppDecodeLevel,
+}
+
+impl<'a> FileRecordDataDisplay<'a> {
+    pub(crate) fn new(level: AppDecodeLevel, records: &'a [FileRecordData]) -> Self {
+        Self { records, level }
+    }
+}
+
+impl std::fmt::Display for FileRecordDataDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "count: {}", self.records.len())?;
+
+        for record in self.records {
+            write!(
+                f,
+                "\nfile: {:#06X} record: {:#06X} count: {}",
+                record.file_number,
+                record.record_number,
+                record.values.len()
+            )?;
+            if self.level.data_values() {
+                for (index, value) in (record.record_number..).zip(record.values.iter()) {
+                    write!(f, "\n{}", Indexed::new(index, *value))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct WriteFileRecordsDisplay<'a> {
+    records: WriteFileRecords<'a>,
+    level: AppDecodeLevel,
+}
+
+impl<'a> WriteFileRecordsDisplay<'a> {
+    pub(crate) fn new(level: AppDecodeLevel, records: WriteFileRecords<'a>) -> Self {
+        Self { records, level }
+    }
+}
+
+impl std::fmt::Display for WriteFileRecordsDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "count: {}", self.records.iter().count())?;
+
+        for (file_number, values) in self.records.iter() {
+            write!(f, "\nfile: {:#06X} {}", file_number, values.range)?;
+            if self.level.data_values() {
+                for x in values.iterator {
+                    write!(f, "\n{}", x)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_read_sub_requests() {
+        let mut cursor = ReadCursor::new(&[
+            0x0E, 0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02, 0x06, 0x00, 0x03, 0x00, 0x09, 0x00,
+            0x02,
+        ]);
+        let records = ReadFileRecords::parse(&mut cursor).unwrap();
+        assert_eq!(
+            records.iter().collect::<Vec<_>>(),
+            vec![FileRecord::new(4, 1, 2), FileRecord::new(3, 9, 2)]
+        );
+    }
+
+    #[test]
+    fn fails_on_unknown_reference_type() {
+        let mut cursor = ReadCursor::new(&[0x07, 0x05, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02]);
+        assert_eq!(
+            ReadFileRecords::parse(&mut cursor).err(),
+            Some(AduParseError::UnknownReferenceType(0x05).into())
+        );
+    }
+
+    #[test]
+    fn fails_when_record_number_too_large() {
+        let mut cursor = ReadCursor::new(&[0x07, 0x06, 0x00, 0x04, 0x27, 0x10, 0x00, 0x02]);
+        assert_eq!(
+            ReadFileRecords::parse(&mut cursor).err(),
+            Some(InvalidRequest::InvalidFileRecordNumber(0x2710).into())
+        );
+    }
+
+    #[test]
+    fn fails_when_read_response_would_not_fit() {
+        let mut cursor = ReadCursor::new(&[0x07, 0x06, 0x00, 0x04, 0x00, 0x00, 0x00, 0x7B]);
+        assert_eq!(
+            ReadFileRecords::parse(&mut cursor).err(),
+            Some(InvalidRequest::CountTooBigForType(0xF8, 0xF5).into())
+        );
+    }
+
+    #[test]
+    fn parses_write_sub_requests() {
+        let mut cursor = ReadCursor::new(&[
+            0x0B, 0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x02, 0x06, 0xAF, 0x04, 0xBE,
+        ]);
+        let records = WriteFileRecords::parse(&mut cursor).unwrap();
+        assert!(records.matches(&[FileRecordData::new(4, 7, vec![0x06AF, 0x04BE])]));
+        assert!(!records.matches(&[FileRecordData::new(4, 7, vec![0x06AF])]));
+        assert!(!records.matches(&[]));
+    }
+
+    #[test]
+    fn fails_when_write_values_are_missing() {
+        let mut cursor =
+            ReadCursor::new(&[0x09, 0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x02, 0x06, 0xAF]);
+        assert_eq!(
+            WriteFileRecords::parse(&mut cursor).err(),
+            Some(AduParseError::InsufficientBytes.into())
+        );
+    }
+}
@@ -169,9 +169,13 @@ pub mod constants;
 pub mod server;
 
 // modules that are re-exported
+pub(crate) mod codec;
 pub(crate) mod decode;
+pub(crate) mod device_id;
+pub(crate) mod diagnostics;
 pub(crate) mod error;
 pub(crate) mod exception;
+pub(crate) mod file_record;
 pub(crate) mod maybe_async;
 pub(crate) mod retry;
 #[cfg(feature = "serial")]
@@ -179,9 +183,13 @@ mod serial;
 pub(crate) mod types;
 
 // re-exports
+pub use crate::codec::*;
 pub use crate::decode::*;
+pub use crate::device_id::*;
+pub use crate::diagnostics::*;
 pub use crate::error::*;
 pub use crate::exception::*;
+pub use crate::file_record::*;
 pub use crate::maybe_async::*;
 pub use crate::retry::*;
 #[cfg(feature = "serial")]
@@ -191,3 +199,4 @@ pub use crate::types::*;
 // internal modules
 mod common;
 mod tcp;
+mod udp;
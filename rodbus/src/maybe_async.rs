@@ -33,6 +33,28 @@ impl<T> MaybeAsync<T> {
         }
     }
 
+    /// Retrieve the value if it is available without awaiting
+    pub(crate) fn try_ready(self) -> Result<T, Self> {
+        match self.inner {
+            Value::Ready(x) => Ok(x),
+            Value::Async(x) => Err(MaybeAsync {
+                inner: Value::Async(x),
+            }),
+        }
+    }
+
+    /// Transform the value once it is available. Doesn't allocate if the value is already available.
+    pub(crate) fn map<U, F>(self, f: F) -> MaybeAsync<U>
+    where
+        T: 'static,
+        F: FnOnce(T) -> U + Send + 'static,
+    {
+        match self.inner {
+            Value::Ready(x) => MaybeAsync::ready(f(x)),
+            Value::Async(x) => MaybeAsync::asynchronous(async move { f(x.await) }),
+        }
+    }
+
     /// Construct a new `MaybeAsync` from a future which yields the value eventually
     pub fn asynchronous<F>(result: F) -> Self
     where
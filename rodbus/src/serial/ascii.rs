@@ -0,0 +1,387 @@
+use std::ops::Range;
+
+use crate::common::buffer::ReadBuffer;
+use crate::common::frame::{
+    Frame, FrameDestination, FrameHeader, FrameInfo, FrameType, FunctionField,
+};
+use crate::common::traits::Serialize;
+use crate::decode::FrameDecodeLevel;
+use crate::error::{FrameParseError, InternalError, RequestError};
+use crate::types::UnitId;
+
+use scursor::WriteCursor;
+
+pub(crate) mod constants {
+    pub(crate) const START: u8 = b':';
+    pub(crate) const CR: u8 = b'\r';
+    pub(crate) const LF: u8 = b'\n';
+    pub(crate) const ADDRESS_LENGTH: usize = 1;
+    pub(crate) const LRC_LENGTH: usize = 1;
+    /// address, PDU and LRC before they are encoded as hexadecimal characters
+    pub(crate) const MAX_BINARY_LENGTH: usize =
+        ADDRESS_LENGTH + crate::common::frame::constants::MAX_ADU_LENGTH + LRC_LENGTH;
+    /// start character, two characters per binary byte, CR and LF
+    pub(crate) const MAX_FRAME_LENGTH: usize = 1 + 2 * MAX_BINARY_LENGTH + 2;
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// longitudinal redundancy check, the two's complement of the sum of the bytes
+fn lrc(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0u8, |sum, x| sum.wrapping_add(*x))
+        .wrapping_neg()
+}
+
+fn hex_value(character: u8) -> Result<u8, FrameParseError> {
+    match character {
+        b'0'..=b'9' => Ok(character - b'0'),
+        b'A'..=b'F' => Ok(character - b'A' + 10),
+        // the specification requires upper case, but some devices send lower case
+        b'a'..=b'f' => Ok(character - b'a' + 10),
+        _ => Err(FrameParseError::InvalidAsciiCharacter(character)),
+    }
+}
+
+pub(crate) struct AsciiParser;
+
+impl AsciiParser {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+
+    /// Number of bytes up to and including the LF that ends the frame at the start of the buffer.
+    ///
+    /// Bytes that precede a start character are discarded, including those of an incomplete
+    /// frame that is interrupted by a new start character.
+    fn frame_length(cursor: &mut ReadBuffer) -> Result<Option<usize>, RequestError> {
+        loop {
+            while !cursor.is_empty() && cursor.peek_at(0)? != constants::START {
+                cursor.read_u8()?;
+            }
+
+            let mut restart = None;
+            for index in 1..cursor.len() {
+                match cursor.peek_at(index)? {
+                    constants::LF => return Ok(Some(index + 1)),
+                    constants::START => {
+                        restart = Some(index);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            match restart {
+                Some(index) => {
+                    tracing::warn!("discarding {} bytes of an incomplete ASCII frame", index);
+                    cursor.read(index)?;
+                }
+                None => {
+                    if cursor.len() >= constants::MAX_FRAME_LENGTH {
+                        let length = cursor.len();
+                        // discard the characters so that parsing resumes with the next frame
+                        cursor.read(length)?;
+                        return Err(RequestError::BadFrame(FrameParseError::FrameLengthTooBig(
+                            length,
+                            constants::MAX_FRAME_LENGTH,
+                        )));
+                    }
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn parse(
+        &mut self,
+        cursor: &mut ReadBuffer,
+        decode_level: FrameDecodeLevel,
+    ) -> Result<Option<Frame>, RequestError> {
+        let length = match Self::frame_length(cursor)? {
+            Some(x) => x,
+            None => return Ok(None),
+        };
+
+        let characters = cursor.read(length)?;
+
+        // everything between the start character and CR LF
+        let hex = match characters[1..length - 1].split_last() {
+            Some((&constants::CR, hex)) => hex,
+            Some((x, _)) => {
+                return Err(RequestError::BadFrame(
+                    FrameParseError::InvalidAsciiCharacter(*x),
+                ))
+            }
+            None => {
+                return Err(RequestError::BadFrame(FrameParseError::InvalidAsciiLength(
+                    0,
+                )))
+            }
+        };
+
+        // address, function code and LRC at a minimum
+        if hex.len() % 2 != 0
+            || hex.len() < 2 * (constants::ADDRESS_LENGTH + 1 + constants::LRC_LENGTH)
+        {
+            return Err(RequestError::BadFrame(FrameParseError::InvalidAsciiLength(
+                hex.len(),
+            )));
+        }
+
+        let mut binary = [0; constants::MAX_BINARY_LENGTH];
+        let binary_length = hex.len() / 2;
+        for (byte, pair) in binary.iter_mut().zip(hex.chunks_exact(2)) {
+            *byte = (hex_value(pair[0])? << 4) | hex_value(pair[1])?;
+        }
+        let data = &binary[..binary_length - constants::LRC_LENGTH];
+        let received_lrc = binary[binary_length - constants::LRC_LENGTH];
+        let expected_lrc = lrc(data);
+        if received_lrc != expected_lrc {
+            return Err(RequestError::BadFrame(
+                FrameParseError::LrcValidationFailure(received_lrc, expected_lrc),
+            ));
+        }
+
+        let unit_id = UnitId::new(data[0]);
+        let destination = if unit_id == UnitId::broadcast() {
+            FrameDestination::Broadcast
+        } else {
+            FrameDestination::UnitId(unit_id)
+        };
+
+        if decode_level.enabled() {
+            tracing::info!(
+                "ASCII RX - {}",
+                AsciiDisplay::new(decode_level, destination, characters, received_lrc)
+            );
+        }
+
+        let mut frame = Frame::new(FrameHeader::new_rtu_header(destination));
+        frame.set(&data[constants::ADDRESS_LENGTH..]);
+        Ok(Some(frame))
+    }
+
+    pub(crate) fn reset(&mut self) {}
+}
+
+/// Write the binary form of the frame, which is encoded as characters by [`encode_in_place`]
+/// once the PDU has been logged
+pub(crate) fn format_ascii_pdu(
+    cursor: &mut WriteCursor,
+    header: FrameHeader,
+    function: FunctionField,
+    msg: &dyn Serialize,
+) -> Result<FrameInfo, RequestError> {
+    let start_frame = cursor.position();
+    cursor.write_u8(header.destination.value())?;
+    cursor.write_u8(function.get_value())?;
+    let start_pdu_body = cursor.position();
+    msg.serialize(cursor)?;
+    let end_pdu_body = cursor.position();
+    let lrc = lrc(cursor.get(start_frame..end_pdu_body).unwrap());
+    cursor.write_u8(lrc)?;
+
+    Ok(FrameInfo::new(
+        FrameType::Ascii(header.destination, lrc),
+        start_pdu_body..end_pdu_body,
+    ))
+}
+
+/// Encode the binary frame at the start of the buffer as an ASCII frame, returning its range
+pub(crate) fn encode_in_place(
+    buffer: &mut [u8],
+    binary_length: usize,
+) -> Result<Range<usize>, InternalError> {
+    let length = 1 + 2 * binary_length + 2;
+    if length > buffer.len() {
+        return Err(InternalError::InsufficientWriteSpace(length, buffer.len()));
+    }
+
+    // work backwards so that each byte is read before its position is overwritten
+    for index in (0..binary_length).rev() {
+        let byte = buffer[index];
+        buffer[1 + 2 * index] = HEX_DIGITS[(byte >> 4) as usize];
+        buffer[2 + 2 * index] = HEX_DIGITS[(byte & 0x0F) as usize];
+    }
+    buffer[0] = constants::START;
+    buffer[length - 2] = constants::CR;
+    buffer[length - 1] = constants::LF;
+
+    Ok(0..length)
+}
+
+pub(crate) struct AsciiDisplay<'a> {
+    level: FrameDecodeLevel,
+    destination: FrameDestination,
+    frame: &'a [u8],
+    lrc: u8,
+}
+
+impl<'a> AsciiDisplay<'a> {
+    pub(crate) fn new(
+        level: FrameDecodeLevel,
+        destination: FrameDestination,
+        frame: &'a [u8],
+        lrc: u8,
+    ) -> Self {
+        AsciiDisplay {
+            level,
+            destination,
+            frame,
+            lrc,
+        }
+    }
+}
+
+impl<'a> std::fmt::Display for AsciiDisplay<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "dest: {} lrc: {:#04X} (frame len = {})",
+            self.destination,
+            self.lrc,
+            self.frame.len(),
+        )?;
+        if self.level.payload_enabled() {
+            // the characters without the trailing CR LF
+            let end = self.frame.len().saturating_sub(2);
+            write!(f, "\n{}", String::from_utf8_lossy(&self.frame[..end]))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Poll;
+
+    use crate::common::frame::{FrameWriter, FramedReader};
+    use crate::common::function::FunctionCode;
+    use crate::common::phys::PhysLayer;
+    use crate::types::{AddressRange, Indexed};
+    use crate::DecodeLevel;
+
+    use super::*;
+
+    const READ_HOLDING_REGISTERS_REQUEST: &[u8] = b":2A0300100002C1\r\n";
+    const READ_HOLDING_REGISTERS_RESPONSE: &[u8] = b":2A030400FE0ED2F1\r\n";
+
+    fn parse(reader: &mut FramedReader, bytes: &[u8]) -> Result<Frame, RequestError> {
+        let (io, mut io_handle) = sfio_tokio_mock_io::mock();
+        let mut layer = PhysLayer::new_mock(io);
+        let mut task =
+            tokio_test::task::spawn(reader.next_frame(&mut layer, DecodeLevel::nothing()));
+        assert!(task.poll().is_pending());
+        io_handle.read(bytes);
+        match task.poll() {
+            Poll::Ready(x) => x,
+            Poll::Pending => panic!("frame was not parsed"),
+        }
+    }
+
+    #[test]
+    fn computes_lrc() {
+        assert_eq!(lrc(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x01]), 0xFB);
+        assert_eq!(lrc(&[]), 0x00);
+    }
+
+    #[test]
+    fn can_parse_request() {
+        let mut reader = FramedReader::ascii();
+        let frame = parse(&mut reader, READ_HOLDING_REGISTERS_REQUEST).unwrap();
+        assert_eq!(
+            frame.header.destination,
+            FrameDestination::new_unit_id(0x2A)
+        );
+        assert_eq!(frame.payload(), &[0x03, 0x00, 0x10, 0x00, 0x02]);
+    }
+
+    #[test]
+    fn can_parse_lower_case_response() {
+        let mut reader = FramedReader::ascii();
+        let frame = READ_HOLDING_REGISTERS_RESPONSE.to_ascii_lowercase();
+        let frame = parse(&mut reader, &frame).unwrap();
+        assert_eq!(frame.payload(), &[0x03, 0x04, 0x00, 0xFE, 0x0E, 0xD2]);
+    }
+
+    #[test]
+    fn discards_bytes_before_start_of_frame() {
+        let mut reader = FramedReader::ascii();
+        let mut bytes = b"\x00\xFF\r\n:2A03".to_vec();
+        bytes.extend_from_slice(READ_HOLDING_REGISTERS_REQUEST);
+        let frame = parse(&mut reader, &bytes).unwrap();
+        assert_eq!(frame.payload(), &[0x03, 0x00, 0x10, 0x00, 0x02]);
+    }
+
+    #[test]
+    fn fails_on_wrong_lrc() {
+        let mut reader = FramedReader::ascii();
+        assert_eq!(
+            parse(&mut reader, b":2A0300100002C2\r\n").err(),
+            Some(RequestError::BadFrame(
+                FrameParseError::LrcValidationFailure(0xC2, 0xC1)
+            ))
+        );
+    }
+
+    #[test]
+    fn fails_on_invalid_characters() {
+        let mut reader = FramedReader::ascii();
+        assert_eq!(
+            parse(&mut reader, b":2A0G001000020000\r\n").err(),
+            Some(RequestError::BadFrame(
+                FrameParseError::InvalidAsciiCharacter(b'G')
+            ))
+        );
+        assert_eq!(
+            parse(&mut reader, b":2A030\r\n").err(),
+            Some(RequestError::BadFrame(FrameParseError::InvalidAsciiLength(
+                5
+            )))
+        );
+        assert_eq!(
+            parse(&mut reader, b":2A0300\n").err(),
+            Some(RequestError::BadFrame(
+                FrameParseError::InvalidAsciiCharacter(b'0')
+            ))
+        );
+    }
+
+    #[test]
+    fn formats_frames_that_can_be_parsed() {
+        let mut writer = FrameWriter::ascii();
+        let range = AddressRange::try_from(0x10, 2).unwrap();
+        let values = [Indexed::new(0x10, 0x00FE), Indexed::new(0x11, 0x0ED2)];
+        let bytes = writer
+            .format_reply(
+                FrameHeader::new_rtu_header(FrameDestination::new_unit_id(0x2A)),
+                FunctionCode::ReadHoldingRegisters,
+                &crate::server::response::RegisterWriter::new(
+                    crate::types::ReadRegistersRange { inner: range },
+                    |i| Ok(values[(i - 0x10) as usize].value),
+                ),
+                DecodeLevel::nothing(),
+            )
+            .unwrap()
+            .to_vec();
+        assert_eq!(bytes, READ_HOLDING_REGISTERS_RESPONSE);
+
+        let mut reader = FramedReader::ascii();
+        let frame = parse(&mut reader, &bytes).unwrap();
+        assert_eq!(frame.payload(), &[0x03, 0x04, 0x00, 0xFE, 0x0E, 0xD2]);
+    }
+
+    #[test]
+    fn encodes_in_place() {
+        let mut buffer = [0u8; 16];
+        buffer[..3].copy_from_slice(&[0x01, 0xAB, 0x54]);
+        let range = encode_in_place(&mut buffer, 3).unwrap();
+        assert_eq!(&buffer[range], b":01AB54\r\n");
+        assert_eq!(
+            encode_in_place(&mut buffer, 7),
+            Err(InternalError::InsufficientWriteSpace(17, 16))
+        );
+    }
+}
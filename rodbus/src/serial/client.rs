@@ -18,11 +18,14 @@ pub(crate) struct SerialChannelTask {
 }
 
 impl SerialChannelTask {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         path: &str,
         serial_settings: SerialSettings,
         rx: Receiver<Command>,
         retry: Box<dyn RetryStrategy>,
+        writer: FrameWriter,
+        reader: FramedReader,
         decode: DecodeLevel,
         listener: Box<dyn Listener<PortState>>,
     ) -> Self {
@@ -30,12 +33,7 @@ impl SerialChannelTask {
             path: path.to_string(),
             serial_settings,
             retry,
-            client_loop: ClientLoop::new(
-                rx,
-                FrameWriter::rtu(),
-                FramedReader::rtu_response(),
-                decode,
-            ),
+            client_loop: ClientLoop::new(rx, writer, reader, decode),
             listener,
         }
     }
@@ -6,6 +6,7 @@ use crate::common::function::FunctionCode;
 use crate::common::traits::Serialize;
 use crate::decode::FrameDecodeLevel;
 use crate::error::{FrameParseError, RequestError};
+use crate::serial::{CustomFunctionLengths, CustomLength};
 use crate::types::UnitId;
 
 use scursor::WriteCursor;
@@ -32,6 +33,7 @@ enum ParseState {
     Start,
     ReadFullBody(FrameDestination, usize), // unit_id, length of rest
     ReadToOffsetForLength(FrameDestination, usize), // unit_id, length to length
+    ReadDeviceIdObjects(FrameDestination), // unit_id
 }
 
 #[derive(Clone, Copy)]
@@ -40,27 +42,41 @@ enum LengthMode {
     Fixed(usize),
     /// You need to read X more bytes. The last byte contains the number of extra bytes to read after that
     Offset(usize),
+    /// Read device identification response, the length is the sum of the length of each object
+    DeviceIdObjects,
     /// Unknown function code, can't determine the size
     Unknown,
 }
 
+impl From<CustomLength> for LengthMode {
+    fn from(x: CustomLength) -> Self {
+        match x {
+            CustomLength::Fixed(length) => LengthMode::Fixed(length as usize),
+            CustomLength::ByteCount(offset) => LengthMode::Offset(offset as usize + 1),
+        }
+    }
+}
+
 pub(crate) struct RtuParser {
     state: ParseState,
     parser_type: ParserType,
+    custom: CustomFunctionLengths,
 }
 
 impl RtuParser {
-    pub(crate) fn new_request_parser() -> Self {
+    pub(crate) fn new_request_parser(custom: CustomFunctionLengths) -> Self {
         Self {
             state: ParseState::Start,
             parser_type: ParserType::Request,
+            custom,
         }
     }
 
-    pub(crate) fn new_response_parser() -> Self {
+    pub(crate) fn new_response_parser(custom: CustomFunctionLengths) -> Self {
         Self {
             state: ParseState::Start,
             parser_type: ParserType::Response,
+            custom,
         }
     }
 
@@ -72,7 +88,9 @@ impl RtuParser {
         }
 
         // Parse function code
-        let function_code = match FunctionCode::get(function_code) {
+        let function_code = match FunctionCode::get(function_code)
+            .or_else(|| FunctionCode::custom(function_code))
+        {
             Some(code) => code,
             None => return LengthMode::Unknown,
         };
@@ -85,8 +103,23 @@ impl RtuParser {
                 FunctionCode::ReadInputRegisters => LengthMode::Fixed(4),
                 FunctionCode::WriteSingleCoil => LengthMode::Fixed(4),
                 FunctionCode::WriteSingleRegister => LengthMode::Fixed(4),
+                FunctionCode::ReadExceptionStatus => LengthMode::Fixed(0),
+                FunctionCode::Diagnostics => LengthMode::Fixed(4),
+                FunctionCode::GetCommEventCounter => LengthMode::Fixed(0),
+                FunctionCode::GetCommEventLog => LengthMode::Fixed(0),
                 FunctionCode::WriteMultipleCoils => LengthMode::Offset(5),
                 FunctionCode::WriteMultipleRegisters => LengthMode::Offset(5),
+                FunctionCode::ReportServerId => LengthMode::Fixed(0),
+                FunctionCode::ReadFileRecord => LengthMode::Offset(1),
+                FunctionCode::WriteFileRecord => LengthMode::Offset(1),
+                FunctionCode::MaskWriteRegister => LengthMode::Fixed(6),
+                FunctionCode::ReadWriteMultipleRegisters => LengthMode::Offset(9),
+                FunctionCode::ReadFifoQueue => LengthMode::Fixed(2),
+                FunctionCode::ReadDeviceIdentification => LengthMode::Fixed(3),
+                FunctionCode::Custom(x) => self
+                    .custom
+                    .request(x)
+                    .map_or(LengthMode::Unknown, LengthMode::from),
             },
             ParserType::Response => match function_code {
                 FunctionCode::ReadCoils => LengthMode::Offset(1),
@@ -95,12 +128,60 @@ impl RtuParser {
                 FunctionCode::ReadInputRegisters => LengthMode::Offset(1),
                 FunctionCode::WriteSingleCoil => LengthMode::Fixed(4),
                 FunctionCode::WriteSingleRegister => LengthMode::Fixed(4),
+                FunctionCode::ReadExceptionStatus => LengthMode::Fixed(1),
+                FunctionCode::Diagnostics => LengthMode::Fixed(4),
+                FunctionCode::GetCommEventCounter => LengthMode::Fixed(4),
+                FunctionCode::GetCommEventLog => LengthMode::Offset(1),
                 FunctionCode::WriteMultipleCoils => LengthMode::Fixed(4),
                 FunctionCode::WriteMultipleRegisters => LengthMode::Fixed(4),
+                FunctionCode::ReportServerId => LengthMode::Offset(1),
+                FunctionCode::ReadFileRecord => LengthMode::Offset(1),
+                FunctionCode::WriteFileRecord => LengthMode::Offset(1),
+                FunctionCode::MaskWriteRegister => LengthMode::Fixed(6),
+                FunctionCode::ReadWriteMultipleRegisters => LengthMode::Offset(1),
+                // the byte count is a u16, but it never exceeds 64 so the low byte is sufficient
+                FunctionCode::ReadFifoQueue => LengthMode::Offset(2),
+                FunctionCode::ReadDeviceIdentification => LengthMode::DeviceIdObjects,
+                FunctionCode::Custom(x) => self
+                    .custom
+                    .response(x)
+                    .map_or(LengthMode::Unknown, LengthMode::from),
             },
         }
     }
 
+    /// Walk the objects of a read device identification response to determine its length
+    fn device_id_objects_length(cursor: &mut ReadBuffer) -> Result<Option<usize>, RequestError> {
+        // MEI type, read device id code, conformity level, more follows, next object id, number of objects
+        const HEADER_LENGTH: usize = 6;
+
+        if cursor.len() < constants::FUNCTION_CODE_LENGTH + HEADER_LENGTH {
+            return Ok(None);
+        }
+
+        let num_objects = cursor.peek_at(constants::FUNCTION_CODE_LENGTH + HEADER_LENGTH - 1)?;
+        let mut length = HEADER_LENGTH;
+        for _ in 0..num_objects {
+            if constants::FUNCTION_CODE_LENGTH + length
+                > crate::common::frame::constants::MAX_ADU_LENGTH
+            {
+                return Err(RequestError::BadFrame(FrameParseError::FrameLengthTooBig(
+                    constants::FUNCTION_CODE_LENGTH + length,
+                    crate::common::frame::constants::MAX_ADU_LENGTH,
+                )));
+            }
+
+            // object id and object length
+            if cursor.len() < constants::FUNCTION_CODE_LENGTH + length + 2 {
+                return Ok(None);
+            }
+            let object_length = cursor.peek_at(constants::FUNCTION_CODE_LENGTH + length + 1)?;
+            length += 2 + object_length as usize;
+        }
+
+        Ok(Some(length))
+    }
+
     pub(crate) fn parse(
         &mut self,
         cursor: &mut ReadBuffer,
@@ -131,6 +212,7 @@ impl RtuParser {
                     LengthMode::Offset(offset) => {
                         ParseState::ReadToOffsetForLength(destination, offset)
                     }
+                    LengthMode::DeviceIdObjects => ParseState::ReadDeviceIdObjects(destination),
                     LengthMode::Unknown => {
                         return Err(RequestError::BadFrame(
                             FrameParseError::UnknownFunctionCode(raw_function_code),
@@ -152,6 +234,15 @@ impl RtuParser {
 
                 self.parse(cursor, decode_level)
             }
+            ParseState::ReadDeviceIdObjects(destination) => {
+                let length = match Self::device_id_objects_length(cursor)? {
+                    Some(length) => length,
+                    None => return Ok(None),
+                };
+                self.state = ParseState::ReadFullBody(destination, length);
+
+                self.parse(cursor, decode_level)
+            }
             ParseState::ReadFullBody(destination, length) => {
                 if constants::FUNCTION_CODE_LENGTH + length
                     > crate::common::frame::constants::MAX_ADU_LENGTH
@@ -377,6 +468,109 @@ mod tests {
         0x83, 0x63, // crc
     ];
 
+    const READ_EXCEPTION_STATUS_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x07,    // function code
+        0x5F, 0x12, // crc
+    ];
+
+    const READ_EXCEPTION_STATUS_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x07,    // function code
+        0x6D,    // output data
+        0x93, 0xD5, // crc
+    ];
+
+    const DIAGNOSTICS_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x08,    // function code
+        0x00, 0x00, // sub-function
+        0xA5, 0x37, // data
+        0xDC, 0x96, // crc
+    ];
+
+    const DIAGNOSTICS_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x08,    // function code
+        0x00, 0x00, // sub-function
+        0xA5, 0x37, // data
+        0xDC, 0x96, // crc
+    ];
+
+    const GET_COMM_EVENT_COUNTER_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x0B,    // function code
+        0x5F, 0x17, // crc
+    ];
+
+    const GET_COMM_EVENT_COUNTER_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x0B,    // function code
+        0x00, 0x00, // status
+        0x01, 0x08, // event count
+        0xA2, 0x46, // crc
+    ];
+
+    const GET_COMM_EVENT_LOG_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x0C,    // function code
+        0x1E, 0xD5, // crc
+    ];
+
+    const GET_COMM_EVENT_LOG_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x0C,    // function code
+        0x08,    // byte count
+        0x00, 0x00, // status
+        0x01, 0x08, // event count
+        0x01, 0x21, // message count
+        0x20, 0x00, // events
+        0x83, 0x25, // crc
+    ];
+
+    const REPORT_SERVER_ID_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x11,    // function code
+        0xDE, 0xDC, // crc
+    ];
+
+    const REPORT_SERVER_ID_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x11,    // function code
+        0x03,    // byte count
+        0x2A, 0xFF, 0x01, // server id, run indicator, additional data
+        0x5A, 0x6E, // crc
+    ];
+
+    const READ_FILE_RECORD_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x14,    // function code
+        0x07,    // byte count
+        0x06, 0x00, 0x04, 0x00, 0x01, 0x00, 0x02, // reference type, file, record, length
+        0xA8, 0x6A, // crc
+    ];
+
+    const READ_FILE_RECORD_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x14,    // function code
+        0x06,    // byte count
+        0x05, 0x06, // sub-response length, reference type
+        0x0D, 0xFE, 0x00, 0x20, // records
+        0x61, 0xBF, // crc
+    ];
+
+    const WRITE_FILE_RECORD_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x15,    // function code
+        0x0B,    // byte count
+        0x06, 0x00, 0x04, 0x00, 0x07, 0x00, 0x02, // reference type, file, record, length
+        0x06, 0xAF, 0x04, 0xBE, // records
+        0xC6, 0xF4, // crc
+    ];
+
+    // the response is an echo of the request
+    const WRITE_FILE_RECORD_RESPONSE: &[u8] = WRITE_FILE_RECORD_REQUEST;
+
     const WRITE_MULTIPLE_COILS_REQUEST: &[u8] = &[
         UNIT_ID, // unit id
         0x0F,    // function code
@@ -413,6 +607,83 @@ mod tests {
         0x46, 0x16, // crc
     ];
 
+    const MASK_WRITE_REGISTER_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x16,    // function code
+        0x00, 0x04, // reference address
+        0x00, 0xF2, // and mask
+        0x00, 0x25, // or mask
+        0x24, 0x45, // crc
+    ];
+
+    const MASK_WRITE_REGISTER_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x16,    // function code
+        0x00, 0x04, // reference address
+        0x00, 0xF2, // and mask
+        0x00, 0x25, // or mask
+        0x24, 0x45, // crc
+    ];
+
+    const READ_FIFO_QUEUE_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x18,    // function code
+        0x04, 0xDE, // fifo pointer address
+        0x0A, 0xA3, // crc
+    ];
+
+    const READ_FIFO_QUEUE_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x18,    // function code
+        0x00, 0x06, // byte count
+        0x00, 0x02, // fifo count
+        0x01, 0xB8, 0x12, 0x84, // fifo values
+        0x69, 0x97, // crc
+    ];
+
+    const READ_DEVICE_IDENTIFICATION_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x2B,    // function code
+        0x0E,    // MEI type
+        0x01,    // read device id code
+        0x00,    // object id
+        0x54, 0x71, // crc
+    ];
+
+    const READ_DEVICE_IDENTIFICATION_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x2B,    // function code
+        0x0E,    // MEI type
+        0x01,    // read device id code
+        0x81,    // conformity level
+        0x00,    // more follows
+        0x00,    // next object id
+        0x02,    // number of objects
+        0x00, 0x02, 0x41, 0x42, // first object
+        0x01, 0x01, 0x43, // second object
+        0x3E, 0x15, // crc
+    ];
+
+    const READ_WRITE_MULTIPLE_REGISTERS_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x17,    // function code
+        0x00, 0x10, // read starting address
+        0x00, 0x02, // qty to read
+        0x00, 0x20, // write starting address
+        0x00, 0x01, // qty to write
+        0x02, // byte count
+        0xCA, 0xFE, // write values
+        0xA0, 0x85, // crc
+    ];
+
+    const READ_WRITE_MULTIPLE_REGISTERS_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x17,    // function code
+        0x04,    // byte count
+        0xCA, 0xFE, 0x12, 0x34, // read values
+        0x31, 0x7A, // crc
+    ];
+
     const ALL_REQUESTS: &[(FunctionCode, &[u8])] = &[
         (FunctionCode::ReadCoils, READ_COILS_REQUEST),
         (
@@ -432,6 +703,19 @@ mod tests {
             FunctionCode::WriteSingleRegister,
             WRITE_SINGLE_REGISTER_REQUEST,
         ),
+        (
+            FunctionCode::ReadExceptionStatus,
+            READ_EXCEPTION_STATUS_REQUEST,
+        ),
+        (FunctionCode::Diagnostics, DIAGNOSTICS_REQUEST),
+        (
+            FunctionCode::GetCommEventCounter,
+            GET_COMM_EVENT_COUNTER_REQUEST,
+        ),
+        (FunctionCode::GetCommEventLog, GET_COMM_EVENT_LOG_REQUEST),
+        (FunctionCode::ReportServerId, REPORT_SERVER_ID_REQUEST),
+        (FunctionCode::ReadFileRecord, READ_FILE_RECORD_REQUEST),
+        (FunctionCode::WriteFileRecord, WRITE_FILE_RECORD_REQUEST),
         (
             FunctionCode::WriteMultipleCoils,
             WRITE_MULTIPLE_COILS_REQUEST,
@@ -440,6 +724,16 @@ mod tests {
             FunctionCode::WriteMultipleRegisters,
             WRITE_MULTIPLE_REGISTERS_REQUEST,
         ),
+        (FunctionCode::MaskWriteRegister, MASK_WRITE_REGISTER_REQUEST),
+        (
+            FunctionCode::ReadWriteMultipleRegisters,
+            READ_WRITE_MULTIPLE_REGISTERS_REQUEST,
+        ),
+        (FunctionCode::ReadFifoQueue, READ_FIFO_QUEUE_REQUEST),
+        (
+            FunctionCode::ReadDeviceIdentification,
+            READ_DEVICE_IDENTIFICATION_REQUEST,
+        ),
     ];
 
     const ALL_RESPONSES: &[(FunctionCode, &[u8])] = &[
@@ -461,6 +755,19 @@ mod tests {
             FunctionCode::WriteSingleRegister,
             WRITE_SINGLE_REGISTER_RESPONSE,
         ),
+        (
+            FunctionCode::ReadExceptionStatus,
+            READ_EXCEPTION_STATUS_RESPONSE,
+        ),
+        (FunctionCode::Diagnostics, DIAGNOSTICS_RESPONSE),
+        (
+            FunctionCode::GetCommEventCounter,
+            GET_COMM_EVENT_COUNTER_RESPONSE,
+        ),
+        (FunctionCode::GetCommEventLog, GET_COMM_EVENT_LOG_RESPONSE),
+        (FunctionCode::ReportServerId, REPORT_SERVER_ID_RESPONSE),
+        (FunctionCode::ReadFileRecord, READ_FILE_RECORD_RESPONSE),
+        (FunctionCode::WriteFileRecord, WRITE_FILE_RECORD_RESPONSE),
         (
             FunctionCode::WriteMultipleCoils,
             WRITE_MULTIPLE_COILS_RESPONSE,
@@ -469,6 +776,19 @@ mod tests {
             FunctionCode::WriteMultipleRegisters,
             WRITE_MULTIPLE_REGISTERS_RESPONSE,
         ),
+        (
+            FunctionCode::MaskWriteRegister,
+            MASK_WRITE_REGISTER_RESPONSE,
+        ),
+        (
+            FunctionCode::ReadWriteMultipleRegisters,
+            READ_WRITE_MULTIPLE_REGISTERS_RESPONSE,
+        ),
+        (FunctionCode::ReadFifoQueue, READ_FIFO_QUEUE_RESPONSE),
+        (
+            FunctionCode::ReadDeviceIdentification,
+            READ_DEVICE_IDENTIFICATION_RESPONSE,
+        ),
     ];
 
     fn assert_can_parse_frame(mut reader: FramedReader, frame: &[u8]) {
@@ -497,7 +817,7 @@ mod tests {
     #[test]
     fn can_parse_request_frames() {
         for (_, request) in ALL_REQUESTS {
-            let reader = FramedReader::rtu_request();
+            let reader = FramedReader::rtu_request(CustomFunctionLengths::default());
             assert_can_parse_frame(reader, request);
         }
     }
@@ -505,7 +825,7 @@ mod tests {
     #[test]
     fn can_parse_response_frames() {
         for (_, response) in ALL_RESPONSES {
-            let reader = FramedReader::rtu_response();
+            let reader = FramedReader::rtu_response(CustomFunctionLengths::default());
             assert_can_parse_frame(reader, response);
         }
     }
@@ -528,7 +848,7 @@ mod tests {
         huge_response.push((crc & 0x00FF) as u8);
         huge_response.push(((crc & 0xFF00) >> 8) as u8);
 
-        let reader = FramedReader::rtu_response();
+        let reader = FramedReader::rtu_response(CustomFunctionLengths::default());
         assert_can_parse_frame(reader, &huge_response);
     }
 
@@ -550,7 +870,7 @@ mod tests {
         huge_response.push((crc & 0x00FF) as u8);
         huge_response.push(((crc & 0xFF00) >> 8) as u8);
 
-        let reader = FramedReader::rtu_response();
+        let reader = FramedReader::rtu_response(CustomFunctionLengths::default());
         assert_can_parse_frame(reader, &huge_response);
     }
 
@@ -587,7 +907,7 @@ mod tests {
     #[test]
     fn can_parse_request_frames_byte_per_byte() {
         for (_, request) in ALL_REQUESTS {
-            let reader = FramedReader::rtu_request();
+            let reader = FramedReader::rtu_request(CustomFunctionLengths::default());
             assert_can_parse_frame_byte_per_byte(reader, request);
         }
     }
@@ -595,7 +915,7 @@ mod tests {
     #[test]
     fn can_parse_response_frames_byte_per_byte() {
         for (_, response) in ALL_RESPONSES {
-            let reader = FramedReader::rtu_response();
+            let reader = FramedReader::rtu_response(CustomFunctionLengths::default());
             assert_can_parse_frame_byte_per_byte(reader, response);
         }
     }
@@ -658,7 +978,7 @@ mod tests {
     #[test]
     fn can_parse_two_request_frames() {
         for (_, request) in ALL_REQUESTS {
-            let reader = FramedReader::rtu_request();
+            let reader = FramedReader::rtu_request(CustomFunctionLengths::default());
             assert_can_parse_two_frames(reader, request);
         }
     }
@@ -666,7 +986,7 @@ mod tests {
     #[test]
     fn can_parse_two_response_frames() {
         for (_, response) in ALL_RESPONSES {
-            let reader = FramedReader::rtu_response();
+            let reader = FramedReader::rtu_response(CustomFunctionLengths::default());
             assert_can_parse_two_frames(reader, response);
         }
     }
@@ -681,7 +1001,7 @@ mod tests {
             0xFF, 0xFF, // wrong crc
         ];
 
-        let mut reader = FramedReader::rtu_request();
+        let mut reader = FramedReader::rtu_request(CustomFunctionLengths::default());
         let (io, mut io_handle) = sfio_tokio_mock_io::mock();
         let mut layer = PhysLayer::new_mock(io);
         let mut task =
@@ -700,12 +1020,75 @@ mod tests {
         }
     }
 
+    const CUSTOM_FUNCTION_REQUEST: &[u8] = &[
+        UNIT_ID, // unit id
+        0x41,    // function code
+        0x01, 0x02, // data
+        0xD8, 0x79, // crc
+    ];
+
+    const CUSTOM_FUNCTION_RESPONSE: &[u8] = &[
+        UNIT_ID, // unit id
+        0x41,    // function code
+        0x03,    // byte count
+        0xAA, 0xBB, 0xCC, // data
+        0x69, 0x1F, // crc
+    ];
+
+    fn custom_function_lengths() -> CustomFunctionLengths {
+        let mut custom = CustomFunctionLengths::new();
+        custom
+            .add(0x41, CustomLength::Fixed(2), CustomLength::ByteCount(0))
+            .unwrap();
+        custom
+    }
+
+    #[test]
+    fn can_parse_custom_function_frames() {
+        let reader = FramedReader::rtu_request(custom_function_lengths());
+        assert_can_parse_frame_byte_per_byte(reader, CUSTOM_FUNCTION_REQUEST);
+        let reader = FramedReader::rtu_response(custom_function_lengths());
+        assert_can_parse_frame_byte_per_byte(reader, CUSTOM_FUNCTION_RESPONSE);
+    }
+
+    #[test]
+    fn fails_on_unregistered_custom_function() {
+        let mut reader = FramedReader::rtu_request(CustomFunctionLengths::default());
+        let (io, mut io_handle) = sfio_tokio_mock_io::mock();
+        let mut layer = PhysLayer::new_mock(io);
+        let mut task =
+            tokio_test::task::spawn(reader.next_frame(&mut layer, DecodeLevel::nothing()));
+
+        io_handle.read(CUSTOM_FUNCTION_REQUEST);
+        if let Poll::Ready(received_frame) = task.poll() {
+            assert!(matches!(
+                received_frame,
+                Err(RequestError::BadFrame(
+                    FrameParseError::UnknownFunctionCode(0x41)
+                ))
+            ));
+        } else {
+            panic!("Task not ready");
+        }
+    }
+
+    #[test]
+    fn cannot_register_implemented_function_codes() {
+        let mut custom = CustomFunctionLengths::new();
+        assert_eq!(
+            custom.add(0x03, CustomLength::Fixed(4), CustomLength::ByteCount(0)),
+            Err(crate::error::InvalidRequest::InvalidCustomFunctionCode(
+                0x03
+            ))
+        );
+    }
+
     struct MockMessage<'a> {
         frame: &'a [u8],
     }
 
     impl<'a> Serialize for MockMessage<'a> {
-        fn serialize(self: &Self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
+        fn serialize(&self, cursor: &mut WriteCursor) -> Result<(), RequestError> {
             for byte in &self.frame[2..self.frame.len() - 2] {
                 cursor.write_u8(*byte)?;
             }
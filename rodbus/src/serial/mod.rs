@@ -1,6 +1,12 @@
+use std::collections::BTreeMap;
+
 use tokio_serial::SerialStream;
 pub use tokio_serial::{DataBits, FlowControl, Parity, StopBits};
 
+use crate::common::function::FunctionCode;
+use crate::error::InvalidRequest;
+
+pub(crate) mod ascii;
 pub(crate) mod client;
 pub(crate) mod frame;
 pub(crate) mod server;
@@ -46,6 +52,63 @@ impl Default for SerialSettings {
     }
 }
 
+/// Length of the data that follows a custom function code in an RTU frame
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CustomLength {
+    /// The data always contains the same number of bytes
+    Fixed(u8),
+    /// The data contains a byte count at the specified offset which is followed by that many bytes
+    ByteCount(u8),
+}
+
+#[derive(Copy, Clone, Debug)]
+struct CustomFunctionLength {
+    request: CustomLength,
+    response: CustomLength,
+}
+
+/// Lengths of the request and response frames of custom function codes
+///
+/// RTU frames do not contain a length field, so the parser must know how many bytes follow
+/// each function code. Frames that use a custom function code without a registered length
+/// are rejected.
+#[derive(Clone, Debug, Default)]
+pub struct CustomFunctionLengths {
+    functions: BTreeMap<u8, CustomFunctionLength>,
+}
+
+impl CustomFunctionLengths {
+    /// Create an empty set of lengths
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the lengths of a custom function code, replacing any previous registration
+    ///
+    /// Fails if the function code is implemented by the library or is not a valid function code
+    pub fn add(
+        &mut self,
+        function_code: u8,
+        request: CustomLength,
+        response: CustomLength,
+    ) -> Result<(), InvalidRequest> {
+        if FunctionCode::custom(function_code).is_none() {
+            return Err(InvalidRequest::InvalidCustomFunctionCode(function_code));
+        }
+        self.functions
+            .insert(function_code, CustomFunctionLength { request, response });
+        Ok(())
+    }
+
+    pub(crate) fn request(&self, function_code: u8) -> Option<CustomLength> {
+        self.functions.get(&function_code).map(|x| x.request)
+    }
+
+    pub(crate) fn response(&self, function_code: u8) -> Option<CustomLength> {
+        self.functions.get(&function_code).map(|x| x.response)
+    }
+}
+
 pub(crate) fn open(path: &str, settings: SerialSettings) -> tokio_serial::Result<SerialStream> {
     let builder = settings.apply(tokio_serial::new(path, settings.baud_rate));
     SerialStream::open(&builder)
@@ -1,21 +1,21 @@
 use crate::common::phys::PhysLayer;
-use crate::server::task::SessionTask;
-use crate::server::RequestHandler;
+use crate::server::task::{SessionHandlers, SessionTask};
+
 use crate::{RequestError, RetryStrategy, SerialSettings, Shutdown};
 
-pub(crate) struct RtuServerTask<T>
+pub(crate) struct RtuServerTask<H>
 where
-    T: RequestHandler,
+    H: SessionHandlers,
 {
     pub(crate) port: String,
     pub(crate) retry: Box<dyn RetryStrategy>,
     pub(crate) settings: SerialSettings,
-    pub(crate) session: SessionTask<T>,
+    pub(crate) session: SessionTask<H>,
 }
 
-impl<T> RtuServerTask<T>
+impl<H> RtuServerTask<H>
 where
-    T: RequestHandler,
+    H: SessionHandlers,
 {
     pub(crate) async fn run(&mut self) -> Shutdown {
         loop {
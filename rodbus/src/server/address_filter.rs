@@ -50,7 +50,7 @@ impl WildcardIPv4 {
             }
         }
 
-        match addr {
+        match unmap(addr) {
             std::net::IpAddr::V4(x) => {
                 let [b3, b2, b1, b0] = x.octets();
                 bm(b3, self.b3) && bm(b2, self.b2) && bm(b1, self.b1) && bm(b0, self.b0)
@@ -60,11 +60,173 @@ impl WildcardIPv4 {
     }
 }
 
+/// IPv4 or IPv6 network in CIDR notation, e.g. `10.0.0.0/8` or `fd00::/8`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct IpNetwork {
+    addr: std::net::IpAddr,
+    prefix: u8,
+}
+
+/// Error returned when a network is not in the correct format or its prefix is too long
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BadIpNetwork;
+
+impl std::error::Error for BadIpNetwork {}
+
+impl std::fmt::Display for BadIpNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.write_str("networks must be an IP address followed by a prefix length, e.g. 10.0.0.0/8")
+    }
+}
+
+impl IpNetwork {
+    /// Create a network from an address and a prefix length, the bits of the address
+    /// after the prefix are ignored
+    ///
+    /// IPv4-mapped networks like `::ffff:10.0.0.0/104` are stored as the equivalent
+    /// IPv4 network, e.g. `10.0.0.0/8`, since peers are always matched as IPv4 addresses
+    pub fn new(addr: std::net::IpAddr, prefix: u8) -> Result<Self, BadIpNetwork> {
+        match addr {
+            std::net::IpAddr::V4(x) if prefix <= 32 => Ok(Self {
+                addr: std::net::Ipv4Addr::from(u32::from(x) & Self::v4_mask(prefix)).into(),
+                prefix,
+            }),
+            std::net::IpAddr::V6(x) if prefix <= 128 => {
+                let x = std::net::Ipv6Addr::from(u128::from(x) & Self::v6_mask(prefix));
+                match x.to_ipv4_mapped() {
+                    Some(x) if prefix >= 96 => Self::new(x.into(), prefix - 96),
+                    _ => Ok(Self {
+                        addr: x.into(),
+                        prefix,
+                    }),
+                }
+            }
+            _ => Err(BadIpNetwork),
+        }
+    }
+
+    /// First address of the network
+    pub fn addr(&self) -> std::net::IpAddr {
+        self.addr
+    }
+
+    /// Length of the prefix in bits
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    fn v4_mask(prefix: u8) -> u32 {
+        u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0)
+    }
+
+    fn v6_mask(prefix: u8) -> u128 {
+        u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0)
+    }
+
+    pub(crate) fn matches(&self, addr: std::net::IpAddr) -> bool {
+        match (self.addr, unmap(addr)) {
+            (std::net::IpAddr::V4(net), std::net::IpAddr::V4(x)) => {
+                u32::from(x) & Self::v4_mask(self.prefix) == u32::from(net)
+            }
+            (std::net::IpAddr::V6(net), std::net::IpAddr::V6(x)) => {
+                u128::from(x) & Self::v6_mask(self.prefix) == u128::from(net)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = BadIpNetwork;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = s.split_once('/').ok_or(BadIpNetwork)?;
+        let addr = addr.parse().map_err(|_| BadIpNetwork)?;
+        let prefix = prefix.parse().map_err(|_| BadIpNetwork)?;
+        Self::new(addr, prefix)
+    }
+}
+
+impl std::fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// Action taken for the addresses that match an entry of [`AddressFilter::List`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FilterAction {
+    /// Accept the connection
+    Allow,
+    /// Reject the connection
+    Deny,
+}
+
+/// Entry of [`AddressFilter::List`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilterEntry {
+    /// Action taken if the address matches the filter
+    pub action: FilterAction,
+    /// Filter that the address is matched against
+    pub filter: AddressFilter,
+}
+
+impl FilterEntry {
+    /// Create an entry that accepts the addresses matched by a filter
+    pub fn allow(filter: AddressFilter) -> Self {
+        Self {
+            action: FilterAction::Allow,
+            filter,
+        }
+    }
+
+    /// Create an entry that rejects the addresses matched by a filter
+    pub fn deny(filter: AddressFilter) -> Self {
+        Self {
+            action: FilterAction::Deny,
+            filter,
+        }
+    }
+}
+
+/// Error returned when an address filter expression is not in the correct format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadAddressFilter {
+    /// Entry of the expression that could not be parsed
+    pub entry: String,
+}
+
+impl std::error::Error for BadAddressFilter {}
+
+impl std::fmt::Display for BadAddressFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "bad address filter entry: \"{}\"", self.entry)
+    }
+}
+
+/// IPv4 clients of a dual-stack server appear as IPv4-mapped IPv6 addresses, i.e.
+/// `::ffff:a.b.c.d`, which are converted back to IPv4 before being matched
+fn unmap(addr: std::net::IpAddr) -> std::net::IpAddr {
+    match addr {
+        std::net::IpAddr::V6(x) => match x.to_ipv4_mapped() {
+            Some(x) => std::net::IpAddr::V4(x),
+            None => std::net::IpAddr::V6(x),
+        },
+        std::net::IpAddr::V4(x) => std::net::IpAddr::V4(x),
+    }
+}
+
 /// Address filter used to control which master address(es) may connect to an outstation.
 ///
+/// Filters may be parsed from a comma separated list of entries evaluated in order, each entry
+/// being an optional `allow` or `deny` action followed by `*`, an IP address, a network in CIDR
+/// notation or an IPv4 address with wildcards, e.g. `deny 10.0.0.5, allow 10.0.0.0/8, fd00::/8`.
+/// Entries without an action allow the addresses they match. A list with a single entry that
+/// allows addresses is parsed as the corresponding variant, other lists as [`AddressFilter::List`].
+///
 /// Note: User code cannot exhaustively match against this enum as new variants may be added in the future.
 #[non_exhaustive]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AddressFilter {
     /// Allow any address
     Any,
@@ -74,22 +236,79 @@ pub enum AddressFilter {
     AnyOf(std::collections::HashSet<std::net::IpAddr>),
     /// Matches against an IPv4 address with wildcards
     WildcardIpv4(WildcardIPv4),
+    /// Allow the addresses of an IPv4 or IPv6 network
+    Network(IpNetwork),
+    /// Entries evaluated in order, the first entry that matches the address decides and
+    /// addresses that match no entry are rejected
+    List(Vec<FilterEntry>),
 }
 
 impl AddressFilter {
     pub(crate) fn matches(&self, addr: std::net::IpAddr) -> bool {
+        let addr = unmap(addr);
         match self {
             AddressFilter::Any => true,
-            AddressFilter::Exact(x) => *x == addr,
-            AddressFilter::AnyOf(set) => set.contains(&addr),
+            AddressFilter::Exact(x) => unmap(*x) == addr,
+            AddressFilter::AnyOf(set) => set.iter().any(|x| unmap(*x) == addr),
             AddressFilter::WildcardIpv4(wc) => wc.matches(addr),
+            AddressFilter::Network(net) => net.matches(addr),
+            AddressFilter::List(entries) => entries
+                .iter()
+                .find(|x| x.filter.matches(addr))
+                .map(|x| x.action == FilterAction::Allow)
+                .unwrap_or(false),
         }
     }
+
+    fn parse_entry(entry: &str) -> Result<FilterEntry, BadAddressFilter> {
+        let bad = || BadAddressFilter {
+            entry: entry.to_string(),
+        };
+
+        let (action, pattern) = match entry.split_once(char::is_whitespace) {
+            Some(("allow", x)) => (FilterAction::Allow, x.trim()),
+            Some(("deny", x)) => (FilterAction::Deny, x.trim()),
+            Some(_) => return Err(bad()),
+            None => (FilterAction::Allow, entry),
+        };
+
+        let filter = if pattern == "*" {
+            AddressFilter::Any
+        } else if pattern.contains('/') {
+            AddressFilter::Network(pattern.parse().map_err(|_| bad())?)
+        } else if pattern.contains('*') {
+            AddressFilter::WildcardIpv4(pattern.parse().map_err(|_| bad())?)
+        } else {
+            AddressFilter::Exact(pattern.parse().map_err(|_| bad())?)
+        };
+
+        Ok(FilterEntry { action, filter })
+    }
+}
+
+impl FromStr for AddressFilter {
+    type Err = BadAddressFilter;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = s
+            .split(',')
+            .map(|x| Self::parse_entry(x.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if entries.len() == 1 && entries[0].action == FilterAction::Allow {
+            return Ok(entries.remove(0).filter);
+        }
+
+        Ok(AddressFilter::List(entries))
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{BadIpv4Wildcard, WildcardIPv4};
+    use super::{
+        AddressFilter, BadAddressFilter, BadIpNetwork, BadIpv4Wildcard, FilterEntry, IpNetwork,
+        WildcardIPv4,
+    };
     use std::net::IpAddr;
 
     #[test]
@@ -148,4 +367,133 @@ mod test {
         assert!(wc.matches(ip1));
         assert!(!wc.matches(ip2));
     }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_networks() {
+        let net: IpNetwork = "10.1.2.3/8".parse().unwrap();
+        assert_eq!(net.addr(), ip("10.0.0.0"));
+        assert_eq!(net.prefix(), 8);
+        assert_eq!(net.to_string(), "10.0.0.0/8");
+
+        let net: IpNetwork = "fd00::1/8".parse().unwrap();
+        assert_eq!(net.addr(), ip("fd00::"));
+
+        for x in [
+            "10.0.0.0",
+            "10.0.0.0/33",
+            "fd00::/129",
+            "10.0.0/8",
+            "10.0.0.0/a",
+        ] {
+            assert_eq!(x.parse::<IpNetwork>(), Err(BadIpNetwork));
+        }
+    }
+
+    #[test]
+    fn network_matching_works() {
+        let v4: IpNetwork = "192.168.0.0/23".parse().unwrap();
+        assert!(v4.matches(ip("192.168.1.200")));
+        assert!(v4.matches(ip("::ffff:192.168.0.1")));
+        assert!(!v4.matches(ip("192.168.2.1")));
+        assert!(!v4.matches(ip("fd00::1")));
+
+        let v6: IpNetwork = "fd00:1::/32".parse().unwrap();
+        assert!(v6.matches(ip("fd00:1:ffff::1")));
+        assert!(!v6.matches(ip("fd00:2::1")));
+        assert!(!v6.matches(ip("10.0.0.1")));
+
+        let all: IpNetwork = "0.0.0.0/0".parse().unwrap();
+        assert!(all.matches(ip("255.255.255.255")));
+        assert!(!all.matches(ip("::1")));
+
+        let mapped: IpNetwork = "::ffff:10.0.0.0/104".parse().unwrap();
+        assert_eq!(mapped, "10.0.0.0/8".parse().unwrap());
+        assert!(mapped.matches(ip("10.1.2.3")));
+        assert!(mapped.matches(ip("::ffff:10.1.2.3")));
+        assert!(!mapped.matches(ip("11.0.0.1")));
+    }
+
+    #[test]
+    fn parses_single_entries_as_simple_filters() {
+        assert_eq!("*".parse(), Ok(AddressFilter::Any));
+        assert_eq!(
+            " allow 10.0.0.1 ".parse(),
+            Ok(AddressFilter::Exact(ip("10.0.0.1")))
+        );
+        assert_eq!(
+            "fd00::/8".parse(),
+            Ok(AddressFilter::Network("fd00::/8".parse().unwrap()))
+        );
+        assert_eq!(
+            "172.17.*.*".parse(),
+            Ok(AddressFilter::WildcardIpv4("172.17.*.*".parse().unwrap()))
+        );
+    }
+
+    #[test]
+    fn parses_lists() {
+        let filter: AddressFilter = "deny 10.0.0.5, allow 10.0.0.0/8, ::1".parse().unwrap();
+        assert_eq!(
+            filter,
+            AddressFilter::List(vec![
+                FilterEntry::deny(AddressFilter::Exact(ip("10.0.0.5"))),
+                FilterEntry::allow(AddressFilter::Network("10.0.0.0/8".parse().unwrap())),
+                FilterEntry::allow(AddressFilter::Exact(ip("::1"))),
+            ])
+        );
+
+        for x in [
+            "",
+            "10.0.0.1,",
+            "block 10.0.0.1",
+            "allow",
+            "deny 10.0.0.0/40",
+        ] {
+            assert!(matches!(
+                x.parse::<AddressFilter>(),
+                Err(BadAddressFilter { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn lists_are_evaluated_in_order() {
+        let filter: AddressFilter = "deny 10.0.0.5, allow 10.0.0.0/8, deny 10.1.0.0/16"
+            .parse()
+            .unwrap();
+        assert!(!filter.matches(ip("10.0.0.5")));
+        assert!(filter.matches(ip("10.0.0.6")));
+        assert!(filter.matches(ip("10.1.0.1")));
+        assert!(!filter.matches(ip("192.168.0.1")));
+
+        let filter: AddressFilter = "deny 192.168.0.*, allow *".parse().unwrap();
+        assert!(!filter.matches(ip("192.168.0.1")));
+        assert!(filter.matches(ip("192.168.1.1")));
+        assert!(filter.matches(ip("fd00::1")));
+    }
+
+    #[test]
+    fn lists_match_ipv4_mapped_addresses() {
+        let filter: AddressFilter = "deny 10.0.0.5, allow 10.0.0.0/8".parse().unwrap();
+        assert!(!filter.matches(ip("::ffff:10.0.0.5")));
+        assert!(filter.matches(ip("::ffff:10.0.0.6")));
+
+        let filter: AddressFilter = "deny 192.168.0.*, allow 192.168.0.0/16".parse().unwrap();
+        assert!(!filter.matches(ip("::ffff:192.168.0.1")));
+        assert!(filter.matches(ip("::ffff:192.168.1.1")));
+
+        let filter: AddressFilter = "deny 10.0.0.5, 10.0.0.6, allow *".parse().unwrap();
+        assert!(!filter.matches(ip("::ffff:10.0.0.5")));
+        assert!(filter.matches(ip("::ffff:10.0.0.7")));
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(ip("10.0.0.5"));
+        assert!(AddressFilter::AnyOf(set).matches(ip("::ffff:10.0.0.5")));
+        assert!(AddressFilter::Exact(ip("10.0.0.5")).matches(ip("::ffff:10.0.0.5")));
+        assert!(AddressFilter::Exact(ip("::ffff:10.0.0.5")).matches(ip("10.0.0.5")));
+    }
 }
@@ -0,0 +1,919 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::common::frame::{FrameHeader, FrameWriter, FunctionField};
+use crate::common::function::FunctionCode;
+use crate::exception::ExceptionCode;
+use crate::maybe_async::MaybeAsync;
+use crate::server::diagnostics::DiagnosticCounters;
+use crate::server::request::{write_result, BroadcastRequest, Request};
+use crate::server::response::{
+    bits_of, registers_of, CustomFunctionResponse, DeviceIdWriter, ExceptionStatus, FifoQueue,
+    ServerId,
+};
+use crate::server::task::{Reply, SessionHandlers, UnitHandler};
+use crate::server::{
+    DeviceIdentificationStore, RequestContext, RequestHandler, ServerHandlerMap, ServerHandlerType,
+    WriteCoils, WriteRegisters,
+};
+use crate::types::*;
+use crate::{DecodeLevel, RequestError};
+
+use scursor::ReadCursor;
+
+/// Trait implemented by the user to process requests asynchronously
+///
+/// Unlike [`RequestHandler`], the methods receive and return owned values and take `&self`, so
+/// the returned [`MaybeAsync`] may complete after the call returns. A handler can therefore wait
+/// on I/O, e.g. a database or another device, without blocking the server. Handlers that modify
+/// state must use interior mutability. Handlers that complete immediately should return
+/// [`MaybeAsync::ready`] which doesn't allocate.
+///
+/// TCP sessions keep reading requests while previous requests are processed and send each reply
+/// as soon as it is available, so replies may be sent in a different order than the requests
+/// were received. Clients match them to requests using the transaction id.
+///
+/// Each method receives the [`RequestContext`] of the request, i.e. the unit id and the client
+/// that sent it. Handlers that need it after returning, e.g. to log the result, must clone it.
+///
+/// The read methods must return exactly one value per address of the requested range, otherwise
+/// [`ExceptionCode::ServerDeviceFailure`] is returned to the client. File record requests are not
+/// supported and are answered with [`ExceptionCode::IllegalFunction`].
+pub trait AsyncRequestHandler: Send + Sync + 'static {
+    /// Read a range of coils
+    fn read_coils(
+        &self,
+        _context: &RequestContext,
+        _range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<bool>, ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Read a range of discrete inputs
+    fn read_discrete_inputs(
+        &self,
+        _context: &RequestContext,
+        _range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<bool>, ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Read a range of holding registers
+    fn read_holding_registers(
+        &self,
+        _context: &RequestContext,
+        _range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Read a range of input registers
+    fn read_input_registers(
+        &self,
+        _context: &RequestContext,
+        _range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Read the contents of the FIFO queue at the specified pointer address
+    ///
+    /// See [`RequestHandler::read_fifo_queue`] for the number of values that may be returned.
+    fn read_fifo_queue(
+        &self,
+        _context: &RequestContext,
+        _address: u16,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Device identification objects returned in response to Read Device Identification requests
+    ///
+    /// The store is retrieved for every request so that it may change at runtime. The default
+    /// implementation returns `None` which causes [`ExceptionCode::IllegalFunction`] to be
+    /// returned to the client.
+    fn device_identification(&self) -> Option<DeviceIdentificationStore> {
+        None
+    }
+
+    /// Read the eight exception status outputs of the device
+    fn read_exception_status(
+        &self,
+        _context: &RequestContext,
+    ) -> MaybeAsync<Result<u8, ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Device specific identification returned in response to Report Server ID requests
+    ///
+    /// See [`RequestHandler::report_server_id`] for the content of the response.
+    fn report_server_id(
+        &self,
+        _context: &RequestContext,
+    ) -> MaybeAsync<Result<Vec<u8>, ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Write a single coil value
+    fn write_single_coil(
+        &self,
+        _context: &RequestContext,
+        _value: Indexed<bool>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Write a single register value
+    fn write_single_register(
+        &self,
+        _context: &RequestContext,
+        _value: Indexed<u16>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Write multiple coils, `values` contains one value per address of `range`
+    fn write_multiple_coils(
+        &self,
+        _context: &RequestContext,
+        _range: AddressRange,
+        _values: Vec<bool>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Write multiple registers, `values` contains one value per address of `range`
+    fn write_multiple_registers(
+        &self,
+        _context: &RequestContext,
+        _range: AddressRange,
+        _values: Vec<u16>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Modify a single register using an AND mask and an OR mask
+    ///
+    /// Unlike [`RequestHandler::mask_write_register`], there is no default implementation since
+    /// the read and the write must be performed atomically.
+    fn mask_write_register(
+        &self,
+        _context: &RequestContext,
+        _request: MaskWriteRegister,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Write registers and then read registers, returning the values that were read
+    fn read_write_multiple_registers(
+        &self,
+        _context: &RequestContext,
+        _read_range: AddressRange,
+        _write_range: AddressRange,
+        _values: Vec<u16>,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+
+    /// Process a request using a function code that is not implemented by the library
+    ///
+    /// See [`RequestHandler::custom_function`] for the content of the request and response.
+    fn custom_function(
+        &self,
+        _context: &RequestContext,
+        _function_code: u8,
+        _data: Vec<u8>,
+    ) -> MaybeAsync<Result<Vec<u8>, ExceptionCode>> {
+        MaybeAsync::ready(Err(ExceptionCode::IllegalFunction))
+    }
+}
+
+/// Type that hides the underlying map implementation
+/// and allows lookups of an [`AsyncRequestHandler`] from a [`UnitId`]
+#[derive(Clone, Default)]
+pub struct AsyncServerHandlerMap {
+    handlers: BTreeMap<UnitId, Arc<dyn AsyncRequestHandler>>,
+}
+
+impl AsyncServerHandlerMap {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Self {
+            handlers: BTreeMap::new(),
+        }
+    }
+
+    /// Create a new map that contains a single value
+    pub fn single(id: UnitId, handler: Arc<dyn AsyncRequestHandler>) -> Self {
+        let mut map = Self::new();
+        map.add(id, handler);
+        map
+    }
+
+    /// Retrieve a reference to an [`AsyncRequestHandler`]
+    pub fn get(&self, id: UnitId) -> Option<&Arc<dyn AsyncRequestHandler>> {
+        self.handlers.get(&id)
+    }
+
+    /// Add a handler to the map
+    pub fn add(
+        &mut self,
+        id: UnitId,
+        handler: Arc<dyn AsyncRequestHandler>,
+    ) -> Option<Arc<dyn AsyncRequestHandler>> {
+        self.handlers.insert(id, handler)
+    }
+}
+
+impl<T: RequestHandler> From<ServerHandlerMap<T>> for AsyncServerHandlerMap {
+    fn from(map: ServerHandlerMap<T>) -> Self {
+        let mut handlers = Self::new();
+        for (id, handler) in map.iter() {
+            handlers.add(id, Arc::new(SyncHandler::new(handler.clone())));
+        }
+        handlers
+    }
+}
+
+/// Adapter that processes the requests of an [`AsyncServerHandlerMap`] using a [`RequestHandler`]
+///
+/// Each request locks the handler, calls [`RequestHandler::begin_request`] with the context of
+/// the request followed by the method that processes it and completes immediately, so
+/// existing handlers can be served alongside asynchronous ones. A whole [`ServerHandlerMap`] may
+/// be converted using [`From`].
+pub struct SyncHandler<T: RequestHandler> {
+    handler: ServerHandlerType<T>,
+}
+
+impl<T: RequestHandler> SyncHandler<T> {
+    /// Create an adapter around a handler that may also be shared with other maps
+    pub fn new(handler: ServerHandlerType<T>) -> Self {
+        Self { handler }
+    }
+
+    fn call<R>(
+        &self,
+        context: &RequestContext,
+        f: impl FnOnce(&mut T) -> Result<R, ExceptionCode>,
+    ) -> MaybeAsync<Result<R, ExceptionCode>>
+    where
+        R: Send + 'static,
+    {
+        let mut handler = self.handler.lock().unwrap();
+        handler.begin_request(context);
+        MaybeAsync::ready(f(handler.as_mut()))
+    }
+}
+
+impl<T: RequestHandler> AsyncRequestHandler for SyncHandler<T> {
+    fn read_coils(
+        &self,
+        context: &RequestContext,
+        range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<bool>, ExceptionCode>> {
+        self.call(context, |handler| {
+            let mut values = vec![false; range.count as usize];
+            handler.read_coils(range, &mut values)?;
+            Ok(values)
+        })
+    }
+
+    fn read_discrete_inputs(
+        &self,
+        context: &RequestContext,
+        range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<bool>, ExceptionCode>> {
+        self.call(context, |handler| {
+            let mut values = vec![false; range.count as usize];
+            handler.read_discrete_inputs(range, &mut values)?;
+            Ok(values)
+        })
+    }
+
+    fn read_holding_registers(
+        &self,
+        context: &RequestContext,
+        range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        self.call(context, |handler| {
+            let mut values = vec![0; range.count as usize];
+            handler.read_holding_registers(range, &mut values)?;
+            Ok(values)
+        })
+    }
+
+    fn read_input_registers(
+        &self,
+        context: &RequestContext,
+        range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        self.call(context, |handler| {
+            let mut values = vec![0; range.count as usize];
+            handler.read_input_registers(range, &mut values)?;
+            Ok(values)
+        })
+    }
+
+    fn read_fifo_queue(
+        &self,
+        context: &RequestContext,
+        address: u16,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        self.call(context, |handler| handler.read_fifo_queue(address))
+    }
+
+    fn device_identification(&self) -> Option<DeviceIdentificationStore> {
+        self.handler
+            .lock()
+            .unwrap()
+            .device_identification()
+            .cloned()
+    }
+
+    fn read_exception_status(
+        &self,
+        context: &RequestContext,
+    ) -> MaybeAsync<Result<u8, ExceptionCode>> {
+        self.call(context, |handler| handler.read_exception_status())
+    }
+
+    fn report_server_id(
+        &self,
+        context: &RequestContext,
+    ) -> MaybeAsync<Result<Vec<u8>, ExceptionCode>> {
+        self.call(context, |handler| handler.report_server_id())
+    }
+
+    fn write_single_coil(
+        &self,
+        context: &RequestContext,
+        value: Indexed<bool>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.call(context, |handler| handler.write_single_coil(value))
+    }
+
+    fn write_single_register(
+        &self,
+        context: &RequestContext,
+        value: Indexed<u16>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.call(context, |handler| handler.write_single_register(value))
+    }
+
+    fn write_multiple_coils(
+        &self,
+        context: &RequestContext,
+        range: AddressRange,
+        values: Vec<bool>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.call(context, |handler| {
+            let bytes = pack_coils(range, &values)?;
+            let mut cursor = ReadCursor::new(&bytes);
+            let iterator = BitIterator::parse_all(range, &mut cursor)
+                .map_err(|_| ExceptionCode::ServerDeviceFailure)?;
+            handler.write_multiple_coils(WriteCoils::new(range, iterator))
+        })
+    }
+
+    fn write_multiple_registers(
+        &self,
+        context: &RequestContext,
+        range: AddressRange,
+        values: Vec<u16>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.call(context, |handler| {
+            let bytes = pack_registers(range, &values)?;
+            let mut cursor = ReadCursor::new(&bytes);
+            let iterator = RegisterIterator::parse_all(range, &mut cursor)
+                .map_err(|_| ExceptionCode::ServerDeviceFailure)?;
+            handler.write_multiple_registers(WriteRegisters::new(range, iterator))
+        })
+    }
+
+    fn mask_write_register(
+        &self,
+        context: &RequestContext,
+        request: MaskWriteRegister,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.call(context, |handler| handler.mask_write_register(request))
+    }
+
+    fn read_write_multiple_registers(
+        &self,
+        context: &RequestContext,
+        read_range: AddressRange,
+        write_range: AddressRange,
+        values: Vec<u16>,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        self.call(context, |handler| {
+            let bytes = pack_registers(write_range, &values)?;
+            let mut cursor = ReadCursor::new(&bytes);
+            let iterator = RegisterIterator::parse_all(write_range, &mut cursor)
+                .map_err(|_| ExceptionCode::ServerDeviceFailure)?;
+            // the read is performed while the handler is still locked, like the synchronous server
+            handler.read_write_multiple_registers(
+                read_range,
+                WriteRegisters::new(write_range, iterator),
+            )?;
+            let mut values = vec![0; read_range.count as usize];
+            handler.read_holding_registers(read_range, &mut values)?;
+            Ok(values)
+        })
+    }
+
+    fn custom_function(
+        &self,
+        context: &RequestContext,
+        function_code: u8,
+        data: Vec<u8>,
+    ) -> MaybeAsync<Result<Vec<u8>, ExceptionCode>> {
+        self.call(context, |handler| {
+            handler.custom_function(function_code, &data)
+        })
+    }
+}
+
+/// pack coil values into the bytes of a request so they can be passed to a [`RequestHandler`]
+fn pack_coils(range: AddressRange, values: &[bool]) -> Result<Vec<u8>, ExceptionCode> {
+    check_count(range, values)?;
+    let mut bytes = vec![0; crate::common::bits::num_bytes_for_bits(range.count)];
+    for (i, value) in values.iter().enumerate() {
+        if *value {
+            bytes[i / 8] |= 1 << (i % 8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// pack register values into the bytes of a request so they can be passed to a [`RequestHandler`]
+fn pack_registers(range: AddressRange, values: &[u16]) -> Result<Vec<u8>, ExceptionCode> {
+    check_count(range, values)?;
+    Ok(values.iter().flat_map(|x| x.to_be_bytes()).collect())
+}
+
+/// Answers the requests that asynchronous handlers don't process, i.e. the diagnostics
+/// requests answered by the session and the unsupported file record requests
+struct Unsupported;
+
+impl RequestHandler for Unsupported {}
+
+impl SessionHandlers for AsyncServerHandlerMap {
+    fn contains(&self, unit_id: UnitId) -> bool {
+        self.handlers.contains_key(&unit_id)
+    }
+
+    fn unit_ids(&self) -> Vec<UnitId> {
+        self.handlers.keys().copied().collect()
+    }
+
+    fn add_unit(&mut self, unit_id: UnitId, handler: &UnitHandler) -> bool {
+        match handler.get::<Arc<dyn AsyncRequestHandler>>() {
+            Some(handler) => {
+                self.add(unit_id, handler.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove_unit(&mut self, unit_id: UnitId) -> bool {
+        self.handlers.remove(&unit_id).is_some()
+    }
+
+    fn process<'a>(
+        &mut self,
+        context: &RequestContext,
+        header: FrameHeader,
+        request: &Request,
+        diagnostics: Option<&mut DiagnosticCounters>,
+        writer: &'a mut FrameWriter,
+        level: DecodeLevel,
+    ) -> Option<Result<Reply<'a>, RequestError>> {
+        let handler = self.handlers.get(&context.unit_id)?;
+        let function = request.get_function();
+
+        if let Request::ReadDeviceIdentification(request) = request {
+            let store = handler.device_identification();
+            let result = match &store {
+                None => Err(ExceptionCode::IllegalFunction),
+                Some(store) => DeviceIdWriter::new(store, request.code, request.object_id),
+            };
+            return Some(write_result(function, header, writer, result, level).map(Reply::Ready));
+        }
+
+        let response = match call(handler.as_ref(), context, request) {
+            Some(x) => x,
+            None => {
+                return Some(
+                    request
+                        .get_reply(header, &mut Unsupported, diagnostics, writer, level)
+                        .map(Reply::Ready),
+                )
+            }
+        };
+
+        // format the reply immediately if the handler completed synchronously
+        match response.try_ready() {
+            Ok(result) => {
+                Some(format_response(header, function, result, writer, level).map(Reply::Ready))
+            }
+            Err(response) => Some(Ok(Reply::Pending(response))),
+        }
+    }
+
+    fn broadcast(
+        &mut self,
+        context: &RequestContext,
+        request: &BroadcastRequest,
+        unit_ids: &[UnitId],
+    ) {
+        for handler in unit_ids.iter().filter_map(|x| self.handlers.get(x)) {
+            let result = match request {
+                BroadcastRequest::WriteSingleCoil(x) => handler.write_single_coil(context, *x),
+                BroadcastRequest::WriteSingleRegister(x) => {
+                    handler.write_single_register(context, *x)
+                }
+                BroadcastRequest::WriteMultipleCoils(x) => handler.write_multiple_coils(
+                    context,
+                    x.range,
+                    x.iterator.map(|x| x.value).collect(),
+                ),
+                BroadcastRequest::WriteMultipleRegisters(x) => handler.write_multiple_registers(
+                    context,
+                    x.range,
+                    x.iterator.map(|x| x.value).collect(),
+                ),
+                BroadcastRequest::MaskWriteRegister(x) => handler.mask_write_register(context, *x),
+            };
+            // no response is returned, so there is nothing to wait for
+            if let Err(result) = result.try_ready() {
+                tokio::spawn(result.get());
+            }
+        }
+    }
+}
+
+/// Values returned by an asynchronous handler that are formatted once they are available
+pub(crate) enum AsyncResponse {
+    Bits(ReadBitsRange, Vec<bool>),
+    Registers(ReadRegistersRange, Vec<u16>),
+    Coil(Indexed<bool>),
+    Register(Indexed<u16>),
+    Range(AddressRange),
+    MaskWrite(MaskWriteRegister),
+    ExceptionStatus(u8),
+    ServerId(Vec<u8>),
+    FifoQueue(Vec<u16>),
+    Custom(Vec<u8>),
+}
+
+/// Call the handler method that processes the request, returns `None` if the request isn't
+/// processed by asynchronous handlers
+fn call(
+    handler: &dyn AsyncRequestHandler,
+    context: &RequestContext,
+    request: &Request,
+) -> Option<MaybeAsync<Result<AsyncResponse, ExceptionCode>>> {
+    let response = match request {
+        Request::ReadCoils(range) => {
+            let range = *range;
+            handler
+                .read_coils(context, range.get())
+                .map(move |x| x.map(|x| AsyncResponse::Bits(range, x)))
+        }
+        Request::ReadDiscreteInputs(range) => {
+            let range = *range;
+            handler
+                .read_discrete_inputs(context, range.get())
+                .map(move |x| x.map(|x| AsyncResponse::Bits(range, x)))
+        }
+        Request::ReadHoldingRegisters(range) => {
+            let range = *range;
+            handler
+                .read_holding_registers(context, range.get())
+                .map(move |x| x.map(|x| AsyncResponse::Registers(range, x)))
+        }
+        Request::ReadInputRegisters(range) => {
+            let range = *range;
+            handler
+                .read_input_registers(context, range.get())
+                .map(move |x| x.map(|x| AsyncResponse::Registers(range, x)))
+        }
+        Request::WriteSingleCoil(value) => {
+            let value = *value;
+            handler
+                .write_single_coil(context, value)
+                .map(move |x| x.map(|_| AsyncResponse::Coil(value)))
+        }
+        Request::WriteSingleRegister(value) => {
+            let value = *value;
+            handler
+                .write_single_register(context, value)
+                .map(move |x| x.map(|_| AsyncResponse::Register(value)))
+        }
+        Request::ReadExceptionStatus => handler
+            .read_exception_status(context)
+            .map(|x| x.map(AsyncResponse::ExceptionStatus)),
+        Request::WriteMultipleCoils(items) => {
+            let range = items.range;
+            handler
+                .write_multiple_coils(context, range, items.iterator.map(|x| x.value).collect())
+                .map(move |x| x.map(|_| AsyncResponse::Range(range)))
+        }
+        Request::WriteMultipleRegisters(items) => {
+            let range = items.range;
+            handler
+                .write_multiple_registers(context, range, items.iterator.map(|x| x.value).collect())
+                .map(move |x| x.map(|_| AsyncResponse::Range(range)))
+        }
+        Request::ReportServerId => handler
+            .report_server_id(context)
+            .map(|x| x.map(AsyncResponse::ServerId)),
+        Request::MaskWriteRegister(request) => {
+            let request = *request;
+            handler
+                .mask_write_register(context, request)
+                .map(move |x| x.map(|_| AsyncResponse::MaskWrite(request)))
+        }
+        Request::ReadWriteMultipleRegisters(request) => {
+            let read_range = request.read_range;
+            handler
+                .read_write_multiple_registers(
+                    context,
+                    read_range.get(),
+                    request.write.range,
+                    request.write.iterator.map(|x| x.value).collect(),
+                )
+                .map(move |x| x.map(|x| AsyncResponse::Registers(read_range, x)))
+        }
+        Request::ReadFifoQueue(address) => handler
+            .read_fifo_queue(context, *address)
+            .map(|x| x.map(AsyncResponse::FifoQueue)),
+        Request::CustomFunction(code, data) => handler
+            .custom_function(context, *code, data.to_vec())
+            .map(|x| x.map(AsyncResponse::Custom)),
+        Request::Diagnostics(_)
+        | Request::GetCommEventCounter
+        | Request::GetCommEventLog
+        | Request::ReadFileRecord(_)
+        | Request::WriteFileRecord(_)
+        | Request::ReadDeviceIdentification(_) => return None,
+    };
+
+    Some(response)
+}
+
+/// check that there is one value per address of the range
+fn check_count<T>(range: AddressRange, values: &[T]) -> Result<(), ExceptionCode> {
+    if values.len() != range.count as usize {
+        tracing::warn!(
+            "{} values were provided for a range of {} addresses",
+            values.len(),
+            range.count
+        );
+        return Err(ExceptionCode::ServerDeviceFailure);
+    }
+    Ok(())
+}
+
+pub(crate) fn format_response(
+    header: FrameHeader,
+    function: FunctionCode,
+    result: Result<AsyncResponse, ExceptionCode>,
+    writer: &mut FrameWriter,
+    level: DecodeLevel,
+) -> Result<&[u8], RequestError> {
+    let response = match result {
+        Ok(x) => x,
+        Err(ex) => return writer.format_ex(header, FunctionField::Exception(function), ex, level),
+    };
+
+    match response {
+        AsyncResponse::Bits(range, values) => {
+            let result = check_count(range.get(), &values).map(|_| bits_of(range, &values));
+            write_result(function, header, writer, result, level)
+        }
+        AsyncResponse::Registers(range, values) => {
+            let result = check_count(range.get(), &values).map(|_| registers_of(range, &values));
+            write_result(function, header, writer, result, level)
+        }
+        AsyncResponse::Coil(x) => writer.format_reply(header, function, &x, level),
+        AsyncResponse::Register(x) => writer.format_reply(header, function, &x, level),
+        AsyncResponse::Range(x) => writer.format_reply(header, function, &x, level),
+        AsyncResponse::MaskWrite(x) => writer.format_reply(header, function, &x, level),
+        AsyncResponse::ExceptionStatus(x) => {
+            writer.format_reply(header, function, &ExceptionStatus::new(x), level)
+        }
+        AsyncResponse::ServerId(x) => {
+            write_result(function, header, writer, ServerId::new(x), level)
+        }
+        AsyncResponse::FifoQueue(x) => {
+            write_result(function, header, writer, FifoQueue::new(x), level)
+        }
+        AsyncResponse::Custom(x) => write_result(
+            function,
+            header,
+            writer,
+            CustomFunctionResponse::new(x),
+            level,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::frame::TxId;
+    use crate::server::TransportKind;
+
+    struct Registers;
+
+    impl AsyncRequestHandler for Registers {
+        fn read_holding_registers(
+            &self,
+            _context: &RequestContext,
+            range: AddressRange,
+        ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+            MaybeAsync::ready(Ok(range.iter().collect()))
+        }
+
+        fn read_input_registers(
+            &self,
+            _context: &RequestContext,
+            range: AddressRange,
+        ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+            MaybeAsync::asynchronous(async move { Ok(range.iter().collect()) })
+        }
+    }
+
+    #[derive(Default)]
+    struct Coils {
+        values: [bool; 10],
+    }
+
+    impl RequestHandler for Coils {
+        fn read_coil(&self, address: u16) -> Result<bool, ExceptionCode> {
+            self.values
+                .get(address as usize)
+                .copied()
+                .ok_or(ExceptionCode::IllegalDataAddress)
+        }
+
+        fn write_multiple_coils(&mut self, values: WriteCoils) -> Result<(), ExceptionCode> {
+            for x in values.iterator {
+                match self.values.get_mut(x.index as usize) {
+                    Some(value) => *value = x.value,
+                    None => return Err(ExceptionCode::IllegalDataAddress),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn context() -> RequestContext {
+        RequestContext::new(UnitId::new(1), None, None, TransportKind::Tcp, None)
+    }
+
+    fn complete<T>(result: MaybeAsync<Result<T, ExceptionCode>>) -> Result<T, ExceptionCode> {
+        match result.try_ready() {
+            Ok(x) => x,
+            Err(_) => panic!("synchronous handler did not complete immediately"),
+        }
+    }
+
+    #[test]
+    fn sync_handler_adapts_per_point_methods() {
+        let handler = SyncHandler::new(Coils::default().wrap());
+        let range = AddressRange::try_from(7, 3).unwrap();
+
+        assert_eq!(
+            complete(handler.write_multiple_coils(&context(), range, vec![true, false, true])),
+            Ok(())
+        );
+        assert_eq!(
+            complete(handler.read_coils(&context(), range)),
+            Ok(vec![true, false, true])
+        );
+        assert_eq!(
+            complete(handler.read_coils(&context(), AddressRange::try_from(9, 2).unwrap())),
+            Err(ExceptionCode::IllegalDataAddress)
+        );
+        assert_eq!(
+            complete(handler.write_multiple_coils(&context(), range, vec![true])),
+            Err(ExceptionCode::ServerDeviceFailure)
+        );
+        assert_eq!(
+            complete(handler.read_holding_registers(&context(), range)),
+            Err(ExceptionCode::IllegalFunction)
+        );
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        peers: Vec<Option<std::net::SocketAddr>>,
+    }
+
+    impl RequestHandler for Recorder {
+        fn begin_request(&mut self, context: &RequestContext) {
+            self.peers.push(context.peer);
+        }
+
+        fn read_holding_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+            Ok(address)
+        }
+    }
+
+    #[test]
+    fn sync_handler_passes_context_before_each_request() {
+        let recorder = Recorder::default().wrap();
+        let handler = SyncHandler::new(recorder.clone());
+        let range = AddressRange::try_from(0, 1).unwrap();
+        let first = "127.0.0.1:40000".parse().unwrap();
+        let second = "127.0.0.1:40001".parse().unwrap();
+
+        for peer in [first, second] {
+            let context =
+                RequestContext::new(UnitId::new(1), None, Some(peer), TransportKind::Tcp, None);
+            assert_eq!(
+                complete(handler.read_holding_registers(&context, range)),
+                Ok(vec![0])
+            );
+        }
+
+        assert_eq!(
+            recorder.lock().unwrap().peers,
+            vec![Some(first), Some(second)]
+        );
+    }
+
+    fn process(request: &Request) -> Option<Vec<u8>> {
+        let mut map = AsyncServerHandlerMap::single(UnitId::new(1), Arc::new(Registers));
+        let mut writer = FrameWriter::tcp();
+        let header = FrameHeader::new_tcp_header(UnitId::new(1), TxId::new(7));
+        match map
+            .process(
+                &context(),
+                header,
+                request,
+                None,
+                &mut writer,
+                DecodeLevel::nothing(),
+            )
+            .unwrap()
+            .unwrap()
+        {
+            Reply::Ready(bytes) => Some(bytes.to_vec()),
+            Reply::Pending(_) => None,
+        }
+    }
+
+    #[test]
+    fn replies_immediately_when_handler_is_ready() {
+        let range = AddressRange::try_from(3, 2).unwrap();
+        let request = Request::ReadHoldingRegisters(range.of_read_registers().unwrap());
+        assert_eq!(
+            process(&request),
+            Some(vec![
+                0x00, 0x07, 0x00, 0x00, 0x00, 0x07, 0x01, 0x03, 0x04, 0x00, 0x03, 0x00, 0x04
+            ])
+        );
+    }
+
+    #[test]
+    fn defers_reply_when_handler_is_asynchronous() {
+        let range = AddressRange::try_from(3, 2).unwrap();
+        let request = Request::ReadInputRegisters(range.of_read_registers().unwrap());
+        assert_eq!(process(&request), None);
+    }
+
+    #[test]
+    fn unsupported_requests_return_illegal_function() {
+        let request = Request::ReadExceptionStatus;
+        assert_eq!(
+            process(&request),
+            Some(vec![0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x87, 0x01])
+        );
+    }
+
+    #[test]
+    fn wrong_number_of_values_returns_server_device_failure() {
+        let range = AddressRange::try_from(3, 2).unwrap();
+        let mut writer = FrameWriter::tcp();
+        let header = FrameHeader::new_tcp_header(UnitId::new(1), TxId::new(7));
+        let bytes = format_response(
+            header,
+            FunctionCode::ReadHoldingRegisters,
+            Ok(AsyncResponse::Registers(
+                range.of_read_registers().unwrap(),
+                vec![0x0001],
+            )),
+            &mut writer,
+            DecodeLevel::nothing(),
+        )
+        .unwrap();
+        assert_eq!(
+            bytes,
+            &[0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x83, 0x04]
+        );
+    }
+}
@@ -0,0 +1,812 @@
+use std::sync::Arc;
+
+use tracing::level_filters::LevelFilter;
+
+use crate::common::function::FunctionCode;
+use crate::server::{Authorization, AuthorizationHandler};
+use crate::types::{AddressRange, UnitId};
+use crate::{FileRecord, ReadDeviceIdCode};
+
+/// Addresses matched by an [`AuthorizationRule`], both bounds are included
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+pub struct AddressSpan {
+    /// First address of the span
+    pub first: u16,
+    /// Last address of the span
+    pub last: u16,
+}
+
+impl AddressSpan {
+    /// Create a span from its first and last address
+    pub fn new(first: u16, last: u16) -> Self {
+        Self { first, last }
+    }
+
+    fn bounds(range: AddressRange) -> (u32, u32) {
+        let start = range.start as u32;
+        (start, start + range.count.max(1) as u32 - 1)
+    }
+
+    fn contains(&self, range: AddressRange) -> bool {
+        let (first, last) = Self::bounds(range);
+        self.first as u32 <= first && last <= self.last as u32
+    }
+
+    fn overlaps(&self, range: AddressRange) -> bool {
+        let (first, last) = Self::bounds(range);
+        first <= self.last as u32 && self.first as u32 <= last
+    }
+}
+
+/// Rule that allows or denies the requests matching all of its criteria
+///
+/// Criteria that are left empty match any request. The addresses of a request are the addresses
+/// of the points it reads or writes, or the record numbers of file record requests. An allow rule
+/// with addresses only matches requests whose ranges each lie within one of its spans, while a
+/// deny rule with addresses matches requests that access any of the addresses of its spans.
+/// Requests without addresses, e.g. diagnostics, never match rules with addresses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize))]
+pub struct AuthorizationRule {
+    /// Optional name of the rule that appears in the logs
+    #[cfg_attr(feature = "config", serde(default))]
+    pub name: Option<String>,
+    /// Decision applied to the requests that match the rule
+    pub effect: Authorization,
+    /// Roles of the clients, as found in their certificate
+    #[cfg_attr(feature = "config", serde(default))]
+    pub roles: Vec<String>,
+    /// Function codes of the requests
+    #[cfg_attr(feature = "config", serde(default))]
+    pub function_codes: Vec<u8>,
+    /// Unit ids the requests are addressed to
+    #[cfg_attr(feature = "config", serde(default))]
+    pub unit_ids: Vec<u8>,
+    /// Addresses accessed by the requests
+    #[cfg_attr(feature = "config", serde(default))]
+    pub addresses: Vec<AddressSpan>,
+}
+
+impl AuthorizationRule {
+    /// Create a rule that allows any request
+    pub fn allow() -> Self {
+        Self::new(Authorization::Allow)
+    }
+
+    /// Create a rule that denies any request
+    pub fn deny() -> Self {
+        Self::new(Authorization::Deny)
+    }
+
+    fn new(effect: Authorization) -> Self {
+        Self {
+            name: None,
+            effect,
+            roles: Vec::new(),
+            function_codes: Vec::new(),
+            unit_ids: Vec::new(),
+            addresses: Vec::new(),
+        }
+    }
+
+    /// Set the name of the rule
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Add a role to the roles matched by the rule
+    pub fn role(mut self, role: &str) -> Self {
+        self.roles.push(role.to_string());
+        self
+    }
+
+    /// Add a function code to the function codes matched by the rule
+    pub fn function_code(mut self, code: u8) -> Self {
+        self.function_codes.push(code);
+        self
+    }
+
+    /// Add a unit id to the unit ids matched by the rule
+    pub fn unit_id(mut self, unit_id: UnitId) -> Self {
+        self.unit_ids.push(unit_id.value);
+        self
+    }
+
+    /// Add a span to the addresses matched by the rule
+    pub fn addresses(mut self, span: AddressSpan) -> Self {
+        self.addresses.push(span);
+        self
+    }
+
+    fn specificity(&self) -> usize {
+        [
+            self.roles.is_empty(),
+            self.function_codes.is_empty(),
+            self.unit_ids.is_empty(),
+            self.addresses.is_empty(),
+        ]
+        .iter()
+        .filter(|x| !**x)
+        .count()
+    }
+
+    fn matches(&self, target: &Target) -> bool {
+        if !self.roles.is_empty() && !self.roles.iter().any(|x| x == target.role) {
+            return false;
+        }
+
+        if !self.function_codes.is_empty()
+            && !self.function_codes.contains(&target.function.get_value())
+        {
+            return false;
+        }
+
+        if !self.unit_ids.is_empty() && !self.unit_ids.contains(&target.unit_id.value) {
+            return false;
+        }
+
+        if self.addresses.is_empty() {
+            return true;
+        }
+
+        if target.ranges.is_empty() {
+            return false;
+        }
+
+        match self.effect {
+            Authorization::Allow => target
+                .ranges
+                .iter()
+                .all(|range| self.addresses.iter().any(|span| span.contains(*range))),
+            Authorization::Deny => target
+                .ranges
+                .iter()
+                .any(|range| self.addresses.iter().any(|span| span.overlaps(*range))),
+        }
+    }
+}
+
+/// Determines which rule decides when several rules match a request
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum RuleMatching {
+    /// The first matching rule in the order of the list decides
+    FirstMatch,
+    /// The matching rule with the most criteria decides, deny rules win over allow rules
+    /// with as many criteria and the first one wins otherwise
+    MostSpecific,
+}
+
+/// Error returned when a set of authorization rules cannot be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RulesError {
+    /// The rule file could not be read
+    Io(String),
+    /// The content of the rules is not in the expected format
+    Parse(String),
+    /// The extension of the rule file is neither `.toml` nor `.json`
+    UnsupportedFormat,
+    /// The first address of a span is after its last address
+    BadSpan(usize, AddressSpan),
+}
+
+impl std::error::Error for RulesError {}
+
+impl std::fmt::Display for RulesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self {
+            RulesError::Io(err) => write!(f, "unable to read the rule file: {}", err),
+            RulesError::Parse(err) => write!(f, "unable to parse the rules: {}", err),
+            RulesError::UnsupportedFormat => {
+                f.write_str("rule files must have a .toml or .json extension")
+            }
+            RulesError::BadSpan(index, span) => write!(
+                f,
+                "rule #{} has a span whose first address ({}) is after its last address ({})",
+                index, span.first, span.last
+            ),
+        }
+    }
+}
+
+/// Ordered list of [`AuthorizationRule`] used by a [`RuleBasedAuthorizationHandler`]
+///
+/// Requests that match no rule receive the default decision, i.e. [`Authorization::Deny`]
+/// unless specified otherwise. With the `config` feature, the rules may be loaded from a TOML or
+/// JSON document using the same field names, e.g.
+///
+/// ```toml
+/// matching = "most_specific"
+/// default = "deny"
+/// log_level = "info"
+///
+/// [[rules]]
+/// name = "operators can read unit 1"
+/// effect = "allow"
+/// roles = ["operator"]
+/// function_codes = [1, 2, 3, 4]
+/// unit_ids = [1]
+///
+/// [[rules]]
+/// name = "setpoints are read-only"
+/// effect = "deny"
+/// function_codes = [6, 16, 22, 23]
+/// addresses = [{ first = 100, last = 199 }]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize), serde(default))]
+pub struct AuthorizationRules {
+    matching: RuleMatching,
+    default: Authorization,
+    #[cfg_attr(feature = "config", serde(deserialize_with = "deserialize_level"))]
+    log_level: LevelFilter,
+    rules: Vec<AuthorizationRule>,
+}
+
+impl Default for AuthorizationRules {
+    fn default() -> Self {
+        Self::new(RuleMatching::FirstMatch)
+    }
+}
+
+impl AuthorizationRules {
+    /// Create an empty list of rules that denies requests by default and logs every
+    /// decision at the INFO level
+    pub fn new(matching: RuleMatching) -> Self {
+        Self {
+            matching,
+            default: Authorization::Deny,
+            log_level: LevelFilter::INFO,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Add a rule after the existing rules
+    pub fn rule(mut self, rule: AuthorizationRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Set the decision applied to the requests that match no rule
+    pub fn default_decision(mut self, decision: Authorization) -> Self {
+        self.default = decision;
+        self
+    }
+
+    /// Set the level at which decisions are logged, [`LevelFilter::OFF`] disables the logs
+    pub fn log_level(mut self, level: LevelFilter) -> Self {
+        self.log_level = level;
+        self
+    }
+
+    /// Load the rules from a TOML document
+    #[cfg(feature = "config")]
+    pub fn from_toml(document: &str) -> Result<Self, RulesError> {
+        let rules: Self = toml::from_str(document).map_err(|x| RulesError::Parse(x.to_string()))?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Load the rules from a JSON document
+    #[cfg(feature = "config")]
+    pub fn from_json(document: &str) -> Result<Self, RulesError> {
+        let rules: Self =
+            serde_json::from_str(document).map_err(|x| RulesError::Parse(x.to_string()))?;
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Load the rules from a file whose format is determined by its `.toml` or `.json` extension
+    #[cfg(feature = "config")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, RulesError> {
+        let path = path.as_ref();
+        let parse = match path.extension().and_then(|x| x.to_str()) {
+            Some("toml") => Self::from_toml,
+            Some("json") => Self::from_json,
+            _ => return Err(RulesError::UnsupportedFormat),
+        };
+        let document = std::fs::read_to_string(path).map_err(|x| RulesError::Io(x.to_string()))?;
+        parse(&document)
+    }
+
+    fn validate(&self) -> Result<(), RulesError> {
+        for (index, rule) in self.rules.iter().enumerate() {
+            if let Some(span) = rule.addresses.iter().find(|x| x.first > x.last) {
+                return Err(RulesError::BadSpan(index, *span));
+            }
+        }
+        Ok(())
+    }
+
+    fn find(&self, target: &Target) -> Option<(usize, &AuthorizationRule)> {
+        let mut matches = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.matches(target));
+
+        match self.matching {
+            RuleMatching::FirstMatch => matches.next(),
+            RuleMatching::MostSpecific => matches.fold(None, |best, (index, rule)| {
+                let key = (rule.specificity(), rule.effect == Authorization::Deny);
+                match best {
+                    Some((_, x)) if (x.specificity(), x.effect == Authorization::Deny) >= key => {
+                        best
+                    }
+                    _ => Some((index, rule)),
+                }
+            }),
+        }
+    }
+
+    fn authorize(&self, target: Target) -> Authorization {
+        let found = self.find(&target);
+        let decision = found.map(|(_, rule)| rule.effect).unwrap_or(self.default);
+        let outcome = match decision {
+            Authorization::Allow => "allowed",
+            Authorization::Deny => "denied",
+        };
+        let reason = Reason(found);
+
+        macro_rules! log {
+            ($level:expr) => {
+                tracing::event!(
+                    $level,
+                    "{} {} for role \"{}\" on unit {}{} - {}",
+                    outcome,
+                    target.function,
+                    target.role,
+                    target.unit_id,
+                    Ranges(target.ranges),
+                    reason
+                )
+            };
+        }
+
+        match self.log_level.into_level() {
+            None => {}
+            Some(tracing::Level::ERROR) => log!(tracing::Level::ERROR),
+            Some(tracing::Level::WARN) => log!(tracing::Level::WARN),
+            Some(tracing::Level::INFO) => log!(tracing::Level::INFO),
+            Some(tracing::Level::DEBUG) => log!(tracing::Level::DEBUG),
+            Some(_) => log!(tracing::Level::TRACE),
+        }
+
+        decision
+    }
+}
+
+#[cfg(feature = "config")]
+fn deserialize_level<'de, D>(deserializer: D) -> Result<LevelFilter, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let level: String = serde::Deserialize::deserialize(deserializer)?;
+    level.parse().map_err(serde::de::Error::custom)
+}
+
+/// Request as seen by the rules
+struct Target<'a> {
+    role: &'a str,
+    unit_id: UnitId,
+    function: FunctionCode,
+    ranges: &'a [AddressRange],
+}
+
+struct Ranges<'a>(&'a [AddressRange]);
+
+impl std::fmt::Display for Ranges<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for range in self.0 {
+            write!(f, " ({})", range)?;
+        }
+        Ok(())
+    }
+}
+
+struct Reason<'a>(Option<(usize, &'a AuthorizationRule)>);
+
+impl std::fmt::Display for Reason<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            None => f.write_str("no matching rule"),
+            Some((index, rule)) => match &rule.name {
+                None => write!(f, "rule #{}", index),
+                Some(name) => write!(f, "rule #{} \"{}\"", index, name),
+            },
+        }
+    }
+}
+
+/// Authorization handler that makes its decisions using a list of [`AuthorizationRules`]
+///
+/// Every decision is logged with the rule that made it at the level of the rules.
+#[derive(Debug)]
+pub struct RuleBasedAuthorizationHandler {
+    rules: AuthorizationRules,
+}
+
+impl RuleBasedAuthorizationHandler {
+    /// Create a handler from a list of rules
+    pub fn new(rules: AuthorizationRules) -> Result<Self, RulesError> {
+        rules.validate()?;
+        Ok(Self { rules })
+    }
+
+    /// Create a handler from a list of rules suitable for passing to the server
+    pub fn create(rules: AuthorizationRules) -> Result<Arc<dyn AuthorizationHandler>, RulesError> {
+        Ok(Arc::new(Self::new(rules)?))
+    }
+
+    fn authorize(
+        &self,
+        role: &str,
+        unit_id: UnitId,
+        function: FunctionCode,
+        ranges: &[AddressRange],
+    ) -> Authorization {
+        self.rules.authorize(Target {
+            role,
+            unit_id,
+            function,
+            ranges,
+        })
+    }
+
+    fn authorize_point(
+        &self,
+        role: &str,
+        unit_id: UnitId,
+        function: FunctionCode,
+        address: u16,
+    ) -> Authorization {
+        let range = AddressRange {
+            start: address,
+            count: 1,
+        };
+        self.authorize(role, unit_id, function, &[range])
+    }
+}
+
+impl AuthorizationHandler for RuleBasedAuthorizationHandler {
+    fn read_coils(&self, unit_id: UnitId, range: AddressRange, role: &str) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::ReadCoils, &[range])
+    }
+
+    fn read_discrete_inputs(
+        &self,
+        unit_id: UnitId,
+        range: AddressRange,
+        role: &str,
+    ) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::ReadDiscreteInputs, &[range])
+    }
+
+    fn read_holding_registers(
+        &self,
+        unit_id: UnitId,
+        range: AddressRange,
+        role: &str,
+    ) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::ReadHoldingRegisters, &[range])
+    }
+
+    fn read_input_registers(
+        &self,
+        unit_id: UnitId,
+        range: AddressRange,
+        role: &str,
+    ) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::ReadInputRegisters, &[range])
+    }
+
+    fn read_fifo_queue(&self, unit_id: UnitId, address: u16, role: &str) -> Authorization {
+        self.authorize_point(role, unit_id, FunctionCode::ReadFifoQueue, address)
+    }
+
+    fn read_device_identification(
+        &self,
+        unit_id: UnitId,
+        _code: ReadDeviceIdCode,
+        _object_id: u8,
+        role: &str,
+    ) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::ReadDeviceIdentification, &[])
+    }
+
+    fn read_exception_status(&self, unit_id: UnitId, role: &str) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::ReadExceptionStatus, &[])
+    }
+
+    fn diagnostics(&self, unit_id: UnitId, _sub_function: u16, role: &str) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::Diagnostics, &[])
+    }
+
+    fn get_comm_event_counter(&self, unit_id: UnitId, role: &str) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::GetCommEventCounter, &[])
+    }
+
+    fn get_comm_event_log(&self, unit_id: UnitId, role: &str) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::GetCommEventLog, &[])
+    }
+
+    fn report_server_id(&self, unit_id: UnitId, role: &str) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::ReportServerId, &[])
+    }
+
+    fn write_single_coil(&self, unit_id: UnitId, idx: u16, role: &str) -> Authorization {
+        self.authorize_point(role, unit_id, FunctionCode::WriteSingleCoil, idx)
+    }
+
+    fn write_single_register(&self, unit_id: UnitId, idx: u16, role: &str) -> Authorization {
+        self.authorize_point(role, unit_id, FunctionCode::WriteSingleRegister, idx)
+    }
+
+    fn write_multiple_coils(
+        &self,
+        unit_id: UnitId,
+        range: AddressRange,
+        role: &str,
+    ) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::WriteMultipleCoils, &[range])
+    }
+
+    fn write_multiple_registers(
+        &self,
+        unit_id: UnitId,
+        range: AddressRange,
+        role: &str,
+    ) -> Authorization {
+        self.authorize(
+            role,
+            unit_id,
+            FunctionCode::WriteMultipleRegisters,
+            &[range],
+        )
+    }
+
+    fn mask_write_register(&self, unit_id: UnitId, idx: u16, role: &str) -> Authorization {
+        self.authorize_point(role, unit_id, FunctionCode::MaskWriteRegister, idx)
+    }
+
+    fn read_write_multiple_registers(
+        &self,
+        unit_id: UnitId,
+        read_range: AddressRange,
+        write_range: AddressRange,
+        role: &str,
+    ) -> Authorization {
+        self.authorize(
+            role,
+            unit_id,
+            FunctionCode::ReadWriteMultipleRegisters,
+            &[read_range, write_range],
+        )
+    }
+
+    fn read_file_record(&self, unit_id: UnitId, record: FileRecord, role: &str) -> Authorization {
+        let range = AddressRange {
+            start: record.record_number,
+            count: record.record_length,
+        };
+        self.authorize(role, unit_id, FunctionCode::ReadFileRecord, &[range])
+    }
+
+    fn write_file_record(
+        &self,
+        unit_id: UnitId,
+        _file_number: u16,
+        range: AddressRange,
+        role: &str,
+    ) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::WriteFileRecord, &[range])
+    }
+
+    fn custom_function(&self, unit_id: UnitId, function_code: u8, role: &str) -> Authorization {
+        self.authorize(role, unit_id, FunctionCode::Custom(function_code), &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::range;
+
+    fn handler(rules: AuthorizationRules) -> RuleBasedAuthorizationHandler {
+        RuleBasedAuthorizationHandler::new(rules.log_level(LevelFilter::OFF)).unwrap()
+    }
+
+    fn rules(matching: RuleMatching) -> AuthorizationRules {
+        AuthorizationRules::new(matching)
+            .rule(
+                AuthorizationRule::allow()
+                    .role("operator")
+                    .function_code(3)
+                    .function_code(6),
+            )
+            .rule(
+                AuthorizationRule::deny()
+                    .function_code(6)
+                    .addresses(AddressSpan::new(100, 199)),
+            )
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let handler = handler(rules(RuleMatching::FirstMatch));
+        let unit = UnitId::new(1);
+
+        assert_eq!(
+            handler.write_single_register(unit, 150, "operator"),
+            Authorization::Allow
+        );
+        assert_eq!(
+            handler.write_single_register(unit, 150, "viewer"),
+            Authorization::Deny
+        );
+        assert_eq!(
+            handler.read_coils(unit, range(0, 10), "operator"),
+            Authorization::Deny
+        );
+    }
+
+    #[test]
+    fn most_specific_rule_decides() {
+        let handler = handler(rules(RuleMatching::MostSpecific));
+        let unit = UnitId::new(1);
+
+        assert_eq!(
+            handler.write_single_register(unit, 150, "operator"),
+            Authorization::Deny
+        );
+        assert_eq!(
+            handler.write_single_register(unit, 99, "operator"),
+            Authorization::Allow
+        );
+        assert_eq!(
+            handler.read_holding_registers(unit, range(150, 10), "operator"),
+            Authorization::Allow
+        );
+    }
+
+    #[test]
+    fn deny_wins_between_rules_with_as_many_criteria() {
+        let handler = handler(
+            AuthorizationRules::new(RuleMatching::MostSpecific)
+                .rule(AuthorizationRule::allow().role("operator"))
+                .rule(AuthorizationRule::deny().unit_id(UnitId::new(2)))
+                .default_decision(Authorization::Allow),
+        );
+
+        assert_eq!(
+            handler.report_server_id(UnitId::new(1), "operator"),
+            Authorization::Allow
+        );
+        assert_eq!(
+            handler.report_server_id(UnitId::new(2), "operator"),
+            Authorization::Deny
+        );
+        assert_eq!(
+            handler.report_server_id(UnitId::new(3), "viewer"),
+            Authorization::Allow
+        );
+    }
+
+    #[test]
+    fn allow_rules_must_contain_every_range() {
+        let handler = handler(
+            AuthorizationRules::new(RuleMatching::FirstMatch).rule(
+                AuthorizationRule::allow()
+                    .addresses(AddressSpan::new(0, 9))
+                    .addresses(AddressSpan::new(20, 29)),
+            ),
+        );
+        let unit = UnitId::new(1);
+
+        assert_eq!(
+            handler.read_coils(unit, range(0, 10), "operator"),
+            Authorization::Allow
+        );
+        assert_eq!(
+            handler.read_coils(unit, range(5, 10), "operator"),
+            Authorization::Deny
+        );
+        assert_eq!(
+            handler.read_write_multiple_registers(unit, range(0, 5), range(20, 5), "operator"),
+            Authorization::Allow
+        );
+        assert_eq!(
+            handler.read_write_multiple_registers(unit, range(0, 5), range(30, 5), "operator"),
+            Authorization::Deny
+        );
+        assert_eq!(
+            handler.read_exception_status(unit, "operator"),
+            Authorization::Deny
+        );
+    }
+
+    #[test]
+    fn rejects_span_that_ends_before_it_starts() {
+        let rules = AuthorizationRules::new(RuleMatching::FirstMatch)
+            .rule(AuthorizationRule::allow())
+            .rule(AuthorizationRule::deny().addresses(AddressSpan::new(10, 9)));
+
+        assert_eq!(
+            RuleBasedAuthorizationHandler::new(rules).err(),
+            Some(RulesError::BadSpan(1, AddressSpan::new(10, 9)))
+        );
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn loads_rules_from_toml_and_json() {
+        let toml = r#"
+            matching = "most_specific"
+            log_level = "debug"
+
+            [[rules]]
+            name = "operators"
+            effect = "allow"
+            roles = ["operator"]
+            unit_ids = [1]
+
+            [[rules]]
+            effect = "deny"
+            function_codes = [6]
+            addresses = [{ first = 100, last = 199 }]
+        "#;
+        let json = r#"{
+            "matching": "most_specific",
+            "log_level": "debug",
+            "rules": [
+                { "name": "operators", "effect": "allow", "roles": ["operator"], "unit_ids": [1] },
+                { "effect": "deny", "function_codes": [6], "addresses": [{ "first": 100, "last": 199 }] }
+            ]
+        }"#;
+        let expected = AuthorizationRules::new(RuleMatching::MostSpecific)
+            .log_level(LevelFilter::DEBUG)
+            .rule(
+                AuthorizationRule::allow()
+                    .name("operators")
+                    .role("operator")
+                    .unit_id(UnitId::new(1)),
+            )
+            .rule(
+                AuthorizationRule::deny()
+                    .function_code(6)
+                    .addresses(AddressSpan::new(100, 199)),
+            );
+
+        assert_eq!(AuthorizationRules::from_toml(toml), Ok(expected.clone()));
+        assert_eq!(AuthorizationRules::from_json(json), Ok(expected));
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn rejects_invalid_documents() {
+        assert!(matches!(
+            AuthorizationRules::from_toml("log_level = \"loud\""),
+            Err(RulesError::Parse(_))
+        ));
+        assert!(matches!(
+            AuthorizationRules::from_json(r#"{ "rules": [{ "roles": ["operator"] }] }"#),
+            Err(RulesError::Parse(_))
+        ));
+        assert_eq!(
+            AuthorizationRules::from_toml(
+                "[[rules]]\neffect = \"deny\"\naddresses = [{ first = 2, last = 1 }]"
+            ),
+            Err(RulesError::BadSpan(0, AddressSpan::new(2, 1)))
+        );
+        assert_eq!(
+            AuthorizationRules::from_file("rules.yaml"),
+            Err(RulesError::UnsupportedFormat)
+        );
+    }
+}
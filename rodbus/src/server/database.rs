@@ -0,0 +1,859 @@
+use std::collections::BTreeMap;
+
+use crate::exception::ExceptionCode;
+use crate::server::{RequestContext, RequestHandler, WriteCoils, WriteRegisters};
+use crate::types::{AddressRange, Indexed};
+
+/// Points of a single type stored by a [`Database`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Table<T> {
+    points: Points<T>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Points<T> {
+    /// every address of a range, the first value is at address `start`
+    Dense { start: u16, values: Vec<T> },
+    /// any set of addresses
+    Sparse(BTreeMap<u16, T>),
+}
+
+/// range starting at `start` with one address per value, if it doesn't overflow u16
+fn range_of<T>(start: u16, values: &[T]) -> Option<AddressRange> {
+    let count = u16::try_from(values.len()).ok()?;
+    AddressRange::try_from(start, count).ok()
+}
+
+/// points of a sparse table within a range
+fn sparse_range<T>(
+    map: &BTreeMap<u16, T>,
+    range: AddressRange,
+) -> impl Iterator<Item = (&u16, &T)> {
+    let end = range.to_std_range().end;
+    map.range(range.start..)
+        .take_while(move |(address, _)| (**address as usize) < end)
+}
+
+impl<T: Copy> Table<T> {
+    /// Create an empty table to which points may be added at any address
+    pub fn sparse() -> Self {
+        Self {
+            points: Points::Sparse(BTreeMap::new()),
+        }
+    }
+
+    /// Create a table containing every address of `range`, each initialized to `value`
+    ///
+    /// Points can't be added to or removed from a dense table, but they are retrieved without
+    /// searching and ranges are copied at once.
+    pub fn dense(range: AddressRange, value: T) -> Self {
+        Self {
+            points: Points::Dense {
+                start: range.start,
+                values: vec![value; range.count as usize],
+            },
+        }
+    }
+
+    /// Number of points in the table
+    pub fn len(&self) -> usize {
+        match &self.points {
+            Points::Dense { values, .. } => values.len(),
+            Points::Sparse(map) => map.len(),
+        }
+    }
+
+    /// True if the table contains no points
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// True if the table contains a point at `address`
+    pub fn contains(&self, address: u16) -> bool {
+        self.get(address).is_some()
+    }
+
+    /// Value of the point at `address`
+    pub fn get(&self, address: u16) -> Option<T> {
+        match &self.points {
+            Points::Dense { start, values } => address
+                .checked_sub(*start)
+                .and_then(|offset| values.get(offset as usize))
+                .copied(),
+            Points::Sparse(map) => map.get(&address).copied(),
+        }
+    }
+
+    /// Iterate over the points of the table in address order
+    pub fn iter(&self) -> impl Iterator<Item = Indexed<T>> + '_ {
+        let (dense, sparse) = match &self.points {
+            Points::Dense { start, values } => (Some((*start, values)), None),
+            Points::Sparse(map) => (None, Some(map)),
+        };
+        let dense = dense.into_iter().flat_map(|(start, values)| {
+            values
+                .iter()
+                .zip(start..=u16::MAX)
+                .map(|(value, address)| Indexed::new(address, *value))
+        });
+        let sparse = sparse.into_iter().flat_map(|map| {
+            map.iter()
+                .map(|(address, value)| Indexed::new(*address, *value))
+        });
+        dense.chain(sparse)
+    }
+
+    /// Add a point to a sparse table
+    ///
+    /// Returns false if the point already exists or if the table is dense.
+    pub fn add(&mut self, address: u16, value: T) -> bool {
+        self.add_range(address, &[value])
+    }
+
+    /// Add a point for each value at consecutive addresses starting at `start`
+    ///
+    /// Returns false, and leaves the table unchanged, if any of the points already exists,
+    /// if the addresses overflow u16 or if the table is dense.
+    pub fn add_range(&mut self, start: u16, values: &[T]) -> bool {
+        if values.is_empty() {
+            return true;
+        }
+        let range = match range_of(start, values) {
+            Some(x) => x,
+            None => return false,
+        };
+        match &mut self.points {
+            Points::Dense { .. } => false,
+            Points::Sparse(map) => {
+                if sparse_range(map, range).next().is_some() {
+                    return false;
+                }
+                map.extend(range.iter().zip(values.iter().copied()));
+                true
+            }
+        }
+    }
+
+    /// Change the value of an existing point
+    ///
+    /// Returns false if the point doesn't exist.
+    pub fn update(&mut self, address: u16, value: T) -> bool {
+        self.update_range(address, &[value])
+    }
+
+    /// Change the values of existing points at consecutive addresses starting at `start`
+    ///
+    /// Returns false, and leaves the table unchanged, if any of the points doesn't exist.
+    pub fn update_range(&mut self, start: u16, values: &[T]) -> bool {
+        if values.is_empty() {
+            return true;
+        }
+        match range_of(start, values) {
+            Some(range) if self.contains_range(range) => {
+                for (address, value) in range.iter().zip(values.iter()) {
+                    self.set(address, *value);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Remove a point from a sparse table
+    ///
+    /// Returns false if the point doesn't exist or if the table is dense.
+    pub fn remove(&mut self, address: u16) -> bool {
+        match &mut self.points {
+            Points::Dense { .. } => false,
+            Points::Sparse(map) => map.remove(&address).is_some(),
+        }
+    }
+
+    /// true if every address of the range is present in the table
+    fn contains_range(&self, range: AddressRange) -> bool {
+        let addresses = range.to_std_range();
+        match &self.points {
+            Points::Dense { start, values } => {
+                let start = *start as usize;
+                addresses.start >= start && addresses.end <= start + values.len()
+            }
+            Points::Sparse(map) => sparse_range(map, range).count() == addresses.len(),
+        }
+    }
+
+    /// write the value of a point known to exist
+    fn set(&mut self, address: u16, value: T) {
+        match &mut self.points {
+            Points::Dense { start, values } => {
+                if let Some(x) = address
+                    .checked_sub(*start)
+                    .and_then(|offset| values.get_mut(offset as usize))
+                {
+                    *x = value;
+                }
+            }
+            Points::Sparse(map) => {
+                if let Some(x) = map.get_mut(&address) {
+                    *x = value;
+                }
+            }
+        }
+    }
+
+    /// copy the values of a range into `dest`
+    fn read(&self, range: AddressRange, dest: &mut [T]) -> Result<(), ExceptionCode> {
+        if !self.contains_range(range) {
+            return Err(ExceptionCode::IllegalDataAddress);
+        }
+        match &self.points {
+            Points::Dense { start, values } => {
+                let offset = (range.start - start) as usize;
+                for (value, x) in dest.iter_mut().zip(&values[offset..]) {
+                    *value = *x;
+                }
+            }
+            Points::Sparse(map) => {
+                for (value, (_, x)) in dest.iter_mut().zip(sparse_range(map, range)) {
+                    *value = *x;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Copy> Default for Table<T> {
+    fn default() -> Self {
+        Self::sparse()
+    }
+}
+
+/// Tables of a [`Database`] that clients are allowed to write
+///
+/// Discrete inputs and input registers are never written by clients.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WritePermissions {
+    /// Allow clients to write coils
+    pub coils: bool,
+    /// Allow clients to write holding registers
+    pub holding_registers: bool,
+}
+
+impl WritePermissions {
+    /// Create permissions for the writable tables
+    pub fn new(coils: bool, holding_registers: bool) -> Self {
+        Self {
+            coils,
+            holding_registers,
+        }
+    }
+}
+
+impl Default for WritePermissions {
+    /// Clients may write coils and holding registers
+    fn default() -> Self {
+        Self::new(true, true)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Change {
+    Coil(Indexed<bool>),
+    DiscreteInput(Indexed<bool>),
+    HoldingRegister(Indexed<u16>),
+    InputRegister(Indexed<u16>),
+}
+
+/// Updates of multiple points applied to a [`Database`] all at once
+///
+/// See [`Database::apply`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Transaction {
+    changes: Vec<Change>,
+}
+
+impl Transaction {
+    /// Create an empty transaction
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the value of a coil
+    pub fn update_coil(&mut self, address: u16, value: bool) -> &mut Self {
+        self.push(Change::Coil(Indexed::new(address, value)))
+    }
+
+    /// Update the value of a discrete input
+    pub fn update_discrete_input(&mut self, address: u16, value: bool) -> &mut Self {
+        self.push(Change::DiscreteInput(Indexed::new(address, value)))
+    }
+
+    /// Update the value of a holding register
+    pub fn update_holding_register(&mut self, address: u16, value: u16) -> &mut Self {
+        self.push(Change::HoldingRegister(Indexed::new(address, value)))
+    }
+
+    /// Update the value of an input register
+    pub fn update_input_register(&mut self, address: u16, value: u16) -> &mut Self {
+        self.push(Change::InputRegister(Indexed::new(address, value)))
+    }
+
+    /// Number of updates in the transaction
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// True if the transaction contains no updates
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn push(&mut self, change: Change) -> &mut Self {
+        self.changes.push(change);
+        self
+    }
+}
+
+/// Number of notifications a subscriber may fall behind before it misses the oldest ones
+const SUBSCRIPTION_CAPACITY: usize = 64;
+
+/// Whether the points of a [`Database`] were updated by the application or written by a client
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChangeSource {
+    /// Points updated by the application using [`Database::apply`]
+    Local,
+    /// Points written by a request of a client
+    Remote,
+}
+
+/// Points of a [`Database`] updated together by a transaction or a request
+///
+/// See [`Database::observe`] and [`Database::subscribe`]. Every written point is reported,
+/// including the points whose value didn't change.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DatabaseChanges {
+    /// Whether the points were updated by the application or written by a client
+    pub source: ChangeSource,
+    /// Unit id, session and address of the client that wrote the points, `None` for local updates
+    pub context: Option<RequestContext>,
+    /// Updated coils
+    pub coils: Vec<Indexed<bool>>,
+    /// Updated discrete inputs
+    pub discrete_inputs: Vec<Indexed<bool>>,
+    /// Updated holding registers
+    pub holding_registers: Vec<Indexed<u16>>,
+    /// Updated input registers
+    pub input_registers: Vec<Indexed<u16>>,
+}
+
+impl DatabaseChanges {
+    fn new(source: ChangeSource, context: Option<RequestContext>) -> Self {
+        Self {
+            source,
+            context,
+            coils: Vec::new(),
+            discrete_inputs: Vec::new(),
+            holding_registers: Vec::new(),
+            input_registers: Vec::new(),
+        }
+    }
+}
+
+type Callback = Box<dyn FnMut(&DatabaseChanges) + Send>;
+
+#[derive(Default)]
+struct Observers {
+    callbacks: Vec<Callback>,
+    subscribers: Option<tokio::sync::broadcast::Sender<DatabaseChanges>>,
+}
+
+impl Observers {
+    fn is_empty(&self) -> bool {
+        self.callbacks.is_empty() && self.subscribers.is_none()
+    }
+
+    fn notify(&mut self, changes: DatabaseChanges) {
+        for callback in self.callbacks.iter_mut() {
+            callback(&changes);
+        }
+        if let Some(subscribers) = &self.subscribers {
+            // fails if every receiver was dropped
+            let _ = subscribers.send(changes);
+        }
+    }
+}
+
+impl std::fmt::Debug for Observers {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Observers")
+            .field("callbacks", &self.callbacks.len())
+            .field(
+                "subscribers",
+                &self.subscribers.as_ref().map_or(0, |x| x.receiver_count()),
+            )
+            .finish()
+    }
+}
+
+/// In-memory database of the points of a server
+///
+/// The database implements [`RequestHandler`] so it can be added directly to a
+/// [`ServerHandlerMap`](crate::server::ServerHandlerMap). Requests for points that aren't in the
+/// tables are answered with [`ExceptionCode::IllegalDataAddress`] and writes to tables that
+/// clients aren't allowed to write with [`ExceptionCode::IllegalFunction`]. Writes of multiple
+/// points are only performed if every point exists.
+///
+/// The server locks the database while it processes a request, so values updated while holding
+/// the lock, e.g. using [`Database::apply`], are never observed partially by clients.
+///
+/// Clones and comparisons only consider the points and the write permissions, the observers of a
+/// database aren't copied to its clones.
+#[derive(Debug, Default)]
+pub struct Database {
+    coils: Table<bool>,
+    discrete_inputs: Table<bool>,
+    holding_registers: Table<u16>,
+    input_registers: Table<u16>,
+    permissions: WritePermissions,
+    observers: Observers,
+    /// client of the request being processed
+    context: Option<RequestContext>,
+}
+
+impl Clone for Database {
+    fn clone(&self) -> Self {
+        Self {
+            coils: self.coils.clone(),
+            discrete_inputs: self.discrete_inputs.clone(),
+            holding_registers: self.holding_registers.clone(),
+            input_registers: self.input_registers.clone(),
+            permissions: self.permissions,
+            observers: Observers::default(),
+            context: None,
+        }
+    }
+}
+
+impl PartialEq for Database {
+    fn eq(&self, other: &Self) -> bool {
+        self.coils == other.coils
+            && self.discrete_inputs == other.discrete_inputs
+            && self.holding_registers == other.holding_registers
+            && self.input_registers == other.input_registers
+            && self.permissions == other.permissions
+    }
+}
+
+impl Eq for Database {}
+
+impl Database {
+    /// Create a database with empty sparse tables to which clients may write
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Coils of the database
+    pub fn coils(&self) -> &Table<bool> {
+        &self.coils
+    }
+
+    /// Discrete inputs of the database
+    pub fn discrete_inputs(&self) -> &Table<bool> {
+        &self.discrete_inputs
+    }
+
+    /// Holding registers of the database
+    pub fn holding_registers(&self) -> &Table<u16> {
+        &self.holding_registers
+    }
+
+    /// Input registers of the database
+    pub fn input_registers(&self) -> &Table<u16> {
+        &self.input_registers
+    }
+
+    /// Coils of the database, e.g. to add points or replace the table with a dense table
+    pub fn coils_mut(&mut self) -> &mut Table<bool> {
+        &mut self.coils
+    }
+
+    /// Discrete inputs of the database
+    pub fn discrete_inputs_mut(&mut self) -> &mut Table<bool> {
+        &mut self.discrete_inputs
+    }
+
+    /// Holding registers of the database
+    pub fn holding_registers_mut(&mut self) -> &mut Table<u16> {
+        &mut self.holding_registers
+    }
+
+    /// Input registers of the database
+    pub fn input_registers_mut(&mut self) -> &mut Table<u16> {
+        &mut self.input_registers
+    }
+
+    /// Tables that clients are allowed to write
+    pub fn write_permissions(&self) -> WritePermissions {
+        self.permissions
+    }
+
+    /// Change the tables that clients are allowed to write
+    pub fn set_write_permissions(&mut self, permissions: WritePermissions) {
+        self.permissions = permissions;
+    }
+
+    /// Apply all of the updates of a transaction
+    ///
+    /// Returns false, and leaves the database unchanged, if any of the updated points doesn't exist.
+    pub fn apply(&mut self, transaction: &Transaction) -> bool {
+        let valid = transaction.changes.iter().all(|change| match change {
+            Change::Coil(x) => self.coils.contains(x.index),
+            Change::DiscreteInput(x) => self.discrete_inputs.contains(x.index),
+            Change::HoldingRegister(x) => self.holding_registers.contains(x.index),
+            Change::InputRegister(x) => self.input_registers.contains(x.index),
+        });
+        if !valid {
+            return false;
+        }
+
+        let mut changes = DatabaseChanges::new(ChangeSource::Local, None);
+        for change in transaction.changes.iter() {
+            match *change {
+                Change::Coil(x) => {
+                    self.coils.set(x.index, x.value);
+                    changes.coils.push(x);
+                }
+                Change::DiscreteInput(x) => {
+                    self.discrete_inputs.set(x.index, x.value);
+                    changes.discrete_inputs.push(x);
+                }
+                Change::HoldingRegister(x) => {
+                    self.holding_registers.set(x.index, x.value);
+                    changes.holding_registers.push(x);
+                }
+                Change::InputRegister(x) => {
+                    self.input_registers.set(x.index, x.value);
+                    changes.input_registers.push(x);
+                }
+            }
+        }
+        if !transaction.is_empty() {
+            self.observers.notify(changes);
+        }
+        true
+    }
+
+    /// Call `callback` with the points updated by each transaction and written by each request
+    ///
+    /// The callback is called while the database is locked by the server, so it shouldn't block.
+    /// Changes made to the tables directly, e.g. using [`Database::coils_mut`], aren't observed.
+    pub fn observe<F>(&mut self, callback: F)
+    where
+        F: FnMut(&DatabaseChanges) + Send + 'static,
+    {
+        self.observers.callbacks.push(Box::new(callback));
+    }
+
+    /// Receive the points updated by each transaction and written by each request asynchronously
+    ///
+    /// A receiver that falls behind by more than 64 notifications skips the oldest ones, see
+    /// [`tokio::sync::broadcast::error::RecvError::Lagged`]. Changes made to the tables directly
+    /// aren't observed.
+    pub fn subscribe(&mut self) -> tokio::sync::broadcast::Receiver<DatabaseChanges> {
+        self.observers
+            .subscribers
+            .get_or_insert_with(|| tokio::sync::broadcast::channel(SUBSCRIPTION_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// notify the observers of points written by the client of the current request
+    fn on_write<F>(&mut self, add: F)
+    where
+        F: FnOnce(&mut DatabaseChanges),
+    {
+        if !self.observers.is_empty() {
+            let mut changes = DatabaseChanges::new(ChangeSource::Remote, self.context.clone());
+            add(&mut changes);
+            self.observers.notify(changes);
+        }
+    }
+}
+
+/// convert the result of a table update into the result of a write request
+fn write_result(permitted: bool, written: bool) -> Result<(), ExceptionCode> {
+    if !permitted {
+        Err(ExceptionCode::IllegalFunction)
+    } else if !written {
+        Err(ExceptionCode::IllegalDataAddress)
+    } else {
+        Ok(())
+    }
+}
+
+impl RequestHandler for Database {
+    fn begin_request(&mut self, context: &RequestContext) {
+        self.context = Some(context.clone());
+    }
+
+    fn read_coil(&self, address: u16) -> Result<bool, ExceptionCode> {
+        self.coils
+            .get(address)
+            .ok_or(ExceptionCode::IllegalDataAddress)
+    }
+
+    fn read_discrete_input(&self, address: u16) -> Result<bool, ExceptionCode> {
+        self.discrete_inputs
+            .get(address)
+            .ok_or(ExceptionCode::IllegalDataAddress)
+    }
+
+    fn read_holding_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+        self.holding_registers
+            .get(address)
+            .ok_or(ExceptionCode::IllegalDataAddress)
+    }
+
+    fn read_input_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+        self.input_registers
+            .get(address)
+            .ok_or(ExceptionCode::IllegalDataAddress)
+    }
+
+    fn read_coils(&self, range: AddressRange, dest: &mut [bool]) -> Result<(), ExceptionCode> {
+        self.coils.read(range, dest)
+    }
+
+    fn read_discrete_inputs(
+        &self,
+        range: AddressRange,
+        dest: &mut [bool],
+    ) -> Result<(), ExceptionCode> {
+        self.discrete_inputs.read(range, dest)
+    }
+
+    fn read_holding_registers(
+        &self,
+        range: AddressRange,
+        dest: &mut [u16],
+    ) -> Result<(), ExceptionCode> {
+        self.holding_registers.read(range, dest)
+    }
+
+    fn read_input_registers(
+        &self,
+        range: AddressRange,
+        dest: &mut [u16],
+    ) -> Result<(), ExceptionCode> {
+        self.input_registers.read(range, dest)
+    }
+
+    fn write_single_coil(&mut self, value: Indexed<bool>) -> Result<(), ExceptionCode> {
+        let permitted = self.permissions.coils;
+        let written = permitted && self.coils.update(value.index, value.value);
+        if written {
+            self.on_write(|changes| changes.coils.push(value));
+        }
+        write_result(permitted, written)
+    }
+
+    fn write_single_register(&mut self, value: Indexed<u16>) -> Result<(), ExceptionCode> {
+        let permitted = self.permissions.holding_registers;
+        let written = permitted && self.holding_registers.update(value.index, value.value);
+        if written {
+            self.on_write(|changes| changes.holding_registers.push(value));
+        }
+        write_result(permitted, written)
+    }
+
+    fn write_multiple_coils(&mut self, values: WriteCoils) -> Result<(), ExceptionCode> {
+        let permitted = self.permissions.coils;
+        let written = permitted && self.coils.contains_range(values.range);
+        if written {
+            let values: Vec<Indexed<bool>> = values.iterator.collect();
+            for x in values.iter() {
+                self.coils.set(x.index, x.value);
+            }
+            self.on_write(|changes| changes.coils = values);
+        }
+        write_result(permitted, written)
+    }
+
+    fn write_multiple_registers(&mut self, values: WriteRegisters) -> Result<(), ExceptionCode> {
+        let permitted = self.permissions.holding_registers;
+        let written = permitted && self.holding_registers.contains_range(values.range);
+        if written {
+            let values: Vec<Indexed<u16>> = values.iterator.collect();
+            for x in values.iter() {
+                self.holding_registers.set(x.index, x.value);
+            }
+            self.on_write(|changes| changes.holding_registers = values);
+        }
+        write_result(permitted, written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{range, RegisterIterator};
+    use scursor::ReadCursor;
+
+    fn write_registers(db: &mut Database, start: u16, values: &[u16]) -> Result<(), ExceptionCode> {
+        let range = range(start, values.len() as u16);
+        let bytes: Vec<u8> = values.iter().flat_map(|x| x.to_be_bytes()).collect();
+        let mut cursor = ReadCursor::new(&bytes);
+        let iterator = RegisterIterator::parse_all(range, &mut cursor).unwrap();
+        db.write_multiple_registers(WriteRegisters::new(range, iterator))
+    }
+
+    #[test]
+    fn sparse_tables_add_and_update_ranges_atomically() {
+        let mut table = Table::sparse();
+        assert!(table.add_range(10, &[1u16, 2, 3]));
+        assert!(table.add(20, 4));
+        assert!(!table.add_range(8, &[5, 6, 7]));
+        assert!(!table.add_range(u16::MAX, &[5, 6]));
+        assert_eq!(table.len(), 4);
+
+        assert!(table.update_range(11, &[7, 8]));
+        assert!(!table.update_range(12, &[9, 9]));
+        assert!(table.remove(20));
+        assert!(!table.remove(20));
+
+        assert_eq!(
+            table.iter().collect::<Vec<_>>(),
+            vec![
+                Indexed::new(10, 1),
+                Indexed::new(11, 7),
+                Indexed::new(12, 8)
+            ]
+        );
+    }
+
+    #[test]
+    fn dense_tables_contain_every_address_of_their_range() {
+        let mut table = Table::dense(range(100, 3), false);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get(99), None);
+        assert_eq!(table.get(102), Some(false));
+        assert_eq!(table.get(103), None);
+
+        assert!(!table.add(103, true));
+        assert!(!table.remove(100));
+        assert!(!table.update_range(101, &[true, true, true]));
+        assert!(table.update_range(101, &[true, true]));
+
+        let mut values = [false; 2];
+        assert_eq!(table.read(range(101, 2), &mut values), Ok(()));
+        assert_eq!(values, [true, true]);
+        assert_eq!(
+            table.read(range(102, 2), &mut values),
+            Err(ExceptionCode::IllegalDataAddress)
+        );
+    }
+
+    #[test]
+    fn transactions_are_applied_all_at_once() {
+        let mut db = Database::new();
+        db.coils_mut().add(0, false);
+        db.input_registers_mut().add_range(0, &[0, 0]);
+
+        let mut transaction = Transaction::new();
+        transaction
+            .update_coil(0, true)
+            .update_input_register(1, 42)
+            .update_holding_register(0, 1);
+        assert!(!db.apply(&transaction));
+        assert_eq!(db.coils().get(0), Some(false));
+
+        db.holding_registers_mut().add(0, 0);
+        assert!(db.apply(&transaction));
+        assert_eq!(db.coils().get(0), Some(true));
+        assert_eq!(db.input_registers().get(1), Some(42));
+        assert_eq!(db.holding_registers().get(0), Some(1));
+    }
+
+    #[test]
+    fn handles_requests_using_the_write_permissions() {
+        let mut db = Database::new();
+        *db.holding_registers_mut() = Table::dense(range(0, 4), 0);
+        db.coils_mut().add(3, false);
+
+        assert_eq!(write_registers(&mut db, 1, &[1, 2]), Ok(()));
+        assert_eq!(
+            write_registers(&mut db, 3, &[3, 4]),
+            Err(ExceptionCode::IllegalDataAddress)
+        );
+        let mut values = [0; 4];
+        assert_eq!(db.read_holding_registers(range(0, 4), &mut values), Ok(()));
+        assert_eq!(values, [0, 1, 2, 0]);
+
+        db.set_write_permissions(WritePermissions::new(true, false));
+        assert_eq!(
+            write_registers(&mut db, 1, &[3, 4]),
+            Err(ExceptionCode::IllegalFunction)
+        );
+        assert_eq!(
+            db.write_single_register(Indexed::new(0, 1)),
+            Err(ExceptionCode::IllegalFunction)
+        );
+        assert_eq!(db.write_single_coil(Indexed::new(3, true)), Ok(()));
+        assert_eq!(
+            db.write_single_coil(Indexed::new(4, true)),
+            Err(ExceptionCode::IllegalDataAddress)
+        );
+        assert_eq!(db.read_coil(3), Ok(true));
+    }
+
+    #[test]
+    fn observers_are_notified_of_local_and_remote_changes() {
+        let mut db = Database::new();
+        db.holding_registers_mut().add_range(0, &[0, 0]);
+        let observed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        {
+            let observed = observed.clone();
+            db.observe(move |changes| observed.lock().unwrap().push(changes.clone()));
+        }
+        let mut subscription = db.subscribe();
+
+        let mut transaction = Transaction::new();
+        transaction.update_holding_register(0, 1);
+        assert!(db.apply(&transaction));
+
+        let context = RequestContext::new(
+            crate::UnitId::new(1),
+            None,
+            None,
+            crate::server::TransportKind::Tcp,
+            None,
+        );
+        db.begin_request(&context);
+        assert_eq!(write_registers(&mut db, 0, &[2, 3]), Ok(()));
+        // failed writes aren't reported
+        assert_eq!(
+            write_registers(&mut db, 1, &[4, 5]),
+            Err(ExceptionCode::IllegalDataAddress)
+        );
+
+        let mut local = DatabaseChanges::new(ChangeSource::Local, None);
+        local.holding_registers = vec![Indexed::new(0, 1)];
+        let mut remote = DatabaseChanges::new(ChangeSource::Remote, Some(context));
+        remote.holding_registers = vec![Indexed::new(0, 2), Indexed::new(1, 3)];
+
+        assert_eq!(
+            *observed.lock().unwrap(),
+            vec![local.clone(), remote.clone()]
+        );
+        assert_eq!(subscription.try_recv(), Ok(local));
+        assert_eq!(subscription.try_recv(), Ok(remote));
+        assert!(subscription.try_recv().is_err());
+
+        // clones are equal to the original but aren't observed
+        let mut copy = db.clone();
+        assert_eq!(copy, db);
+        assert!(copy.apply(&transaction));
+        assert_eq!(observed.lock().unwrap().len(), 2);
+        assert!(subscription.try_recv().is_err());
+        assert_ne!(copy, db);
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use crate::constants::device_id::{FIRST_EXTENDED, VENDOR_URL};
+use crate::error::InvalidRequest;
+
+/// Device identification objects keyed by object id
+///
+/// Returned from [`crate::server::RequestHandler::device_identification`] to answer
+/// Read Device Identification requests.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceIdentificationStore {
+    objects: BTreeMap<u8, Vec<u8>>,
+}
+
+impl DeviceIdentificationStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self {
+            objects: BTreeMap::new(),
+        }
+    }
+
+    /// Create a store containing the three mandatory basic objects
+    pub fn basic(vendor_name: &str, product_code: &str, revision: &str) -> Self {
+        let mut store = Self::new();
+        for (id, value) in [
+            (crate::constants::device_id::VENDOR_NAME, vendor_name),
+            (crate::constants::device_id::PRODUCT_CODE, product_code),
+            (crate::constants::device_id::MAJOR_MINOR_REVISION, revision),
+        ] {
+            // truncate rather than fail so that this constructor is infallible
+            let length = value
+                .len()
+                .min(crate::constants::limits::MAX_DEVICE_ID_OBJECT_LENGTH);
+            store
+                .objects
+                .insert(id, value.as_bytes()[..length].to_vec());
+        }
+        store
+    }
+
+    /// Add or replace an object, returning the previous value if present
+    ///
+    /// Fails if the value is too long to fit in a response
+    pub fn set(&mut self, id: u8, value: Vec<u8>) -> Result<Option<Vec<u8>>, InvalidRequest> {
+        let length = u16::try_from(value.len())
+            .map_err(|_| InvalidRequest::CountTooBigForU16(value.len()))?;
+        let max = crate::constants::limits::MAX_DEVICE_ID_OBJECT_LENGTH as u16;
+        if length > max {
+            return Err(InvalidRequest::CountTooBigForType(length, max));
+        }
+        Ok(self.objects.insert(id, value))
+    }
+
+    /// Retrieve the value of an object
+    pub fn get(&self, id: u8) -> Option<&[u8]> {
+        self.objects.get(&id).map(|x| x.as_slice())
+    }
+
+    /// Remove an object, returning its value if present
+    pub fn remove(&mut self, id: u8) -> Option<Vec<u8>> {
+        self.objects.remove(&id)
+    }
+
+    /// Conformity level derived from the objects present, individual access is always supported
+    pub(crate) fn conformity_level(&self) -> u8 {
+        let category = match self.objects.keys().next_back() {
+            Some(id) if *id >= FIRST_EXTENDED => 0x03,
+            Some(id) if *id >= VENDOR_URL => 0x02,
+            _ => 0x01,
+        };
+        0x80 | category
+    }
+
+    pub(crate) fn range(&self, start: u8, end: u8) -> impl Iterator<Item = (u8, &[u8])> + '_ {
+        self.objects
+            .range(start..=end)
+            .map(|(id, value)| (*id, value.as_slice()))
+    }
+
+    pub(crate) fn contains(&self, id: u8) -> bool {
+        self.objects.contains_key(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conformity_level_depends_on_objects() {
+        let mut store = DeviceIdentificationStore::basic("vendor", "code", "1.0");
+        assert_eq!(store.conformity_level(), 0x81);
+        store.set(0x05, b"model".to_vec()).unwrap();
+        assert_eq!(store.conformity_level(), 0x82);
+        store.set(0x80, b"private".to_vec()).unwrap();
+        assert_eq!(store.conformity_level(), 0x83);
+    }
+
+    #[test]
+    fn rejects_objects_that_are_too_long() {
+        let mut store = DeviceIdentificationStore::new();
+        assert_eq!(
+            store.set(0x00, vec![0; 245]),
+            Err(InvalidRequest::CountTooBigForType(245, 244))
+        );
+    }
+}
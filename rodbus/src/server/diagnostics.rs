@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use crate::constants::comm_event;
+use crate::diagnostics::{
+    sub_function, CommEventCounter, CommEventLog, DiagnosticCounter, DiagnosticsRequest,
+    CLEAR_EVENT_LOG,
+};
+use crate::exception::ExceptionCode;
+
+/// Counters and event log of a serial line, used to answer diagnostic requests
+#[derive(Debug, Default)]
+pub(crate) struct DiagnosticCounters {
+    bus_message: u16,
+    bus_communication_error: u16,
+    bus_exception_error: u16,
+    server_message: u16,
+    server_no_response: u16,
+    comm_event: u16,
+    listen_only: bool,
+    /// most recent event first
+    events: VecDeque<u8>,
+}
+
+impl DiagnosticCounters {
+    pub(crate) fn is_listen_only(&self) -> bool {
+        self.listen_only
+    }
+
+    pub(crate) fn get(&self, counter: DiagnosticCounter) -> u16 {
+        match counter {
+            DiagnosticCounter::BusMessage => self.bus_message,
+            DiagnosticCounter::BusCommunicationError => self.bus_communication_error,
+            DiagnosticCounter::BusExceptionError => self.bus_exception_error,
+            DiagnosticCounter::ServerMessage => self.server_message,
+            DiagnosticCounter::ServerNoResponse => self.server_no_response,
+        }
+    }
+
+    pub(crate) fn comm_event_counter(&self) -> CommEventCounter {
+        CommEventCounter::new(false, self.comm_event)
+    }
+
+    pub(crate) fn comm_event_log(&self) -> CommEventLog {
+        CommEventLog {
+            busy: false,
+            event_count: self.comm_event,
+            message_count: self.bus_message,
+            events: self.events.iter().copied().collect(),
+        }
+    }
+
+    /// a frame was received on the bus, `addressed` is true if it targets this device or is a broadcast
+    pub(crate) fn on_message(&mut self, addressed: bool, broadcast: bool) {
+        self.bus_message = self.bus_message.wrapping_add(1);
+        if addressed {
+            self.server_message = self.server_message.wrapping_add(1);
+            let mut event = comm_event::RECEIVE;
+            if self.listen_only {
+                event |= comm_event::RECEIVE_LISTEN_ONLY_MODE;
+            }
+            if broadcast {
+                event |= comm_event::RECEIVE_BROADCAST;
+            }
+            self.push_event(event);
+        }
+    }
+
+    /// a frame was discarded because of a CRC failure
+    pub(crate) fn on_communication_error(&mut self) {
+        self.bus_communication_error = self.bus_communication_error.wrapping_add(1);
+        self.push_event(comm_event::RECEIVE | comm_event::RECEIVE_COMMUNICATION_ERROR);
+    }
+
+    /// a normal response was returned
+    pub(crate) fn on_response(&mut self, counts_as_event: bool) {
+        if counts_as_event {
+            self.comm_event = self.comm_event.wrapping_add(1);
+        }
+        self.push_event(comm_event::SEND);
+    }
+
+    /// an exception response was returned
+    pub(crate) fn on_exception(&mut self, ex: ExceptionCode) {
+        self.bus_exception_error = self.bus_exception_error.wrapping_add(1);
+        let flag = match ex {
+            ExceptionCode::IllegalFunction
+            | ExceptionCode::IllegalDataAddress
+            | ExceptionCode::IllegalDataValue => comm_event::SEND_READ_EXCEPTION,
+            ExceptionCode::ServerDeviceFailure => comm_event::SEND_ABORT_EXCEPTION,
+            ExceptionCode::Acknowledge | ExceptionCode::ServerDeviceBusy => {
+                comm_event::SEND_BUSY_EXCEPTION
+            }
+            ExceptionCode::Unknown(0x07) => comm_event::SEND_NAK_EXCEPTION,
+            _ => 0,
+        };
+        self.push_event(comm_event::SEND | flag);
+    }
+
+    /// a request addressed to this device was processed without returning a response
+    pub(crate) fn on_no_response(&mut self) {
+        self.server_no_response = self.server_no_response.wrapping_add(1);
+        if self.listen_only {
+            self.push_event(comm_event::SEND | comm_event::SEND_LISTEN_ONLY_MODE);
+        }
+    }
+
+    /// clear the counters and leave listen only mode, optionally clearing the event log
+    pub(crate) fn restart(&mut self, clear_log: bool) {
+        self.clear_counters();
+        self.listen_only = false;
+        if clear_log {
+            self.events.clear();
+        }
+        self.push_event(comm_event::COMMUNICATION_RESTART);
+    }
+
+    pub(crate) fn force_listen_only(&mut self) {
+        self.listen_only = true;
+        self.push_event(comm_event::ENTERED_LISTEN_ONLY_MODE);
+    }
+
+    /// answer a diagnostics request, force listen only mode must be handled by the caller
+    /// because no response is returned
+    pub(crate) fn diagnostics(
+        &mut self,
+        request: DiagnosticsRequest,
+    ) -> Result<DiagnosticsRequest, ExceptionCode> {
+        if let Some(counter) = DiagnosticCounter::get(request.sub_function) {
+            return Ok(DiagnosticsRequest::new(
+                request.sub_function,
+                self.get(counter),
+            ));
+        }
+
+        match request.sub_function {
+            sub_function::RETURN_QUERY_DATA => Ok(request),
+            sub_function::RESTART_COMMUNICATIONS => match request.data {
+                0 | CLEAR_EVENT_LOG => {
+                    self.restart(request.data == CLEAR_EVENT_LOG);
+                    Ok(request)
+                }
+                _ => Err(ExceptionCode::IllegalDataValue),
+            },
+            sub_function::CLEAR_COUNTERS => {
+                self.clear_counters();
+                Ok(request)
+            }
+            _ => Err(ExceptionCode::IllegalFunction),
+        }
+    }
+
+    fn clear_counters(&mut self) {
+        self.bus_message = 0;
+        self.bus_communication_error = 0;
+        self.bus_exception_error = 0;
+        self.server_message = 0;
+        self.server_no_response = 0;
+        self.comm_event = 0;
+    }
+
+    fn push_event(&mut self, event: u8) {
+        self.events.push_front(event);
+        self.events
+            .truncate(crate::constants::limits::MAX_COMM_EVENT_LOG_COUNT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_messages_and_errors() {
+        let mut counters = DiagnosticCounters::default();
+        counters.on_message(true, false);
+        counters.on_message(false, false);
+        counters.on_communication_error();
+        counters.on_exception(ExceptionCode::IllegalDataAddress);
+
+        assert_eq!(counters.get(DiagnosticCounter::BusMessage), 2);
+        assert_eq!(counters.get(DiagnosticCounter::ServerMessage), 1);
+        assert_eq!(counters.get(DiagnosticCounter::BusCommunicationError), 1);
+        assert_eq!(counters.get(DiagnosticCounter::BusExceptionError), 1);
+        assert_eq!(counters.comm_event_log().events, vec![0x41, 0x82, 0x80]);
+    }
+
+    #[test]
+    fn restart_clears_counters_and_listen_only_mode() {
+        let mut counters = DiagnosticCounters::default();
+        counters.on_message(true, false);
+        counters.force_listen_only();
+        assert!(counters.is_listen_only());
+
+        let request = DiagnosticsRequest::new(sub_function::RESTART_COMMUNICATIONS, 0);
+        assert_eq!(counters.diagnostics(request), Ok(request));
+        assert!(!counters.is_listen_only());
+        assert_eq!(counters.get(DiagnosticCounter::BusMessage), 0);
+        assert_eq!(counters.comm_event_log().events, vec![0x00, 0x04, 0x80]);
+    }
+
+    #[test]
+    fn restart_can_clear_the_event_log() {
+        let mut counters = DiagnosticCounters::default();
+        counters.on_message(true, false);
+        let request = DiagnosticsRequest::new(sub_function::RESTART_COMMUNICATIONS, 0xFF00);
+        assert_eq!(counters.diagnostics(request), Ok(request));
+        assert_eq!(counters.comm_event_log().events, vec![0x00]);
+    }
+
+    #[test]
+    fn event_log_is_limited() {
+        let mut counters = DiagnosticCounters::default();
+        for _ in 0..100 {
+            counters.on_response(true);
+        }
+        assert_eq!(counters.comm_event_counter().event_count, 100);
+        assert_eq!(counters.comm_event_log().events.len(), 64);
+    }
+
+    #[test]
+    fn unknown_sub_function_is_illegal_function() {
+        let mut counters = DiagnosticCounters::default();
+        assert_eq!(
+            counters.diagnostics(DiagnosticsRequest::new(0x15, 0)),
+            Err(ExceptionCode::IllegalFunction)
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use std::future::Future;
+
+use crate::client::{Channel, ReadWriteMultiple, RequestParam, WriteMultiple};
+use crate::exception::ExceptionCode;
+use crate::maybe_async::MaybeAsync;
+use crate::server::{AsyncRequestHandler, RequestContext};
+use crate::types::*;
+use crate::RequestError;
+
+/// Handler that forwards the requests it receives to a device reachable through a client [`Channel`]
+///
+/// Add a handler to an [`AsyncServerHandlerMap`](crate::server::AsyncServerHandlerMap) for each
+/// unit id answered by the gateway and spawn the map using one of the asynchronous server functions,
+/// e.g. [`spawn_async_tcp_server_task`](crate::server::spawn_async_tcp_server_task). Requests are
+/// forwarded to the unit id of the [`RequestParam`], so the unit ids of the server may be remapped
+/// to different unit ids on the downstream channel.
+///
+/// Each channel queues the requests of every handler that shares it, e.g. all of the devices on
+/// an RTU line, and sends them according to its own settings. Errors on the downstream channel
+/// are returned to the client as exceptions:
+///
+/// * exceptions returned by the device are forwarded as is
+/// * [`ExceptionCode::GatewayTargetDeviceFailedToRespond`] if the device didn't answer within
+///   the response timeout or answered with an invalid response
+/// * [`ExceptionCode::GatewayPathUnavailable`] if the channel isn't connected or was shutdown
+///
+/// The diagnostics, device identification and file record requests are not forwarded.
+pub struct GatewayHandler {
+    channel: Channel,
+    param: RequestParam,
+}
+
+impl GatewayHandler {
+    /// Create a handler that forwards requests to the device identified by `param` on the channel
+    pub fn new(channel: Channel, param: RequestParam) -> Self {
+        Self { channel, param }
+    }
+
+    fn forward<T, F, R>(&self, request: F) -> MaybeAsync<Result<T, ExceptionCode>>
+    where
+        T: Send + 'static,
+        F: FnOnce(Channel, RequestParam) -> R,
+        R: Future<Output = Result<T, RequestError>> + Send + 'static,
+    {
+        let response = request(self.channel.clone(), self.param);
+        MaybeAsync::asynchronous(async move { response.await.map_err(to_exception) })
+    }
+}
+
+/// convert the error of a forwarded request into the exception returned to the client
+fn to_exception(err: RequestError) -> ExceptionCode {
+    match err {
+        RequestError::Exception(ex) => ex,
+        RequestError::ResponseTimeout
+        | RequestError::BadFrame(_)
+        | RequestError::BadResponse(_) => ExceptionCode::GatewayTargetDeviceFailedToRespond,
+        // the server validates requests before they are forwarded
+        RequestError::BadRequest(_) => ExceptionCode::IllegalDataValue,
+        RequestError::Io(_)
+        | RequestError::Internal(_)
+        | RequestError::NoConnection
+        | RequestError::Shutdown => ExceptionCode::GatewayPathUnavailable,
+    }
+}
+
+/// values of a response without their addresses
+fn into_values<T>(values: Vec<Indexed<T>>) -> Vec<T> {
+    values.into_iter().map(|x| x.value).collect()
+}
+
+impl AsyncRequestHandler for GatewayHandler {
+    fn read_coils(
+        &self,
+        _context: &RequestContext,
+        range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<bool>, ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            channel.read_coils(param, range).await.map(into_values)
+        })
+    }
+
+    fn read_discrete_inputs(
+        &self,
+        _context: &RequestContext,
+        range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<bool>, ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            channel
+                .read_discrete_inputs(param, range)
+                .await
+                .map(into_values)
+        })
+    }
+
+    fn read_holding_registers(
+        &self,
+        _context: &RequestContext,
+        range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            channel
+                .read_holding_registers(param, range)
+                .await
+                .map(into_values)
+        })
+    }
+
+    fn read_input_registers(
+        &self,
+        _context: &RequestContext,
+        range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            channel
+                .read_input_registers(param, range)
+                .await
+                .map(into_values)
+        })
+    }
+
+    fn read_fifo_queue(
+        &self,
+        _context: &RequestContext,
+        address: u16,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        self.forward(
+            |mut channel, param| async move { channel.read_fifo_queue(param, address).await },
+        )
+    }
+
+    fn read_exception_status(
+        &self,
+        _context: &RequestContext,
+    ) -> MaybeAsync<Result<u8, ExceptionCode>> {
+        self.forward(|mut channel, param| async move { channel.read_exception_status(param).await })
+    }
+
+    fn report_server_id(
+        &self,
+        _context: &RequestContext,
+    ) -> MaybeAsync<Result<Vec<u8>, ExceptionCode>> {
+        self.forward(|mut channel, param| async move { channel.report_server_id(param).await })
+    }
+
+    fn write_single_coil(
+        &self,
+        _context: &RequestContext,
+        value: Indexed<bool>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            channel.write_single_coil(param, value).await.map(|_| ())
+        })
+    }
+
+    fn write_single_register(
+        &self,
+        _context: &RequestContext,
+        value: Indexed<u16>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            channel
+                .write_single_register(param, value)
+                .await
+                .map(|_| ())
+        })
+    }
+
+    fn write_multiple_coils(
+        &self,
+        _context: &RequestContext,
+        range: AddressRange,
+        values: Vec<bool>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            let request = WriteMultiple::from(range.start, values)?;
+            channel
+                .write_multiple_coils(param, request)
+                .await
+                .map(|_| ())
+        })
+    }
+
+    fn write_multiple_registers(
+        &self,
+        _context: &RequestContext,
+        range: AddressRange,
+        values: Vec<u16>,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            let request = WriteMultiple::from(range.start, values)?;
+            channel
+                .write_multiple_registers(param, request)
+                .await
+                .map(|_| ())
+        })
+    }
+
+    fn mask_write_register(
+        &self,
+        _context: &RequestContext,
+        request: MaskWriteRegister,
+    ) -> MaybeAsync<Result<(), ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            channel
+                .mask_write_register(param, request)
+                .await
+                .map(|_| ())
+        })
+    }
+
+    fn read_write_multiple_registers(
+        &self,
+        _context: &RequestContext,
+        read_range: AddressRange,
+        write_range: AddressRange,
+        values: Vec<u16>,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            let request = ReadWriteMultiple::new(read_range, write_range.start, values)?;
+            channel
+                .read_write_multiple_registers(param, request)
+                .await
+                .map(into_values)
+        })
+    }
+
+    fn custom_function(
+        &self,
+        _context: &RequestContext,
+        function_code: u8,
+        data: Vec<u8>,
+    ) -> MaybeAsync<Result<Vec<u8>, ExceptionCode>> {
+        self.forward(|mut channel, param| async move {
+            channel
+                .send_custom_function(param, function_code, data)
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{AduParseError, InvalidRequest};
+
+    #[test]
+    fn converts_errors_into_gateway_exceptions() {
+        assert_eq!(
+            to_exception(RequestError::Exception(ExceptionCode::IllegalDataAddress)),
+            ExceptionCode::IllegalDataAddress
+        );
+        assert_eq!(
+            to_exception(RequestError::ResponseTimeout),
+            ExceptionCode::GatewayTargetDeviceFailedToRespond
+        );
+        assert_eq!(
+            to_exception(RequestError::BadResponse(AduParseError::InsufficientBytes)),
+            ExceptionCode::GatewayTargetDeviceFailedToRespond
+        );
+        assert_eq!(
+            to_exception(RequestError::NoConnection),
+            ExceptionCode::GatewayPathUnavailable
+        );
+        assert_eq!(
+            to_exception(RequestError::Shutdown),
+            ExceptionCode::GatewayPathUnavailable
+        );
+        assert_eq!(
+            to_exception(RequestError::BadRequest(InvalidRequest::CountTooBigForU16(
+                0x10000
+            ))),
+            ExceptionCode::IllegalDataValue
+        );
+    }
+}
@@ -1,9 +1,17 @@
 use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 
+use crate::common::frame::{FrameHeader, FrameWriter};
+use crate::device_id::ReadDeviceIdCode;
+use crate::diagnostics::DiagnosticsRequest;
 use crate::exception::ExceptionCode;
-use crate::server::{WriteCoils, WriteRegisters};
+use crate::file_record::FileRecord;
+use crate::server::diagnostics::DiagnosticCounters;
+use crate::server::request::{BroadcastRequest, Request};
+use crate::server::task::{Reply, SessionHandlers, UnitHandler};
+use crate::server::{DeviceIdentificationStore, RequestContext, WriteCoils, WriteRegisters};
 use crate::types::*;
+use crate::{DecodeLevel, RequestError};
 
 /// Trait implemented by the user to process requests received from the client
 ///
@@ -23,6 +31,12 @@ pub trait RequestHandler: Send + 'static {
         Arc::new(Mutex::new(Box::new(self)))
     }
 
+    /// Called before the handler processes a request with the details of the client that sent it
+    ///
+    /// The handler remains locked until the request is processed. The default implementation
+    /// does nothing.
+    fn begin_request(&mut self, _context: &RequestContext) {}
+
     /// Read single coil or return an ExceptionCode
     fn read_coil(&self, _address: u16) -> Result<bool, ExceptionCode> {
         Err(ExceptionCode::IllegalFunction)
@@ -43,6 +57,100 @@ pub trait RequestHandler: Send + 'static {
         Err(ExceptionCode::IllegalFunction)
     }
 
+    /// Read a range of coils into `dest` which contains one element per address of the range
+    ///
+    /// The default implementation calls [`RequestHandler::read_coil`] for each address. Override
+    /// it to look up the whole range at once or to return a consistent snapshot of the values.
+    fn read_coils(&self, range: AddressRange, dest: &mut [bool]) -> Result<(), ExceptionCode> {
+        for (value, address) in dest.iter_mut().zip(range.iter()) {
+            *value = self.read_coil(address)?;
+        }
+        Ok(())
+    }
+
+    /// Read a range of discrete inputs into `dest` which contains one element per address of the range
+    ///
+    /// The default implementation calls [`RequestHandler::read_discrete_input`] for each address.
+    fn read_discrete_inputs(
+        &self,
+        range: AddressRange,
+        dest: &mut [bool],
+    ) -> Result<(), ExceptionCode> {
+        for (value, address) in dest.iter_mut().zip(range.iter()) {
+            *value = self.read_discrete_input(address)?;
+        }
+        Ok(())
+    }
+
+    /// Read a range of holding registers into `dest` which contains one element per address of the range
+    ///
+    /// The default implementation calls [`RequestHandler::read_holding_register`] for each address.
+    fn read_holding_registers(
+        &self,
+        range: AddressRange,
+        dest: &mut [u16],
+    ) -> Result<(), ExceptionCode> {
+        for (value, address) in dest.iter_mut().zip(range.iter()) {
+            *value = self.read_holding_register(address)?;
+        }
+        Ok(())
+    }
+
+    /// Read a range of input registers into `dest` which contains one element per address of the range
+    ///
+    /// The default implementation calls [`RequestHandler::read_input_register`] for each address.
+    fn read_input_registers(
+        &self,
+        range: AddressRange,
+        dest: &mut [u16],
+    ) -> Result<(), ExceptionCode> {
+        for (value, address) in dest.iter_mut().zip(range.iter()) {
+            *value = self.read_input_register(address)?;
+        }
+        Ok(())
+    }
+
+    /// Read the contents of the FIFO queue at the specified pointer address
+    ///
+    /// At most 31 values may be returned, otherwise [`ExceptionCode::IllegalDataValue`]
+    /// is returned to the client.
+    fn read_fifo_queue(&self, _address: u16) -> Result<Vec<u16>, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Device identification objects returned in response to Read Device Identification requests
+    ///
+    /// The default implementation returns `None` which causes [`ExceptionCode::IllegalFunction`]
+    /// to be returned to the client.
+    fn device_identification(&self) -> Option<&DeviceIdentificationStore> {
+        None
+    }
+
+    /// Read the eight exception status outputs of the device
+    fn read_exception_status(&self) -> Result<u8, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Device specific identification returned in response to Report Server ID requests
+    ///
+    /// The content is typically the server id, the run indicator status (0x00 = OFF, 0xFF = ON)
+    /// and any additional data. At most 251 bytes may be returned, otherwise
+    /// [`ExceptionCode::IllegalDataValue`] is returned to the client.
+    fn report_server_id(&self) -> Result<Vec<u8>, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Read a single record of a file or return an ExceptionCode
+    ///
+    /// Called once for every record referenced by the sub-requests of a Read File Record request.
+    fn read_file_record(
+        &self,
+        _file_number: u16,
+        _record_number: u16,
+    ) -> Result<u16, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
     /// Write a single coil value
     fn write_single_coil(&mut self, _value: Indexed<bool>) -> Result<(), ExceptionCode> {
         Err(ExceptionCode::IllegalFunction)
@@ -62,6 +170,56 @@ pub trait RequestHandler: Send + 'static {
     fn write_multiple_registers(&mut self, _values: WriteRegisters) -> Result<(), ExceptionCode> {
         Err(ExceptionCode::IllegalFunction)
     }
+
+    /// Modify a single register using an AND mask and an OR mask
+    ///
+    /// The default implementation reads the current value using [`RequestHandler::read_holding_register`],
+    /// applies the masks and writes the result using [`RequestHandler::write_single_register`].
+    fn mask_write_register(&mut self, request: MaskWriteRegister) -> Result<(), ExceptionCode> {
+        let current = self.read_holding_register(request.address)?;
+        self.write_single_register(Indexed::new(request.address, request.apply(current)))
+    }
+
+    /// Perform the write portion of a read/write multiple registers request
+    ///
+    /// The registers in `read_range` are subsequently read using [`RequestHandler::read_holding_registers`]
+    /// while the handler is still locked. The default implementation delegates the write to
+    /// [`RequestHandler::write_multiple_registers`].
+    fn read_write_multiple_registers(
+        &mut self,
+        _read_range: AddressRange,
+        values: WriteRegisters,
+    ) -> Result<(), ExceptionCode> {
+        self.write_multiple_registers(values)
+    }
+
+    /// Write consecutive records of a file
+    ///
+    /// Called once for every sub-request of a Write File Record request, the range of `values`
+    /// starts at the first record number. Processing stops at the first sub-request that fails.
+    fn write_file_record(
+        &mut self,
+        _file_number: u16,
+        _values: WriteRegisters,
+    ) -> Result<(), ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
+
+    /// Process a request using a function code that is not implemented by the library
+    ///
+    /// `data` contains the request bytes that follow the function code and the returned bytes
+    /// follow the function code in the response. At most 252 bytes may be returned, otherwise
+    /// [`ExceptionCode::ServerDeviceFailure`] is returned to the client.
+    ///
+    /// RTU servers only receive these requests if the length of the frames was registered when
+    /// the server was spawned.
+    fn custom_function(
+        &mut self,
+        _function_code: u8,
+        _data: &[u8],
+    ) -> Result<Vec<u8>, ExceptionCode> {
+        Err(ExceptionCode::IllegalFunction)
+    }
 }
 
 /// Trait useful for converting None into IllegalDataAddress
@@ -137,13 +295,74 @@ where
         self.handlers.insert(id, server)
     }
 
-    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut ServerHandlerType<T>> {
-        self.handlers.values_mut()
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (UnitId, &ServerHandlerType<T>)> {
+        self.handlers.iter().map(|(id, handler)| (*id, handler))
+    }
+}
+
+impl<T> SessionHandlers for ServerHandlerMap<T>
+where
+    T: RequestHandler,
+{
+    fn contains(&self, unit_id: UnitId) -> bool {
+        self.handlers.contains_key(&unit_id)
+    }
+
+    fn unit_ids(&self) -> Vec<UnitId> {
+        self.handlers.keys().copied().collect()
+    }
+
+    fn add_unit(&mut self, unit_id: UnitId, handler: &UnitHandler) -> bool {
+        match handler.get::<ServerHandlerType<T>>() {
+            Some(handler) => {
+                self.add(unit_id, handler.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove_unit(&mut self, unit_id: UnitId) -> bool {
+        self.handlers.remove(&unit_id).is_some()
+    }
+
+    fn process<'a>(
+        &mut self,
+        context: &RequestContext,
+        header: FrameHeader,
+        request: &Request,
+        diagnostics: Option<&mut DiagnosticCounters>,
+        writer: &'a mut FrameWriter,
+        level: DecodeLevel,
+    ) -> Option<Result<Reply<'a>, RequestError>> {
+        let handler = self.get(context.unit_id)?;
+        let mut handler = handler.lock().unwrap();
+        handler.begin_request(context);
+        let reply = request.get_reply(header, handler.as_mut(), diagnostics, writer, level);
+        Some(reply.map(Reply::Ready))
+    }
+
+    fn broadcast(
+        &mut self,
+        context: &RequestContext,
+        request: &BroadcastRequest,
+        unit_ids: &[UnitId],
+    ) {
+        for handler in unit_ids.iter().filter_map(|x| self.handlers.get(x)) {
+            let mut handler = handler.lock().unwrap();
+            handler.begin_request(context);
+            request.execute(handler.as_mut());
+        }
     }
 }
 
 /// Authorization result
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum Authorization {
     /// Client is authorized to perform the operation
     Allow,
@@ -197,6 +416,47 @@ pub trait AuthorizationHandler: Send + Sync + 'static {
         Authorization::Deny
     }
 
+    /// Authorize a Read FIFO Queue request
+    fn read_fifo_queue(&self, _unit_id: UnitId, _address: u16, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a Read Device Identification request
+    fn read_device_identification(
+        &self,
+        _unit_id: UnitId,
+        _code: ReadDeviceIdCode,
+        _object_id: u8,
+        _role: &str,
+    ) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a Read Exception Status request
+    fn read_exception_status(&self, _unit_id: UnitId, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a Diagnostics request
+    fn diagnostics(&self, _unit_id: UnitId, _sub_function: u16, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a Get Comm Event Counter request
+    fn get_comm_event_counter(&self, _unit_id: UnitId, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a Get Comm Event Log request
+    fn get_comm_event_log(&self, _unit_id: UnitId, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a Report Server ID request
+    fn report_server_id(&self, _unit_id: UnitId, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
+
     /// Authorize a Write Single Coil request
     fn write_single_coil(&self, _unit_id: UnitId, _idx: u16, _role: &str) -> Authorization {
         Authorization::Deny
@@ -226,6 +486,52 @@ pub trait AuthorizationHandler: Send + Sync + 'static {
     ) -> Authorization {
         Authorization::Deny
     }
+
+    /// Authorize a Mask Write Register request
+    fn mask_write_register(&self, _unit_id: UnitId, _idx: u16, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a Read/Write Multiple Registers request
+    fn read_write_multiple_registers(
+        &self,
+        _unit_id: UnitId,
+        _read_range: AddressRange,
+        _write_range: AddressRange,
+        _role: &str,
+    ) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a sub-request of a Read File Record request
+    ///
+    /// The request is denied if any of its sub-requests is denied.
+    fn read_file_record(
+        &self,
+        _unit_id: UnitId,
+        _record: FileRecord,
+        _role: &str,
+    ) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a sub-request of a Write File Record request
+    ///
+    /// The request is denied if any of its sub-requests is denied.
+    fn write_file_record(
+        &self,
+        _unit_id: UnitId,
+        _file_number: u16,
+        _range: AddressRange,
+        _role: &str,
+    ) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a request using a custom function code
+    fn custom_function(&self, _unit_id: UnitId, _function_code: u8, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
 }
 
 /// Read-only authorization handler that blindly accepts
@@ -275,6 +581,51 @@ impl AuthorizationHandler for ReadOnlyAuthorizationHandler {
         Authorization::Allow
     }
 
+    /// Authorize a Read FIFO Queue request
+    fn read_fifo_queue(&self, _unit_id: UnitId, _address: u16, _role: &str) -> Authorization {
+        Authorization::Allow
+    }
+
+    /// Authorize a Read Device Identification request
+    fn read_device_identification(
+        &self,
+        _unit_id: UnitId,
+        _code: ReadDeviceIdCode,
+        _object_id: u8,
+        _role: &str,
+    ) -> Authorization {
+        Authorization::Allow
+    }
+
+    /// Authorize a Read Exception Status request
+    fn read_exception_status(&self, _unit_id: UnitId, _role: &str) -> Authorization {
+        Authorization::Allow
+    }
+
+    /// Authorize a Diagnostics request, only sub-functions that don't change the state of the device are allowed
+    fn diagnostics(&self, _unit_id: UnitId, sub_function: u16, _role: &str) -> Authorization {
+        if DiagnosticsRequest::new(sub_function, 0).is_read_only() {
+            Authorization::Allow
+        } else {
+            Authorization::Deny
+        }
+    }
+
+    /// Authorize a Get Comm Event Counter request
+    fn get_comm_event_counter(&self, _unit_id: UnitId, _role: &str) -> Authorization {
+        Authorization::Allow
+    }
+
+    /// Authorize a Get Comm Event Log request
+    fn get_comm_event_log(&self, _unit_id: UnitId, _role: &str) -> Authorization {
+        Authorization::Allow
+    }
+
+    /// Authorize a Report Server ID request
+    fn report_server_id(&self, _unit_id: UnitId, _role: &str) -> Authorization {
+        Authorization::Allow
+    }
+
     /// Authorize a Write Single Coil request
     fn write_single_coil(&self, _unit_id: UnitId, _idx: u16, _role: &str) -> Authorization {
         Authorization::Deny
@@ -304,6 +655,48 @@ impl AuthorizationHandler for ReadOnlyAuthorizationHandler {
     ) -> Authorization {
         Authorization::Deny
     }
+
+    /// Authorize a Mask Write Register request
+    fn mask_write_register(&self, _unit_id: UnitId, _idx: u16, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a Read/Write Multiple Registers request
+    fn read_write_multiple_registers(
+        &self,
+        _unit_id: UnitId,
+        _read_range: AddressRange,
+        _write_range: AddressRange,
+        _role: &str,
+    ) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a sub-request of a Read File Record request
+    fn read_file_record(
+        &self,
+        _unit_id: UnitId,
+        _record: FileRecord,
+        _role: &str,
+    ) -> Authorization {
+        Authorization::Allow
+    }
+
+    /// Authorize a sub-request of a Write File Record request
+    fn write_file_record(
+        &self,
+        _unit_id: UnitId,
+        _file_number: u16,
+        _range: AddressRange,
+        _role: &str,
+    ) -> Authorization {
+        Authorization::Deny
+    }
+
+    /// Authorize a request using a custom function code, denied because it may change the state of the device
+    fn custom_function(&self, _unit_id: UnitId, _function_code: u8, _role: &str) -> Authorization {
+        Authorization::Deny
+    }
 }
 
 #[cfg(test)]
@@ -337,6 +730,67 @@ mod tests {
             handler.write_single_register(Indexed::new(0, 0)),
             Err(ExceptionCode::IllegalFunction)
         );
+        assert_eq!(
+            handler.mask_write_register(MaskWriteRegister::new(0, 0xFFFF, 0x0000)),
+            Err(ExceptionCode::IllegalFunction)
+        );
+        assert_eq!(
+            handler.read_exception_status(),
+            Err(ExceptionCode::IllegalFunction)
+        );
+        assert_eq!(
+            handler.report_server_id(),
+            Err(ExceptionCode::IllegalFunction)
+        );
+        assert_eq!(
+            handler.read_file_record(4, 1),
+            Err(ExceptionCode::IllegalFunction)
+        );
+        let range = AddressRange::try_from(1, 1).unwrap();
+        let mut cursor = scursor::ReadCursor::new(&[0x00, 0x01]);
+        let values = RegisterIterator::parse(range, &mut cursor).unwrap();
+        assert_eq!(
+            handler.write_file_record(4, WriteRegisters::new(range, values)),
+            Err(ExceptionCode::IllegalFunction)
+        );
+        assert_eq!(
+            handler.custom_function(0x41, &[0x01]),
+            Err(ExceptionCode::IllegalFunction)
+        );
+    }
+
+    #[test]
+    fn default_mask_write_register_uses_read_and_write() {
+        struct SingleRegister(u16);
+        impl RequestHandler for SingleRegister {
+            fn read_holding_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+                match address {
+                    0 => Ok(self.0),
+                    _ => Err(ExceptionCode::IllegalDataAddress),
+                }
+            }
+
+            fn write_single_register(&mut self, value: Indexed<u16>) -> Result<(), ExceptionCode> {
+                match value.index {
+                    0 => {
+                        self.0 = value.value;
+                        Ok(())
+                    }
+                    _ => Err(ExceptionCode::IllegalDataAddress),
+                }
+            }
+        }
+
+        let mut handler = SingleRegister(0x0012);
+        assert_eq!(
+            handler.mask_write_register(MaskWriteRegister::new(0, 0x00F2, 0x0025)),
+            Ok(())
+        );
+        assert_eq!(handler.0, 0x0017);
+        assert_eq!(
+            handler.mask_write_register(MaskWriteRegister::new(1, 0x00F2, 0x0025)),
+            Err(ExceptionCode::IllegalDataAddress)
+        );
     }
 
     #[test]
@@ -2,12 +2,20 @@ use std::net::SocketAddr;
 
 use tracing::Instrument;
 
+use crate::common::frame::TcpFraming;
 use crate::decode::DecodeLevel;
 use crate::server::task::ServerSetting;
 use crate::tcp::server::{ServerTask, TcpServerConnectionHandler};
+use crate::types::UnitId;
 
 /// server handling
 mod address_filter;
+pub(crate) mod async_handler;
+mod authorization;
+mod database;
+mod device_id;
+pub(crate) mod diagnostics;
+mod gateway;
 pub(crate) mod handler;
 pub(crate) mod request;
 pub(crate) mod response;
@@ -20,6 +28,11 @@ pub(crate) const SERVER_SETTING_CHANNEL_CAPACITY: usize = 8;
 use crate::error::Shutdown;
 
 pub use address_filter::*;
+pub use async_handler::{AsyncRequestHandler, AsyncServerHandlerMap, SyncHandler};
+pub use authorization::*;
+pub use database::*;
+pub use device_id::*;
+pub use gateway::*;
 pub use handler::*;
 pub use types::*;
 
@@ -48,6 +61,102 @@ impl ServerHandle {
         self.tx.send(ServerSetting::ChangeDecoding(level)).await?;
         Ok(())
     }
+
+    /// Add or replace the handler of a unit id on a server spawned with a [`ServerHandlerMap<T>`]
+    ///
+    /// The handler is ignored if the server was spawned with handlers of another type.
+    pub async fn add_unit<T: RequestHandler>(
+        &mut self,
+        id: UnitId,
+        handler: ServerHandlerType<T>,
+    ) -> Result<(), Shutdown> {
+        self.tx
+            .send(ServerSetting::AddUnit(id, task::UnitHandler::new(handler)))
+            .await?;
+        Ok(())
+    }
+
+    /// Add or replace the handler of a unit id on a server spawned with an [`AsyncServerHandlerMap`]
+    ///
+    /// The handler is ignored if the server was spawned with synchronous handlers.
+    pub async fn add_async_unit(
+        &mut self,
+        id: UnitId,
+        handler: std::sync::Arc<dyn AsyncRequestHandler>,
+    ) -> Result<(), Shutdown> {
+        self.tx
+            .send(ServerSetting::AddUnit(id, task::UnitHandler::new(handler)))
+            .await?;
+        Ok(())
+    }
+
+    /// Remove the handler and the settings of a unit id, requests addressed to it are no longer answered
+    pub async fn remove_unit(&mut self, id: UnitId) -> Result<(), Shutdown> {
+        self.tx.send(ServerSetting::RemoveUnit(id)).await?;
+        Ok(())
+    }
+
+    /// Change the response delay and the state of a unit id
+    ///
+    /// This allows a single RTU server to emulate a multi-drop line of devices with different
+    /// timings. The settings may be changed before the handler of the unit id is added.
+    pub async fn configure_unit(
+        &mut self,
+        id: UnitId,
+        settings: UnitSettings,
+    ) -> Result<(), Shutdown> {
+        self.tx
+            .send(ServerSetting::ConfigureUnit(id, settings))
+            .await?;
+        Ok(())
+    }
+
+    /// Replace the filter of the addresses allowed to connect to a TCP or TLS server
+    ///
+    /// Active sessions of clients that don't match the new filter are closed. A UDP server applies
+    /// the filter to the datagrams it receives.
+    pub async fn set_address_filter(&mut self, filter: AddressFilter) -> Result<(), Shutdown> {
+        self.tx.send(ServerSetting::ChangeFilter(filter)).await?;
+        Ok(())
+    }
+
+    /// Change the maximum number of concurrent sessions of a TCP or TLS server
+    ///
+    /// The oldest sessions are closed if more sessions than the new maximum are active.
+    pub async fn set_max_sessions(&mut self, max_sessions: usize) -> Result<(), Shutdown> {
+        self.tx
+            .send(ServerSetting::ChangeMaxSessions(max_sessions))
+            .await?;
+        Ok(())
+    }
+
+    /// Change the TLS configuration, e.g. renewed certificates, of a TLS server
+    ///
+    /// The configuration is used by the sessions accepted after the change. Active sessions
+    /// aren't affected.
+    #[cfg(feature = "tls")]
+    pub async fn set_tls_config(&mut self, config: TlsServerConfig) -> Result<(), Shutdown> {
+        self.tx.send(ServerSetting::ChangeTlsConfig(config)).await?;
+        Ok(())
+    }
+
+    /// Retrieve the active sessions of a TCP or TLS server
+    ///
+    /// Servers that don't accept connections, i.e. UDP and serial servers, have no sessions.
+    pub async fn sessions(&mut self) -> Result<Vec<SessionInfo>, Shutdown> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.tx.send(ServerSetting::ListSessions(tx)).await?;
+        Ok(rx.await?)
+    }
+
+    /// Close an active session of a TCP or TLS server
+    ///
+    /// Returns false if the session was already closed.
+    pub async fn close_session(&mut self, id: SessionId) -> Result<bool, Shutdown> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.tx.send(ServerSetting::CloseSession(id, tx)).await?;
+        Ok(rx.await?)
+    }
 }
 
 /// Spawns a TCP server task onto the runtime. This method can only
@@ -68,6 +177,168 @@ pub async fn spawn_tcp_server_task<T: RequestHandler>(
     handlers: ServerHandlerMap<T>,
     filter: AddressFilter,
     decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_tcp_server_task_impl(
+        max_sessions,
+        addr,
+        handlers,
+        TcpFraming::Mbap,
+        filter,
+        decode,
+    )
+    .await
+}
+
+/// Spawns a TCP server task onto the runtime that processes requests using asynchronous handlers.
+///
+/// Each incoming connection will spawn a new task to handle it. A session processes up to 16
+/// requests of a connection at the same time and sends each reply as soon as the handler
+/// completes, so clients that pipeline requests aren't delayed by a slow request.
+///
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of asynchronous handlers keyed by a unit id
+/// * `filter` - Address filter which may be used to restrict the connecting IP address
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+pub async fn spawn_async_tcp_server_task(
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: AsyncServerHandlerMap,
+    filter: AddressFilter,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_tcp_server_task_impl(
+        max_sessions,
+        addr,
+        handlers,
+        TcpFraming::Mbap,
+        filter,
+        decode,
+    )
+    .await
+}
+
+/// Spawns a Modbus over UDP server task onto the runtime.
+///
+/// Each datagram contains a single request using the MBAP header. The response is sent to
+/// the address the request was received from. Requests are processed one at a time regardless
+/// of the address that sent them.
+///
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `filter` - Address filter which may be used to restrict the IP addresses that are answered
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+pub async fn spawn_udp_server_task<T: RequestHandler>(
+    addr: SocketAddr,
+    handlers: ServerHandlerMap<T>,
+    filter: AddressFilter,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_udp_server_task_impl(addr, handlers, filter, decode).await
+}
+
+/// Spawns a Modbus over UDP server task onto the runtime that processes requests using
+/// asynchronous handlers.
+///
+/// Requests are still answered one at a time, the server waits for the handler to complete
+/// before receiving the next datagram.
+///
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of asynchronous handlers keyed by a unit id
+/// * `filter` - Address filter which may be used to restrict the IP addresses that are answered
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+pub async fn spawn_async_udp_server_task(
+    addr: SocketAddr,
+    handlers: AsyncServerHandlerMap,
+    filter: AddressFilter,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_udp_server_task_impl(addr, handlers, filter, decode).await
+}
+
+async fn spawn_udp_server_task_impl<H: task::SessionHandlers>(
+    addr: SocketAddr,
+    handlers: H,
+    filter: AddressFilter,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    let socket = tokio::net::UdpSocket::bind(addr).await?;
+
+    let (tx, rx) = tokio::sync::mpsc::channel(SERVER_SETTING_CHANNEL_CAPACITY);
+    let session = task::SessionTask::new(
+        handlers,
+        task::AuthorizationType::None,
+        crate::common::frame::FrameWriter::tcp(),
+        crate::common::frame::FramedReader::tcp(),
+        rx,
+        decode,
+        false,
+    );
+
+    let mut server = crate::udp::server::UdpServerTask {
+        phys: crate::common::phys::PhysLayer::new_udp_server(
+            crate::udp::server::UdpServerSocket::new(socket, filter),
+        ),
+        session,
+    };
+
+    let task = async move {
+        server
+            .run()
+            .instrument(tracing::info_span!("Modbus-Server-UDP", "listen" = ?addr))
+            .await
+    };
+
+    tokio::spawn(task);
+
+    Ok(ServerHandle::new(tx))
+}
+
+/// Spawns a TCP server task onto the runtime that exchanges RTU frames instead of using
+/// the MBAP header. This method can only be called from within the runtime context.
+///
+/// Each request and response is an RTU frame including its CRC. This is the framing used
+/// by serial-to-Ethernet converters in "transparent" mode.
+///
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `filter` - Address filter which may be used to restrict the connecting IP address
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(feature = "serial")]
+pub async fn spawn_rtu_over_tcp_server_task<T: RequestHandler>(
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: ServerHandlerMap<T>,
+    filter: AddressFilter,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_tcp_server_task_impl(
+        max_sessions,
+        addr,
+        handlers,
+        TcpFraming::Rtu,
+        filter,
+        decode,
+    )
+    .await
+}
+
+async fn spawn_tcp_server_task_impl<H: task::SessionHandlers>(
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: H,
+    framing: TcpFraming,
+    filter: AddressFilter,
+    decode: DecodeLevel,
 ) -> Result<ServerHandle, std::io::Error> {
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
@@ -79,6 +350,7 @@ pub async fn spawn_tcp_server_task<T: RequestHandler>(
             listener,
             handlers,
             TcpServerConnectionHandler::Tcp,
+            framing,
             filter,
             decode,
         )
@@ -109,30 +381,141 @@ pub fn spawn_rtu_server_task<T: RequestHandler>(
     handlers: ServerHandlerMap<T>,
     decode: DecodeLevel,
 ) -> Result<ServerHandle, std::io::Error> {
-    let (tx, rx) = tokio::sync::mpsc::channel(SERVER_SETTING_CHANNEL_CAPACITY);
-    let session = crate::server::task::SessionTask::new(
+    spawn_rtu_server_task_with_custom_functions(
+        path,
+        settings,
+        retry,
+        handlers,
+        crate::serial::CustomFunctionLengths::default(),
+        decode,
+    )
+}
+
+/// Spawns a RTU server task onto the runtime that also accepts requests using custom function codes.
+///
+/// * `path` - Path to the serial device. Generally `/dev/tty0` on Linux and `COM1` on Windows.
+/// * `settings` - Serial port settings
+/// * `retry` - A boxed trait object that controls when opening the serial port is retried after a failure
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `custom` - Lengths of the requests using custom function codes, see [`RequestHandler::custom_function`]
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(feature = "serial")]
+pub fn spawn_rtu_server_task_with_custom_functions<T: RequestHandler>(
+    path: &str,
+    settings: crate::serial::SerialSettings,
+    retry: Box<dyn crate::retry::RetryStrategy>,
+    handlers: ServerHandlerMap<T>,
+    custom: crate::serial::CustomFunctionLengths,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_serial_server_task(
+        path,
+        settings,
+        retry,
         handlers,
-        crate::server::task::AuthorizationType::None,
         crate::common::frame::FrameWriter::rtu(),
-        crate::common::frame::FramedReader::rtu_request(),
+        crate::common::frame::FramedReader::rtu_request(custom),
+        decode,
+        tracing::info_span!("Modbus-Server-RTU", "port" = ?path),
+    )
+}
+
+/// Spawns a RTU server task onto the runtime that processes requests using asynchronous handlers.
+///
+/// Requests are answered one at a time, the server waits for the handler to complete before
+/// reading the next frame from the serial port.
+///
+/// * `path` - Path to the serial device. Generally `/dev/tty0` on Linux and `COM1` on Windows.
+/// * `settings` - Serial port settings
+/// * `retry` - A boxed trait object that controls when opening the serial port is retried after a failure
+/// * `handlers` - A map of asynchronous handlers keyed by a unit id
+/// * `custom` - Lengths of the requests using custom function codes, see [`AsyncRequestHandler::custom_function`]
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(feature = "serial")]
+pub fn spawn_async_rtu_server_task(
+    path: &str,
+    settings: crate::serial::SerialSettings,
+    retry: Box<dyn crate::retry::RetryStrategy>,
+    handlers: AsyncServerHandlerMap,
+    custom: crate::serial::CustomFunctionLengths,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_serial_server_task(
+        path,
+        settings,
+        retry,
+        handlers,
+        crate::common::frame::FrameWriter::rtu(),
+        crate::common::frame::FramedReader::rtu_request(custom),
+        decode,
+        tracing::info_span!("Modbus-Server-RTU", "port" = ?path),
+    )
+}
+
+/// Spawns a Modbus ASCII server task onto the runtime.
+///
+/// * `path` - Path to the serial device. Generally `/dev/tty0` on Linux and `COM1` on Windows.
+/// * `settings` - Serial port settings
+/// * `retry` - A boxed trait object that controls when opening the serial port is retried after a failure
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(feature = "serial")]
+pub fn spawn_ascii_server_task<T: RequestHandler>(
+    path: &str,
+    settings: crate::serial::SerialSettings,
+    retry: Box<dyn crate::retry::RetryStrategy>,
+    handlers: ServerHandlerMap<T>,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_serial_server_task(
+        path,
+        settings,
+        retry,
+        handlers,
+        crate::common::frame::FrameWriter::ascii(),
+        crate::common::frame::FramedReader::ascii(),
+        decode,
+        tracing::info_span!("Modbus-Server-ASCII", "port" = ?path),
+    )
+}
+
+#[cfg(feature = "serial")]
+#[allow(clippy::too_many_arguments)]
+fn spawn_serial_server_task<H: task::SessionHandlers>(
+    path: &str,
+    settings: crate::serial::SerialSettings,
+    retry: Box<dyn crate::retry::RetryStrategy>,
+    handlers: H,
+    writer: crate::common::frame::FrameWriter,
+    reader: crate::common::frame::FramedReader,
+    decode: DecodeLevel,
+    span: tracing::Span,
+) -> Result<ServerHandle, std::io::Error> {
+    let (tx, rx) = tokio::sync::mpsc::channel(SERVER_SETTING_CHANNEL_CAPACITY);
+    let session = task::SessionTask::new(
+        handlers,
+        task::AuthorizationType::None,
+        writer,
+        reader,
         rx,
         decode,
+        true,
     );
 
-    let mut rtu = crate::serial::server::RtuServerTask {
+    let mut server = crate::serial::server::RtuServerTask {
         port: path.to_string(),
         retry,
         settings,
         session,
     };
 
-    let path = path.to_string();
-
-    let task = async move {
-        rtu.run()
-            .instrument(tracing::info_span!("Modbus-Server-RTU", "port" = ?path))
-            .await
-    };
+    let task = async move { server.run().instrument(span).await };
 
     tokio::spawn(task);
 
@@ -168,6 +551,7 @@ pub async fn spawn_tls_server_task<T: RequestHandler>(
         handlers,
         None,
         tls_config,
+        TcpFraming::Mbap,
         filter,
         decode,
     )
@@ -206,19 +590,162 @@ pub async fn spawn_tls_server_task_with_authz<T: RequestHandler>(
         handlers,
         Some(auth_handler),
         tls_config,
+        TcpFraming::Mbap,
+        filter,
+        decode,
+    )
+    .await
+}
+
+/// Spawns a "raw" TLS server task onto the runtime that processes requests using asynchronous
+/// handlers. See [`spawn_async_tcp_server_task`] for how requests are processed and
+/// [`spawn_tls_server_task`] for how clients are authorized.
+///
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of asynchronous handlers keyed by a unit id
+/// * `tls_config` - TLS configuration
+/// * `filter` - Address filter which may be used to restrict the connecting IP address
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(feature = "tls")]
+pub async fn spawn_async_tls_server_task(
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: AsyncServerHandlerMap,
+    tls_config: TlsServerConfig,
+    filter: AddressFilter,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_tls_server_task_impl(
+        max_sessions,
+        addr,
+        handlers,
+        None,
+        tls_config,
+        TcpFraming::Mbap,
         filter,
         decode,
     )
     .await
 }
 
+/// Spawns a "Secure Modbus" TLS server task onto the runtime that processes requests using
+/// asynchronous handlers. See [`spawn_async_tcp_server_task`] for how requests are processed
+/// and [`spawn_tls_server_task_with_authz`] for how requests are authorized.
+///
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of asynchronous handlers keyed by a unit id
+/// * `auth_handler` - Handler used to authorize requests
+/// * `tls_config` - TLS configuration
+/// * `filter` - Address filter which may be used to restrict the connecting IP address
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
 #[cfg(feature = "tls")]
-async fn spawn_tls_server_task_impl<T: RequestHandler>(
+pub async fn spawn_async_tls_server_task_with_authz(
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: AsyncServerHandlerMap,
+    auth_handler: std::sync::Arc<dyn AuthorizationHandler>,
+    tls_config: TlsServerConfig,
+    filter: AddressFilter,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_tls_server_task_impl(
+        max_sessions,
+        addr,
+        handlers,
+        Some(auth_handler),
+        tls_config,
+        TcpFraming::Mbap,
+        filter,
+        decode,
+    )
+    .await
+}
+
+/// Spawns a "raw" TLS server task onto the runtime that exchanges RTU frames instead of using
+/// the MBAP header. See [`spawn_rtu_over_tcp_server_task`] for a description of the framing
+/// and [`spawn_tls_server_task`] for how clients are authorized.
+///
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `tls_config` - TLS configuration
+/// * `filter` - Address filter which may be used to restrict the connecting IP address
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(all(feature = "tls", feature = "serial"))]
+pub async fn spawn_rtu_over_tls_server_task<T: RequestHandler>(
     max_sessions: usize,
     addr: SocketAddr,
     handlers: ServerHandlerMap<T>,
+    tls_config: TlsServerConfig,
+    filter: AddressFilter,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_tls_server_task_impl(
+        max_sessions,
+        addr,
+        handlers,
+        None,
+        tls_config,
+        TcpFraming::Rtu,
+        filter,
+        decode,
+    )
+    .await
+}
+
+/// Spawns a "Secure Modbus" TLS server task onto the runtime that exchanges RTU frames instead
+/// of using the MBAP header. See [`spawn_rtu_over_tcp_server_task`] for a description of the
+/// framing and [`spawn_tls_server_task_with_authz`] for how requests are authorized.
+///
+/// * `max_sessions` - Maximum number of concurrent sessions
+/// * `addr` - A socket address to bound to
+/// * `handlers` - A map of handlers keyed by a unit id
+/// * `auth_handler` - Handler used to authorize requests
+/// * `tls_config` - TLS configuration
+/// * `filter` - Address filter which may be used to restrict the connecting IP address
+/// * `decode` - Decode log level
+///
+/// `WARNING`: This function must be called from with the context of the Tokio runtime or it will panic.
+#[cfg(all(feature = "tls", feature = "serial"))]
+pub async fn spawn_rtu_over_tls_server_task_with_authz<T: RequestHandler>(
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: ServerHandlerMap<T>,
+    auth_handler: std::sync::Arc<dyn AuthorizationHandler>,
+    tls_config: TlsServerConfig,
+    filter: AddressFilter,
+    decode: DecodeLevel,
+) -> Result<ServerHandle, std::io::Error> {
+    spawn_tls_server_task_impl(
+        max_sessions,
+        addr,
+        handlers,
+        Some(auth_handler),
+        tls_config,
+        TcpFraming::Rtu,
+        filter,
+        decode,
+    )
+    .await
+}
+
+#[cfg(feature = "tls")]
+#[allow(clippy::too_many_arguments)]
+async fn spawn_tls_server_task_impl<H: task::SessionHandlers>(
+    max_sessions: usize,
+    addr: SocketAddr,
+    handlers: H,
     auth_handler: Option<std::sync::Arc<dyn AuthorizationHandler>>,
     tls_config: TlsServerConfig,
+    framing: TcpFraming,
     filter: AddressFilter,
     decode: DecodeLevel,
 ) -> Result<ServerHandle, std::io::Error> {
@@ -232,6 +759,7 @@ async fn spawn_tls_server_task_impl<T: RequestHandler>(
             listener,
             handlers,
             TcpServerConnectionHandler::Tls(tls_config, auth_handler),
+            framing,
             filter,
             decode,
         )
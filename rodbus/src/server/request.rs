@@ -1,11 +1,20 @@
 use crate::common::frame::{FrameHeader, FrameWriter, FunctionField};
 use crate::common::function::FunctionCode;
 use crate::common::traits::{Loggable, Parse, Serialize};
+use crate::constants::limits;
 use crate::decode::AppDecodeLevel;
-use crate::error::RequestError;
+use crate::device_id::ReadDeviceIdCode;
+use crate::diagnostics::DiagnosticsRequest;
+use crate::error::{AduParseError, RequestError};
 use crate::exception::ExceptionCode;
+use crate::file_record::{ReadFileRecords, WriteFileRecords, WriteFileRecordsDisplay};
+use crate::server::diagnostics::DiagnosticCounters;
 use crate::server::handler::RequestHandler;
-use crate::server::response::{BitWriter, RegisterWriter};
+use crate::server::response::{
+    bits_of, registers_of, CustomFunctionResponse, DeviceIdWriter, ExceptionStatus, FifoQueue,
+    FileRecordWriter, ServerId,
+};
+use crate::server::types::{ReadDeviceId, ReadWriteRegisters};
 use crate::server::*;
 use crate::types::*;
 
@@ -19,8 +28,21 @@ pub(crate) enum Request<'a> {
     ReadInputRegisters(ReadRegistersRange),
     WriteSingleCoil(Indexed<bool>),
     WriteSingleRegister(Indexed<u16>),
+    ReadExceptionStatus,
+    Diagnostics(DiagnosticsRequest),
+    GetCommEventCounter,
+    GetCommEventLog,
     WriteMultipleCoils(WriteCoils<'a>),
     WriteMultipleRegisters(WriteRegisters<'a>),
+    ReportServerId,
+    ReadFileRecord(ReadFileRecords<'a>),
+    WriteFileRecord(WriteFileRecords<'a>),
+    MaskWriteRegister(MaskWriteRegister),
+    ReadWriteMultipleRegisters(ReadWriteRegisters<'a>),
+    ReadFifoQueue(u16),
+    ReadDeviceIdentification(ReadDeviceId),
+    /// function code and the bytes that follow it
+    CustomFunction(u8, &'a [u8]),
 }
 
 /// All requests that support broadcast
@@ -31,6 +53,24 @@ pub(crate) enum BroadcastRequest<'a> {
     WriteSingleRegister(Indexed<u16>),
     WriteMultipleCoils(WriteCoils<'a>),
     WriteMultipleRegisters(WriteRegisters<'a>),
+    MaskWriteRegister(MaskWriteRegister),
+}
+
+/// format the response or the exception returned by a handler
+pub(crate) fn write_result<T>(
+    function: FunctionCode,
+    header: FrameHeader,
+    writer: &mut FrameWriter,
+    result: Result<T, ExceptionCode>,
+    level: DecodeLevel,
+) -> Result<&[u8], RequestError>
+where
+    T: Serialize + Loggable,
+{
+    match result {
+        Ok(response) => writer.format_reply(header, function, &response, level),
+        Err(ex) => writer.format_ex(header, FunctionField::Exception(function), ex, level),
+    }
 }
 
 impl<'a> BroadcastRequest<'a> {
@@ -49,6 +89,9 @@ impl<'a> BroadcastRequest<'a> {
             BroadcastRequest::WriteMultipleRegisters(x) => {
                 let _ = handler.write_multiple_registers(*x);
             }
+            BroadcastRequest::MaskWriteRegister(x) => {
+                let _ = handler.mask_write_register(*x);
+            }
         }
     }
 }
@@ -62,8 +105,20 @@ impl<'a> Request<'a> {
             Request::ReadInputRegisters(_) => FunctionCode::ReadInputRegisters,
             Request::WriteSingleCoil(_) => FunctionCode::WriteSingleCoil,
             Request::WriteSingleRegister(_) => FunctionCode::WriteSingleRegister,
+            Request::ReadExceptionStatus => FunctionCode::ReadExceptionStatus,
+            Request::Diagnostics(_) => FunctionCode::Diagnostics,
+            Request::GetCommEventCounter => FunctionCode::GetCommEventCounter,
+            Request::GetCommEventLog => FunctionCode::GetCommEventLog,
             Request::WriteMultipleCoils(_) => FunctionCode::WriteMultipleCoils,
             Request::WriteMultipleRegisters(_) => FunctionCode::WriteMultipleRegisters,
+            Request::ReportServerId => FunctionCode::ReportServerId,
+            Request::ReadFileRecord(_) => FunctionCode::ReadFileRecord,
+            Request::WriteFileRecord(_) => FunctionCode::WriteFileRecord,
+            Request::MaskWriteRegister(_) => FunctionCode::MaskWriteRegister,
+            Request::ReadWriteMultipleRegisters(_) => FunctionCode::ReadWriteMultipleRegisters,
+            Request::ReadFifoQueue(_) => FunctionCode::ReadFifoQueue,
+            Request::ReadDeviceIdentification(_) => FunctionCode::ReadDeviceIdentification,
+            Request::CustomFunction(x, _) => FunctionCode::Custom(*x),
         }
     }
 
@@ -75,8 +130,20 @@ impl<'a> Request<'a> {
             Request::ReadInputRegisters(_) => None,
             Request::WriteSingleCoil(x) => Some(BroadcastRequest::WriteSingleCoil(x)),
             Request::WriteSingleRegister(x) => Some(BroadcastRequest::WriteSingleRegister(x)),
+            Request::ReadExceptionStatus => None,
+            Request::Diagnostics(_) => None,
+            Request::GetCommEventCounter => None,
+            Request::GetCommEventLog => None,
             Request::WriteMultipleCoils(x) => Some(BroadcastRequest::WriteMultipleCoils(x)),
             Request::WriteMultipleRegisters(x) => Some(BroadcastRequest::WriteMultipleRegisters(x)),
+            Request::ReportServerId => None,
+            Request::ReadFileRecord(_) => None,
+            Request::WriteFileRecord(_) => None,
+            Request::MaskWriteRegister(x) => Some(BroadcastRequest::MaskWriteRegister(x)),
+            Request::ReadWriteMultipleRegisters(_) => None,
+            Request::ReadFifoQueue(_) => None,
+            Request::ReadDeviceIdentification(_) => None,
+            Request::CustomFunction(_, _) => None,
         }
     }
 
@@ -84,44 +151,45 @@ impl<'a> Request<'a> {
         &self,
         header: FrameHeader,
         handler: &mut dyn RequestHandler,
+        diagnostics: Option<&mut DiagnosticCounters>,
         writer: &'b mut FrameWriter,
         level: DecodeLevel,
     ) -> Result<&'b [u8], RequestError> {
-        fn write_result<T>(
-            function: FunctionCode,
-            header: FrameHeader,
-            writer: &mut FrameWriter,
-            result: Result<T, ExceptionCode>,
-            level: DecodeLevel,
-        ) -> Result<&[u8], RequestError>
-        where
-            T: Serialize + Loggable,
-        {
-            match result {
-                Ok(response) => writer.format_reply(header, function, &response, level),
-                Err(ex) => writer.format_ex(header, FunctionField::Exception(function), ex, level),
-            }
-        }
-
         let function = self.get_function();
 
         // make a first pass effort to serialize a response
         match self {
             Request::ReadCoils(range) => {
-                let bits = BitWriter::new(*range, |i| handler.read_coil(i));
-                writer.format_reply(header, function, &bits, level)
+                let mut values = [false; limits::MAX_READ_COILS_COUNT as usize];
+                let values = &mut values[..range.get().count as usize];
+                let result = handler
+                    .read_coils(range.get(), values)
+                    .map(|_| bits_of(*range, values));
+                write_result(function, header, writer, result, level)
             }
             Request::ReadDiscreteInputs(range) => {
-                let bits = BitWriter::new(*range, |i| handler.read_discrete_input(i));
-                writer.format_reply(header, function, &bits, level)
+                let mut values = [false; limits::MAX_READ_COILS_COUNT as usize];
+                let values = &mut values[..range.get().count as usize];
+                let result = handler
+                    .read_discrete_inputs(range.get(), values)
+                    .map(|_| bits_of(*range, values));
+                write_result(function, header, writer, result, level)
             }
             Request::ReadHoldingRegisters(range) => {
-                let registers = RegisterWriter::new(*range, |i| handler.read_holding_register(i));
-                writer.format_reply(header, function, &registers, level)
+                let mut values = [0; limits::MAX_READ_REGISTERS_COUNT as usize];
+                let values = &mut values[..range.get().count as usize];
+                let result = handler
+                    .read_holding_registers(range.get(), values)
+                    .map(|_| registers_of(*range, values));
+                write_result(function, header, writer, result, level)
             }
             Request::ReadInputRegisters(range) => {
-                let registers = RegisterWriter::new(*range, |i| handler.read_input_register(i));
-                writer.format_reply(header, function, &registers, level)
+                let mut values = [0; limits::MAX_READ_REGISTERS_COUNT as usize];
+                let values = &mut values[..range.get().count as usize];
+                let result = handler
+                    .read_input_registers(range.get(), values)
+                    .map(|_| registers_of(*range, values));
+                write_result(function, header, writer, result, level)
             }
             Request::WriteSingleCoil(request) => {
                 let result = handler.write_single_coil(*request).map(|_| *request);
@@ -131,6 +199,30 @@ impl<'a> Request<'a> {
                 let result = handler.write_single_register(*request).map(|_| *request);
                 write_result(function, header, writer, result, level)
             }
+            Request::ReadExceptionStatus => {
+                let result = handler.read_exception_status().map(ExceptionStatus::new);
+                write_result(function, header, writer, result, level)
+            }
+            // the serial line diagnostics are only answered by sessions that maintain counters
+            Request::Diagnostics(request) => {
+                let result = match diagnostics {
+                    None => Err(ExceptionCode::IllegalFunction),
+                    Some(counters) => counters.diagnostics(*request),
+                };
+                write_result(function, header, writer, result, level)
+            }
+            Request::GetCommEventCounter => {
+                let result = diagnostics
+                    .map(|x| x.comm_event_counter())
+                    .ok_or(ExceptionCode::IllegalFunction);
+                write_result(function, header, writer, result, level)
+            }
+            Request::GetCommEventLog => {
+                let result = diagnostics
+                    .map(|x| x.comm_event_log())
+                    .ok_or(ExceptionCode::IllegalFunction);
+                write_result(function, header, writer, result, level)
+            }
             Request::WriteMultipleCoils(items) => {
                 let result = handler.write_multiple_coils(*items).map(|_| items.range);
                 write_result(function, header, writer, result, level)
@@ -141,6 +233,59 @@ impl<'a> Request<'a> {
                     .map(|_| items.range);
                 write_result(function, header, writer, result, level)
             }
+            Request::ReportServerId => {
+                let result = handler.report_server_id().and_then(ServerId::new);
+                write_result(function, header, writer, result, level)
+            }
+            Request::ReadFileRecord(records) => {
+                let records = FileRecordWriter::new(*records, |file, record| {
+                    handler.read_file_record(file, record)
+                });
+                writer.format_reply(header, function, &records, level)
+            }
+            Request::WriteFileRecord(records) => {
+                let result = records
+                    .iter()
+                    .try_for_each(|(file, values)| handler.write_file_record(file, values))
+                    .map(|_| *records);
+                write_result(function, header, writer, result, level)
+            }
+            Request::MaskWriteRegister(request) => {
+                let result = handler.mask_write_register(*request).map(|_| *request);
+                write_result(function, header, writer, result, level)
+            }
+            Request::ReadWriteMultipleRegisters(request) => {
+                // the write is performed before the read as required by the specification
+                if let Err(ex) =
+                    handler.read_write_multiple_registers(request.read_range.get(), request.write)
+                {
+                    return writer.format_ex(header, FunctionField::Exception(function), ex, level);
+                }
+                let range = request.read_range;
+                let mut values = [0; limits::MAX_READ_REGISTERS_COUNT as usize];
+                let values = &mut values[..range.get().count as usize];
+                let result = handler
+                    .read_holding_registers(range.get(), values)
+                    .map(|_| registers_of(range, values));
+                write_result(function, header, writer, result, level)
+            }
+            Request::ReadFifoQueue(address) => {
+                let result = handler.read_fifo_queue(*address).and_then(FifoQueue::new);
+                write_result(function, header, writer, result, level)
+            }
+            Request::ReadDeviceIdentification(request) => {
+                let result = match handler.device_identification() {
+                    None => Err(ExceptionCode::IllegalFunction),
+                    Some(store) => DeviceIdWriter::new(store, request.code, request.object_id),
+                };
+                write_result(function, header, writer, result, level)
+            }
+            Request::CustomFunction(code, data) => {
+                let result = handler
+                    .custom_function(*code, data)
+                    .and_then(CustomFunctionResponse::new);
+                write_result(function, header, writer, result, level)
+            }
         }
     }
 
@@ -182,6 +327,33 @@ impl<'a> Request<'a> {
                 cursor.expect_empty()?;
                 Ok(x)
             }
+            FunctionCode::ReadExceptionStatus => {
+                cursor.expect_empty()?;
+                Ok(Request::ReadExceptionStatus)
+            }
+            FunctionCode::Diagnostics => {
+                let x = Request::Diagnostics(DiagnosticsRequest::parse(cursor)?);
+                cursor.expect_empty()?;
+                Ok(x)
+            }
+            FunctionCode::GetCommEventCounter => {
+                cursor.expect_empty()?;
+                Ok(Request::GetCommEventCounter)
+            }
+            FunctionCode::GetCommEventLog => {
+                cursor.expect_empty()?;
+                Ok(Request::GetCommEventLog)
+            }
+            FunctionCode::ReportServerId => {
+                cursor.expect_empty()?;
+                Ok(Request::ReportServerId)
+            }
+            FunctionCode::ReadFileRecord => {
+                Ok(Request::ReadFileRecord(ReadFileRecords::parse(cursor)?))
+            }
+            FunctionCode::WriteFileRecord => {
+                Ok(Request::WriteFileRecord(WriteFileRecords::parse(cursor)?))
+            }
             FunctionCode::WriteMultipleCoils => {
                 let range = AddressRange::parse(cursor)?;
                 // don't care about the count, validated b/c all bytes are consumed
@@ -200,6 +372,47 @@ impl<'a> Request<'a> {
                     RegisterIterator::parse_all(range, cursor)?,
                 )))
             }
+            FunctionCode::MaskWriteRegister => {
+                let x = Request::MaskWriteRegister(MaskWriteRegister::parse(cursor)?);
+                cursor.expect_empty()?;
+                Ok(x)
+            }
+            FunctionCode::ReadFifoQueue => {
+                let x = Request::ReadFifoQueue(cursor.read_u16_be()?);
+                cursor.expect_empty()?;
+                Ok(x)
+            }
+            FunctionCode::ReadDeviceIdentification => {
+                let mei_type = cursor.read_u8()?;
+                if mei_type != crate::device_id::MEI_TYPE_READ_DEVICE_ID {
+                    return Err(AduParseError::UnknownMeiType(mei_type).into());
+                }
+                let code = ReadDeviceIdCode::parse(cursor)?;
+                let x =
+                    Request::ReadDeviceIdentification(ReadDeviceId::new(code, cursor.read_u8()?));
+                cursor.expect_empty()?;
+                Ok(x)
+            }
+            FunctionCode::ReadWriteMultipleRegisters => {
+                let read_range = AddressRange::parse(cursor)?.of_read_registers()?;
+                let write_range = AddressRange::parse(cursor)?
+                    .limited_count(limits::MAX_READ_WRITE_WRITE_REGISTERS_COUNT)?;
+                // don't care about the count, validated b/c all bytes are consumed
+                cursor.read_u8()?;
+                Ok(Request::ReadWriteMultipleRegisters(
+                    ReadWriteRegisters::new(
+                        read_range,
+                        WriteRegisters::new(
+                            write_range,
+                            RegisterIterator::parse_all(write_range, cursor)?,
+                        ),
+                    ),
+                ))
+            }
+            FunctionCode::Custom(code) => Ok(Request::CustomFunction(
+                code,
+                cursor.read_bytes(cursor.remaining())?,
+            )),
         }
     }
 }
@@ -239,6 +452,22 @@ impl std::fmt::Display for RequestDisplay<'_, '_> {
                 Request::WriteSingleRegister(request) => {
                     write!(f, " {}", request)?;
                 }
+                Request::ReadExceptionStatus => {}
+                Request::Diagnostics(request) => {
+                    write!(f, " {}", request)?;
+                }
+                Request::GetCommEventCounter => {}
+                Request::GetCommEventLog => {}
+                Request::ReportServerId => {}
+                Request::ReadFileRecord(records) => {
+                    write!(f, " count: {}", records.iter().count())?;
+                    for x in records.iter() {
+                        write!(f, "\n{}", x)?;
+                    }
+                }
+                Request::WriteFileRecord(records) => {
+                    write!(f, " {}", WriteFileRecordsDisplay::new(self.level, *records))?;
+                }
                 Request::WriteMultipleCoils(items) => {
                     write!(
                         f,
@@ -253,6 +482,30 @@ impl std::fmt::Display for RequestDisplay<'_, '_> {
                         RegisterIteratorDisplay::new(self.level, items.iterator)
                     )?;
                 }
+                Request::MaskWriteRegister(request) => {
+                    write!(f, " {}", request)?;
+                }
+                Request::ReadWriteMultipleRegisters(request) => {
+                    write!(
+                        f,
+                        " read: {} write: {}",
+                        request.read_range.get(),
+                        RegisterIteratorDisplay::new(self.level, request.write.iterator)
+                    )?;
+                }
+                Request::ReadFifoQueue(address) => {
+                    write!(f, " ptr: {:#06X}", address)?;
+                }
+                Request::ReadDeviceIdentification(request) => {
+                    write!(
+                        f,
+                        " code: {} object: {:#04X}",
+                        request.code, request.object_id
+                    )?;
+                }
+                Request::CustomFunction(_, data) => {
+                    write!(f, " {}", CustomFunctionDisplay::new(self.level, data))?;
+                }
             }
         }
 
@@ -386,4 +639,115 @@ mod tests {
             )
         }
     }
+
+    mod read_write_registers {
+        use scursor::ReadCursor;
+
+        use super::super::*;
+        use crate::error::{AduParseError, InvalidRange};
+        use crate::types::Indexed;
+
+        #[test]
+        fn fails_when_too_many_registers_written() {
+            let mut cursor = ReadCursor::new(&[0x00, 0x01, 0x00, 0x01, 0x00, 0x01, 0x00, 0x7A]);
+            let err = Request::parse(FunctionCode::ReadWriteMultipleRegisters, &mut cursor)
+                .err()
+                .unwrap();
+            assert_eq!(err, InvalidRange::CountTooLargeForType(0x7A, 0x79).into());
+        }
+
+        #[test]
+        fn fails_when_specified_byte_count_not_present() {
+            let mut cursor =
+                ReadCursor::new(&[0x00, 0x01, 0x00, 0x01, 0x00, 0x01, 0x00, 0x01, 0x02, 0xFF]);
+            let err = Request::parse(FunctionCode::ReadWriteMultipleRegisters, &mut cursor)
+                .err()
+                .unwrap();
+            assert_eq!(err, AduParseError::InsufficientBytes.into());
+        }
+
+        #[test]
+        fn can_parse_read_write_registers() {
+            let mut cursor = ReadCursor::new(&[
+                0x00, 0x01, 0x00, 0x03, 0x00, 0x05, 0x00, 0x02, 0x04, 0xCA, 0xFE, 0xBB, 0xDD,
+            ]);
+            let request = match Request::parse(
+                FunctionCode::ReadWriteMultipleRegisters,
+                &mut cursor,
+            )
+            .unwrap()
+            {
+                Request::ReadWriteMultipleRegisters(x) => x,
+                _ => panic!("bad match"),
+            };
+
+            assert_eq!(
+                request.read_range.get(),
+                AddressRange::try_from(1, 3).unwrap()
+            );
+            assert_eq!(request.write.range, AddressRange::try_from(5, 2).unwrap());
+            assert_eq!(
+                request.write.iterator.collect::<Vec<Indexed<u16>>>(),
+                vec![Indexed::new(5, 0xCAFE), Indexed::new(6, 0xBBDD)]
+            )
+        }
+    }
+
+    mod replies {
+        use super::super::*;
+        use crate::common::frame::TxId;
+
+        /// only implements the range callback so that the per-point methods can't be used
+        struct Snapshot;
+
+        impl RequestHandler for Snapshot {
+            fn read_holding_registers(
+                &self,
+                range: AddressRange,
+                dest: &mut [u16],
+            ) -> Result<(), ExceptionCode> {
+                for (value, address) in dest.iter_mut().zip(range.iter()) {
+                    *value = 0xFF00 | address;
+                }
+                Ok(())
+            }
+        }
+
+        fn reply(request: Request) -> Vec<u8> {
+            let mut writer = FrameWriter::tcp();
+            let header = FrameHeader::new_tcp_header(UnitId::new(1), TxId::new(7));
+            request
+                .get_reply(
+                    header,
+                    &mut Snapshot,
+                    None,
+                    &mut writer,
+                    DecodeLevel::nothing(),
+                )
+                .unwrap()
+                .to_vec()
+        }
+
+        #[test]
+        fn reads_registers_using_range_callback() {
+            let range = AddressRange::try_from(2, 2).unwrap();
+            assert_eq!(
+                reply(Request::ReadHoldingRegisters(
+                    range.of_read_registers().unwrap()
+                )),
+                vec![0x00, 0x07, 0x00, 0x00, 0x00, 0x07, 0x01, 0x03, 0x04, 0xFF, 0x02, 0xFF, 0x03]
+            );
+        }
+
+        #[test]
+        fn default_range_callback_uses_per_point_methods() {
+            let range = AddressRange::try_from(2, 2).unwrap();
+            assert_eq!(
+                reply(Request::ReadInputRegisters(
+                    range.of_read_registers().unwrap()
+                )),
+                vec![0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0x01, 0x84, 0x01]
+            );
+        }
+    }
 }
@@ -1,4 +1,7 @@
+use crate::device_id::ReadDeviceIdCode;
 use crate::exception::ExceptionCode;
+use crate::file_record::ReadFileRecords;
+use crate::server::DeviceIdentificationStore;
 use crate::types::{ReadBitsRange, ReadRegistersRange};
 
 pub(crate) struct BitWriter<T>
@@ -34,3 +37,166 @@ where
         Self { range, getter }
     }
 }
+
+/// writer of bits that were already read, `values` contains one value per address of the range
+pub(crate) fn bits_of(
+    range: ReadBitsRange,
+    values: &[bool],
+) -> BitWriter<impl Fn(u16) -> Result<bool, ExceptionCode> + '_> {
+    let start = range.get().start;
+    BitWriter::new(range, move |i| Ok(values[(i - start) as usize]))
+}
+
+/// writer of registers that were already read, `values` contains one value per address of the range
+pub(crate) fn registers_of(
+    range: ReadRegistersRange,
+    values: &[u16],
+) -> RegisterWriter<impl Fn(u16) -> Result<u16, ExceptionCode> + '_> {
+    let start = range.get().start;
+    RegisterWriter::new(range, move |i| Ok(values[(i - start) as usize]))
+}
+
+pub(crate) struct FileRecordWriter<'a, T>
+where
+    T: Fn(u16, u16) -> Result<u16, ExceptionCode>,
+{
+    pub(crate) records: ReadFileRecords<'a>,
+    pub(crate) getter: T,
+}
+
+impl<'a, T> FileRecordWriter<'a, T>
+where
+    T: Fn(u16, u16) -> Result<u16, ExceptionCode>,
+{
+    pub(crate) fn new(records: ReadFileRecords<'a>, getter: T) -> Self {
+        Self { records, getter }
+    }
+}
+
+pub(crate) struct FifoQueue {
+    pub(crate) values: Vec<u16>,
+}
+
+impl FifoQueue {
+    pub(crate) fn new(values: Vec<u16>) -> Result<Self, ExceptionCode> {
+        if values.len() > crate::constants::limits::MAX_FIFO_COUNT as usize {
+            tracing::warn!(
+                "FIFO queue contains {} values, maximum is {}",
+                values.len(),
+                crate::constants::limits::MAX_FIFO_COUNT
+            );
+            return Err(ExceptionCode::IllegalDataValue);
+        }
+        Ok(Self { values })
+    }
+}
+
+pub(crate) struct ExceptionStatus {
+    pub(crate) value: u8,
+}
+
+impl ExceptionStatus {
+    pub(crate) fn new(value: u8) -> Self {
+        Self { value }
+    }
+}
+
+pub(crate) struct ServerId {
+    pub(crate) data: Vec<u8>,
+}
+
+impl ServerId {
+    pub(crate) fn new(data: Vec<u8>) -> Result<Self, ExceptionCode> {
+        if data.len() > crate::constants::limits::MAX_REPORT_SERVER_ID_LENGTH {
+            tracing::warn!(
+                "server id contains {} bytes, maximum is {}",
+                data.len(),
+                crate::constants::limits::MAX_REPORT_SERVER_ID_LENGTH
+            );
+            return Err(ExceptionCode::IllegalDataValue);
+        }
+        Ok(Self { data })
+    }
+}
+
+pub(crate) struct CustomFunctionResponse {
+    pub(crate) data: Vec<u8>,
+}
+
+impl CustomFunctionResponse {
+    pub(crate) fn new(data: Vec<u8>) -> Result<Self, ExceptionCode> {
+        if data.len() > crate::constants::limits::MAX_CUSTOM_FUNCTION_DATA_LENGTH {
+            tracing::warn!(
+                "custom function response contains {} bytes, maximum is {}",
+                data.len(),
+                crate::constants::limits::MAX_CUSTOM_FUNCTION_DATA_LENGTH
+            );
+            return Err(ExceptionCode::ServerDeviceFailure);
+        }
+        Ok(Self { data })
+    }
+}
+
+pub(crate) struct DeviceIdWriter<'a> {
+    pub(crate) store: &'a DeviceIdentificationStore,
+    pub(crate) code: ReadDeviceIdCode,
+    /// first object id included in the response
+    pub(crate) first: u8,
+    /// last object id included in the response
+    pub(crate) last: u8,
+    /// object id where the next request should start if the objects did not fit
+    pub(crate) next_object_id: Option<u8>,
+}
+
+impl<'a> DeviceIdWriter<'a> {
+    /// function code, MEI type, read device id code, conformity level, more follows, next object id, number of objects
+    const HEADER_LENGTH: usize = 7;
+
+    pub(crate) fn new(
+        store: &'a DeviceIdentificationStore,
+        code: ReadDeviceIdCode,
+        object_id: u8,
+    ) -> Result<Self, ExceptionCode> {
+        if code == ReadDeviceIdCode::Individual {
+            if !store.contains(object_id) {
+                return Err(ExceptionCode::IllegalDataAddress);
+            }
+            return Ok(Self {
+                store,
+                code,
+                first: object_id,
+                last: object_id,
+                next_object_id: None,
+            });
+        }
+
+        // streaming restarts at the beginning if the requested object doesn't exist
+        let first = if object_id <= code.last_object_id() && store.contains(object_id) {
+            object_id
+        } else {
+            0
+        };
+
+        let mut remaining = crate::common::frame::constants::MAX_ADU_LENGTH - Self::HEADER_LENGTH;
+        let mut last = first;
+        let mut next_object_id = None;
+        for (id, value) in store.range(first, code.last_object_id()) {
+            // object id and length precede the value
+            let size = 2 + value.len();
+            if size > remaining {
+                next_object_id = Some(id);
+                break;
+            }
+            remaining -= size;
+            last = id;
+        }
+
+        Ok(Self {
+            store,
+            code,
+            first,
+            last,
+            next_object_id,
+        })
+    }
+}
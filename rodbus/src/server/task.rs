@@ -1,49 +1,170 @@
 use crate::common::phys::PhysLayer;
-use crate::server::{Authorization, AuthorizationHandler};
+use crate::server::{
+    AddressFilter, Authorization, AuthorizationHandler, RequestContext, SessionId, SessionInfo,
+    TlsIdentity, UnitSettings, UnitState,
+};
 use crate::{DecodeLevel, UnitId};
 
 use crate::common::frame::{
     Frame, FrameDestination, FrameHeader, FrameWriter, FramedReader, FunctionField,
 };
 use crate::common::function::FunctionCode;
+use crate::diagnostics::sub_function;
 use crate::error::*;
 use crate::exception::ExceptionCode;
-use crate::server::handler::{RequestHandler, ServerHandlerMap};
-use crate::server::request::{Request, RequestDisplay};
+use crate::maybe_async::MaybeAsync;
+use crate::server::async_handler::{format_response, AsyncResponse};
+use crate::server::diagnostics::DiagnosticCounters;
+use crate::server::request::{BroadcastRequest, Request, RequestDisplay};
 
 use scursor::ReadCursor;
+use std::any::Any;
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use tracing::Instrument;
+
+/// Maximum number of requests a session processes asynchronously at the same time. Once reached,
+/// the session stops reading requests until a reply is sent.
+const MAX_PENDING_REPLIES: usize = 16;
 
 /// Messages that can be sent to change server settings dynamically
-#[derive(Copy, Clone)]
 pub enum ServerSetting {
     ChangeDecoding(DecodeLevel),
+    AddUnit(UnitId, UnitHandler),
+    RemoveUnit(UnitId),
+    ConfigureUnit(UnitId, UnitSettings),
+    ChangeFilter(AddressFilter),
+    ChangeMaxSessions(usize),
+    #[cfg(feature = "tls")]
+    ChangeTlsConfig(crate::server::TlsServerConfig),
+    ListSessions(tokio::sync::oneshot::Sender<Vec<SessionInfo>>),
+    CloseSession(SessionId, tokio::sync::oneshot::Sender<bool>),
+}
+
+impl ServerSetting {
+    /// copy of a setting that also applies to the active sessions of a server
+    pub(crate) fn for_sessions(&self) -> Option<Self> {
+        match self {
+            Self::ChangeDecoding(level) => Some(Self::ChangeDecoding(*level)),
+            Self::AddUnit(unit_id, handler) => Some(Self::AddUnit(*unit_id, handler.clone())),
+            Self::RemoveUnit(unit_id) => Some(Self::RemoveUnit(*unit_id)),
+            Self::ConfigureUnit(unit_id, settings) => {
+                Some(Self::ConfigureUnit(*unit_id, *settings))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Handler of a unit id added to a running server
+///
+/// The type of the handler is checked when it is added since it must match the handlers the
+/// server was spawned with.
+#[derive(Clone)]
+pub struct UnitHandler(Arc<dyn Any + Send + Sync>);
+
+impl UnitHandler {
+    pub(crate) fn new<T: Any + Send + Sync>(handler: T) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    pub(crate) fn get<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+/// Reply to a request addressed to a single unit id
+pub(crate) enum Reply<'a> {
+    /// reply that was formatted immediately
+    Ready(&'a [u8]),
+    /// response computed asynchronously by the handler
+    Pending(MaybeAsync<Result<AsyncResponse, ExceptionCode>>),
 }
 
-pub(crate) struct SessionTask<T>
+/// Handlers of the unit ids answered by a session
+pub(crate) trait SessionHandlers: Clone + Send + 'static {
+    fn contains(&self, unit_id: UnitId) -> bool;
+
+    fn unit_ids(&self) -> Vec<UnitId>;
+
+    /// add or replace the handler of a unit id, returns false if it isn't of the type used by the map
+    fn add_unit(&mut self, unit_id: UnitId, handler: &UnitHandler) -> bool;
+
+    /// remove the handler of a unit id, returns false if no handler is mapped to it
+    fn remove_unit(&mut self, unit_id: UnitId) -> bool;
+
+    /// process a request addressed to the unit id of the context, returns `None` if no handler is mapped to it
+    fn process<'a>(
+        &mut self,
+        context: &RequestContext,
+        header: FrameHeader,
+        request: &Request,
+        diagnostics: Option<&mut DiagnosticCounters>,
+        writer: &'a mut FrameWriter,
+        level: DecodeLevel,
+    ) -> Option<Result<Reply<'a>, RequestError>>;
+
+    /// execute a broadcast request against the handlers of the unit ids
+    fn broadcast(
+        &mut self,
+        context: &RequestContext,
+        request: &BroadcastRequest,
+        unit_ids: &[UnitId],
+    );
+}
+
+/// Response of an asynchronous handler sent back to the session
+struct PendingReply {
+    header: FrameHeader,
+    function: FunctionCode,
+    result: Result<AsyncResponse, ExceptionCode>,
+}
+
+pub(crate) struct SessionTask<H>
 where
-    T: RequestHandler,
+    H: SessionHandlers,
 {
-    handlers: ServerHandlerMap<T>,
+    handlers: H,
     auth: AuthorizationType,
     commands: tokio::sync::mpsc::Receiver<ServerSetting>,
     writer: FrameWriter,
     reader: FramedReader,
     decode: DecodeLevel,
+    /// counters of each unit id on a serial line, TCP sessions do not support the diagnostic functions
+    diagnostics: Option<BTreeMap<UnitId, DiagnosticCounters>>,
+    /// settings of the unit ids, units without an entry use the default settings
+    units: BTreeMap<UnitId, UnitSettings>,
+    reply_tx: tokio::sync::mpsc::Sender<PendingReply>,
+    reply_rx: tokio::sync::mpsc::Receiver<PendingReply>,
+    /// number of requests processed asynchronously that haven't been answered
+    pending: usize,
+    /// id assigned by a TCP or TLS server
+    session: Option<SessionId>,
+    /// identity of the client of a TLS session
+    tls: Option<Arc<TlsIdentity>>,
 }
 
-impl<T> SessionTask<T>
+impl<H> SessionTask<H>
 where
-    T: RequestHandler,
+    H: SessionHandlers,
 {
     pub(crate) fn new(
-        handlers: ServerHandlerMap<T>,
+        handlers: H,
         auth: AuthorizationType,
         writer: FrameWriter,
         reader: FramedReader,
         commands: tokio::sync::mpsc::Receiver<ServerSetting>,
         decode: DecodeLevel,
+        diagnostics: bool,
     ) -> Self {
+        let (reply_tx, reply_rx) = tokio::sync::mpsc::channel(MAX_PENDING_REPLIES);
+        let diagnostics = diagnostics.then(|| {
+            handlers
+                .unit_ids()
+                .into_iter()
+                .map(|x| (x, DiagnosticCounters::default()))
+                .collect()
+        });
         Self {
             handlers,
             auth,
@@ -51,9 +172,68 @@ where
             writer,
             reader,
             decode,
+            diagnostics,
+            units: BTreeMap::new(),
+            reply_tx,
+            reply_rx,
+            pending: 0,
+            session: None,
+            tls: None,
         }
     }
 
+    /// identify the session in the context of the requests it receives
+    pub(crate) fn with_session(mut self, session: SessionId, tls: Option<TlsIdentity>) -> Self {
+        self.session = Some(session);
+        self.tls = tls.map(Arc::new);
+        self
+    }
+
+    /// use the settings of unit ids that were configured before the session was created
+    pub(crate) fn with_units(mut self, units: BTreeMap<UnitId, UnitSettings>) -> Self {
+        self.units = units;
+        self
+    }
+
+    fn unit_settings(&self, unit_id: UnitId) -> UnitSettings {
+        self.units.get(&unit_id).copied().unwrap_or_default()
+    }
+
+    /// counters of the unit id that a frame is addressed to
+    fn counters(&mut self, destination: FrameDestination) -> Option<&mut DiagnosticCounters> {
+        match destination {
+            FrameDestination::UnitId(unit_id) => self.diagnostics.as_mut()?.get_mut(&unit_id),
+            FrameDestination::Broadcast => None,
+        }
+    }
+
+    /// unit ids that execute broadcast requests, i.e. online unit ids not in listen only mode
+    fn broadcast_targets(&self) -> Vec<UnitId> {
+        self.handlers
+            .unit_ids()
+            .into_iter()
+            .filter(|id| self.unit_settings(*id).state != UnitState::Offline)
+            .filter(|id| {
+                !self
+                    .diagnostics
+                    .as_ref()
+                    .and_then(|x| x.get(id))
+                    .map(|x| x.is_listen_only())
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// counters of the unit ids that are present on the bus
+    fn online_counters(&mut self) -> impl Iterator<Item = (UnitId, &mut DiagnosticCounters)> {
+        let units = &self.units;
+        self.diagnostics
+            .iter_mut()
+            .flat_map(|x| x.iter_mut())
+            .filter(move |(id, _)| units.get(id).map(|x| x.state) != Some(UnitState::Offline))
+            .map(|(id, counters)| (*id, counters))
+    }
+
     async fn reply_with_error(
         &mut self,
         io: &mut PhysLayer,
@@ -76,6 +256,13 @@ where
         if header.destination != FrameDestination::Broadcast {
             let bytes = self.writer.format_ex(header, func, ex, self.decode)?;
             io.write(bytes, self.decode.physical).await?;
+            if let Some(counters) = self.counters(header.destination) {
+                counters.on_exception(ex);
+            }
+        } else {
+            for (_, counters) in self.online_counters() {
+                counters.on_no_response();
+            }
         }
         Ok(())
     }
@@ -116,13 +303,37 @@ where
 
     async fn run_one(&mut self, io: &mut PhysLayer) -> Result<(), RequestError> {
         tokio::select! {
-            frame = self.reader.next_frame(io, self.decode) => {
-                let frame = frame?;
+            frame = self.reader.next_frame(io, self.decode), if self.pending < MAX_PENDING_REPLIES => {
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        self.on_frame_error(&err);
+                        return Err(err);
+                    }
+                };
                 self.handle_frame(io, frame).await
             }
+            reply = self.reply_rx.recv() => {
+                match reply {
+                    // never happens since the session owns a sender
+                    None => Ok(()),
+                    Some(reply) => {
+                        self.pending -= 1;
+                        self.write_pending_reply(io, reply).await
+                    }
+                }
+            }
             cmd = self.commands.recv() => {
                match cmd {
-                    None => Err(crate::error::RequestError::Shutdown),
+                    None => Err(RequestError::Shutdown),
+                    Some(ServerSetting::ChangeFilter(filter)) => {
+                        if io.set_address_filter(filter) {
+                            tracing::info!("changed address filter");
+                        } else {
+                            tracing::warn!("setting doesn't apply to this type of server");
+                        }
+                        Ok(())
+                    }
                     Some(setting) => {
                         self.apply_setting(setting);
                         Ok(())
@@ -132,15 +343,174 @@ where
         }
     }
 
+    /// wait for the response in another task so that the session can process other requests
+    fn spawn_pending_reply(
+        &mut self,
+        header: FrameHeader,
+        function: FunctionCode,
+        response: MaybeAsync<Result<AsyncResponse, ExceptionCode>>,
+        settings: UnitSettings,
+    ) {
+        self.pending += 1;
+        let tx = self.reply_tx.clone();
+        let task = async move {
+            let result = response.get().await;
+            delay_response(settings).await;
+            let _ = tx
+                .send(PendingReply {
+                    header,
+                    function,
+                    result,
+                })
+                .await;
+        };
+        tokio::spawn(task.in_current_span());
+    }
+
+    async fn write_pending_reply(
+        &mut self,
+        io: &mut PhysLayer,
+        reply: PendingReply,
+    ) -> Result<(), RequestError> {
+        let bytes = format_response(
+            reply.header,
+            reply.function,
+            reply.result,
+            &mut self.writer,
+            self.decode,
+        )?;
+        io.write(bytes, self.decode.physical).await?;
+        self.on_reply_written(reply.header.destination, reply.function);
+        Ok(())
+    }
+
+    fn on_reply_written(&mut self, destination: FrameDestination, function: FunctionCode) {
+        let exception = self.writer.exception();
+        if let Some(counters) = self.counters(destination) {
+            match exception {
+                Some(ex) => counters.on_exception(ex),
+                // fetching the event counter is not counted as an event
+                None => counters.on_response(function != FunctionCode::GetCommEventCounter),
+            }
+        }
+    }
+
     fn apply_setting(&mut self, setting: ServerSetting) {
         match setting {
             ServerSetting::ChangeDecoding(level) => {
                 self.decode = level;
             }
+            ServerSetting::AddUnit(unit_id, handler) => {
+                if self.handlers.add_unit(unit_id, &handler) {
+                    if let Some(diagnostics) = self.diagnostics.as_mut() {
+                        diagnostics.entry(unit_id).or_default();
+                    }
+                } else {
+                    tracing::warn!(
+                        "handler of unit id {} doesn't match the type of the server's handlers",
+                        unit_id
+                    );
+                }
+            }
+            ServerSetting::RemoveUnit(unit_id) => {
+                self.handlers.remove_unit(unit_id);
+                if let Some(diagnostics) = self.diagnostics.as_mut() {
+                    diagnostics.remove(&unit_id);
+                }
+                self.units.remove(&unit_id);
+            }
+            ServerSetting::ConfigureUnit(unit_id, settings) => {
+                self.units.insert(unit_id, settings);
+            }
+            ServerSetting::ChangeFilter(_) | ServerSetting::ChangeMaxSessions(_) => {
+                tracing::warn!("setting doesn't apply to this type of server");
+            }
+            #[cfg(feature = "tls")]
+            ServerSetting::ChangeTlsConfig(_) => {
+                tracing::warn!("setting doesn't apply to this type of server");
+            }
+            // servers that don't accept connections have no sessions
+            ServerSetting::ListSessions(reply) => {
+                let _ = reply.send(Vec::new());
+            }
+            ServerSetting::CloseSession(_, reply) => {
+                let _ = reply.send(false);
+            }
+        }
+    }
+
+    fn on_frame_error(&mut self, err: &RequestError) {
+        if let RequestError::BadFrame(
+            FrameParseError::CrcValidationFailure(_, _)
+            | FrameParseError::LrcValidationFailure(_, _),
+        ) = err
+        {
+            for (_, counters) in self.online_counters() {
+                counters.on_communication_error();
+            }
+        }
+    }
+
+    /// In listen only mode, requests are monitored but only a restart communications request
+    /// is processed. The response to this request is suppressed.
+    fn handle_listen_only(counters: &mut DiagnosticCounters, frame: &Frame) {
+        let mut cursor = ReadCursor::new(frame.payload());
+        let request = match cursor.read_u8().ok().and_then(FunctionCode::get) {
+            Some(function) => Request::parse(function, &mut cursor).ok(),
+            None => None,
+        };
+
+        match request {
+            Some(Request::Diagnostics(x))
+                if x.sub_function == sub_function::RESTART_COMMUNICATIONS =>
+            {
+                tracing::info!("restarting communications, leaving listen only mode");
+                if counters.diagnostics(x).is_err() {
+                    counters.on_no_response();
+                }
+            }
+            _ => counters.on_no_response(),
         }
     }
 
     async fn handle_frame(&mut self, io: &mut PhysLayer, frame: Frame) -> Result<(), RequestError> {
+        let destination = frame.header.destination;
+
+        if self.diagnostics.is_some() {
+            let mut listen_only = false;
+            for (unit_id, counters) in self.online_counters() {
+                let addressed = match destination {
+                    FrameDestination::UnitId(x) => x == unit_id,
+                    FrameDestination::Broadcast => true,
+                };
+                counters.on_message(addressed, destination.is_broadcast());
+                if addressed && counters.is_listen_only() {
+                    Self::handle_listen_only(counters, &frame);
+                    listen_only = !destination.is_broadcast();
+                }
+            }
+            if listen_only {
+                return Ok(());
+            }
+        }
+
+        // if no addresses match, then don't respond
+        let settings = match destination {
+            FrameDestination::UnitId(unit_id) => {
+                if !self.handlers.contains(unit_id) {
+                    tracing::warn!("received frame for unmapped unit id: {}", unit_id);
+                    return Ok(());
+                }
+                let settings = self.unit_settings(unit_id);
+                if settings.state == UnitState::Offline {
+                    tracing::info!("ignoring frame for offline unit id: {}", unit_id);
+                    return Ok(());
+                }
+                settings
+            }
+            FrameDestination::Broadcast => UnitSettings::default(),
+        };
+
         let mut cursor = ReadCursor::new(frame.payload());
 
         let function = match cursor.read_u8() {
@@ -148,7 +518,7 @@ where
                 tracing::warn!("received an empty frame");
                 return Ok(());
             }
-            Ok(value) => match FunctionCode::get(value) {
+            Ok(value) => match FunctionCode::get(value).or_else(|| FunctionCode::custom(value)) {
                 Some(x) => x,
                 None => {
                     tracing::warn!("received unknown function code: {}", value);
@@ -184,9 +554,9 @@ where
         // check authorization
         if let Authorization::Deny = self
             .auth
-            .is_authorized(frame.header.destination.into_unit_id(), &request)
+            .is_authorized(destination.into_unit_id(), &request)
         {
-            if !frame.header.destination.is_broadcast() {
+            if !destination.is_broadcast() {
                 self.reply_with_error(
                     io,
                     frame.header,
@@ -198,32 +568,85 @@ where
             return Ok(());
         }
 
-        // if no addresses match, then don't respond
-        match frame.header.destination {
+        let context = RequestContext::new(
+            destination.into_unit_id(),
+            self.session,
+            io.peer_addr(),
+            io.transport(),
+            self.tls.clone(),
+        );
+
+        match destination {
             FrameDestination::UnitId(unit_id) => {
-                let handler = match self.handlers.get(unit_id) {
-                    None => {
-                        tracing::warn!("received frame for unmapped unit id: {}", unit_id);
+                if let (Some(counters), Request::Diagnostics(x)) =
+                    (self.counters(destination), &request)
+                {
+                    if x.sub_function == sub_function::FORCE_LISTEN_ONLY_MODE {
+                        // no response is returned when entering listen only mode
+                        tracing::info!("entering listen only mode");
+                        counters.force_listen_only();
+                        counters.on_no_response();
                         return Ok(());
                     }
-                    Some(handler) => handler,
-                };
+                }
                 // get the reply data (or exception reply)
-                let reply: &[u8] = request.get_reply(
+                let reply = match self.handlers.process(
+                    &context,
                     frame.header,
-                    handler.lock().unwrap().as_mut(),
+                    &request,
+                    self.diagnostics.as_mut().and_then(|x| x.get_mut(&unit_id)),
                     &mut self.writer,
                     self.decode,
-                )?;
-                io.write(reply, self.decode.physical).await?;
+                ) {
+                    Some(x) => x?,
+                    None => return Ok(()),
+                };
+                if settings.state == UnitState::Silent {
+                    // the request is processed, but the response is discarded
+                    if let Reply::Pending(response) = reply {
+                        tokio::spawn(response.get());
+                    }
+                    if let Some(counters) = self.counters(destination) {
+                        counters.on_no_response();
+                    }
+                    return Ok(());
+                }
+                match reply {
+                    Reply::Ready(reply) => {
+                        delay_response(settings).await;
+                        io.write(reply, self.decode.physical).await?;
+                        self.on_reply_written(destination, function);
+                    }
+                    // datagrams are answered to the sender of the last one and frames without
+                    // a transaction id can't be matched to their request, so these requests
+                    // can't be answered out of order
+                    Reply::Pending(response)
+                        if io.is_datagram() || frame.header.tx_id.is_none() =>
+                    {
+                        let reply = PendingReply {
+                            header: frame.header,
+                            function,
+                            result: response.get().await,
+                        };
+                        delay_response(settings).await;
+                        self.write_pending_reply(io, reply).await?;
+                    }
+                    Reply::Pending(response) => {
+                        self.spawn_pending_reply(frame.header, function, response, settings);
+                    }
+                }
             }
             FrameDestination::Broadcast => match request.into_broadcast_request() {
                 None => {
                     tracing::warn!("broadcast is not supported for {}", function);
                 }
                 Some(request) => {
-                    for handler in self.handlers.iter_mut() {
-                        request.execute(handler.lock().unwrap().as_mut());
+                    let targets = self.broadcast_targets();
+                    self.handlers.broadcast(&context, &request, &targets);
+                    for (_, counters) in self.online_counters() {
+                        if !counters.is_listen_only() {
+                            counters.on_no_response();
+                        }
                     }
                 }
             },
@@ -233,6 +656,13 @@ where
     }
 }
 
+/// wait before sending the response of a unit id
+async fn delay_response(settings: UnitSettings) {
+    if !settings.response_delay.is_zero() {
+        tokio::time::sleep(settings.response_delay).await;
+    }
+}
+
 /// Determines how authorization of user defined requests are handled
 pub(crate) enum AuthorizationType {
     /// Requests do not require authorization checks (TCP / RTU)
@@ -256,6 +686,34 @@ impl AuthorizationType {
                 handler.read_holding_registers(unit_id, x.inner, role)
             }
             Request::ReadInputRegisters(x) => handler.read_input_registers(unit_id, x.inner, role),
+            Request::ReadFifoQueue(x) => handler.read_fifo_queue(unit_id, *x, role),
+            Request::ReadDeviceIdentification(x) => {
+                handler.read_device_identification(unit_id, x.code, x.object_id, role)
+            }
+            Request::ReadExceptionStatus => handler.read_exception_status(unit_id, role),
+            Request::Diagnostics(x) => handler.diagnostics(unit_id, x.sub_function, role),
+            Request::GetCommEventCounter => handler.get_comm_event_counter(unit_id, role),
+            Request::GetCommEventLog => handler.get_comm_event_log(unit_id, role),
+            Request::ReportServerId => handler.report_server_id(unit_id, role),
+            Request::ReadFileRecord(x) => {
+                if x.iter()
+                    .all(|r| handler.read_file_record(unit_id, r, role) == Authorization::Allow)
+                {
+                    Authorization::Allow
+                } else {
+                    Authorization::Deny
+                }
+            }
+            Request::WriteFileRecord(x) => {
+                if x.iter().all(|(file, values)| {
+                    handler.write_file_record(unit_id, file, values.range, role)
+                        == Authorization::Allow
+                }) {
+                    Authorization::Allow
+                } else {
+                    Authorization::Deny
+                }
+            }
             Request::WriteSingleCoil(x) => handler.write_single_coil(unit_id, x.index, role),
             Request::WriteSingleRegister(x) => {
                 handler.write_single_register(unit_id, x.index, role)
@@ -264,6 +722,14 @@ impl AuthorizationType {
             Request::WriteMultipleRegisters(x) => {
                 handler.write_multiple_registers(unit_id, x.range, role)
             }
+            Request::MaskWriteRegister(x) => handler.mask_write_register(unit_id, x.address, role),
+            Request::ReadWriteMultipleRegisters(x) => handler.read_write_multiple_registers(
+                unit_id,
+                x.read_range.get(),
+                x.write.range,
+                role,
+            ),
+            Request::CustomFunction(x, _) => handler.custom_function(unit_id, *x, role),
         }
     }
 
@@ -284,3 +750,276 @@ impl AuthorizationType {
         }
     }
 }
+
+#[cfg(all(test, feature = "serial"))]
+mod tests {
+    use super::*;
+    use crate::common::traits::{Loggable, Serialize};
+    use crate::diagnostics::DiagnosticsRequest;
+    use crate::server::{RequestHandler, ServerHandlerMap, ServerHandlerType};
+    use crate::types::{AddressRange, Indexed};
+
+    use sfio_tokio_mock_io::Event;
+    use std::time::Duration;
+
+    /// answers the address of a holding register as its value and records the last write
+    #[derive(Default)]
+    struct Addresses {
+        written: Option<Indexed<u16>>,
+    }
+
+    impl RequestHandler for Addresses {
+        fn read_holding_register(&self, address: u16) -> Result<u16, ExceptionCode> {
+            Ok(address)
+        }
+
+        fn write_single_register(&mut self, value: Indexed<u16>) -> Result<(), ExceptionCode> {
+            self.written = Some(value);
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        io: sfio_tokio_mock_io::Handle,
+        commands: tokio::sync::mpsc::Sender<ServerSetting>,
+        units: Vec<ServerHandlerType<Addresses>>,
+    }
+
+    impl Fixture {
+        /// spawn an RTU session answering unit ids 1, 2 and 3
+        fn new(settings: &[(u8, UnitSettings)]) -> Self {
+            let units: Vec<_> = (0..3).map(|_| Addresses::default().wrap()).collect();
+            let mut handlers = ServerHandlerMap::new();
+            for (unit_id, handler) in (1..=3).zip(&units) {
+                handlers.add(UnitId::new(unit_id), handler.clone());
+            }
+            let (tx, rx) = tokio::sync::mpsc::channel(8);
+            let mut session = SessionTask::new(
+                handlers,
+                AuthorizationType::None,
+                FrameWriter::rtu(),
+                FramedReader::rtu_request(Default::default()),
+                rx,
+                DecodeLevel::nothing(),
+                true,
+            );
+            for (unit_id, settings) in settings {
+                session.apply_setting(ServerSetting::ConfigureUnit(
+                    UnitId::new(*unit_id),
+                    *settings,
+                ));
+            }
+
+            let (io, handle) = sfio_tokio_mock_io::mock();
+            tokio::spawn(async move {
+                let mut phys = PhysLayer::new_mock(io);
+                session.run(&mut phys).await
+            });
+
+            Self {
+                io: handle,
+                commands: tx,
+                units,
+            }
+        }
+
+        /// last value written to one of the unit ids 1, 2 and 3
+        fn written(&self, unit_id: u8) -> Option<Indexed<u16>> {
+            self.units[unit_id as usize - 1].lock().unwrap().written
+        }
+
+        async fn send<T: Serialize + Loggable>(
+            &mut self,
+            unit_id: u8,
+            function: FunctionCode,
+            payload: &T,
+        ) {
+            self.send_to(FrameDestination::new_unit_id(unit_id), function, payload)
+                .await;
+        }
+
+        async fn send_to<T: Serialize + Loggable>(
+            &mut self,
+            destination: FrameDestination,
+            function: FunctionCode,
+            payload: &T,
+        ) {
+            let mut writer = FrameWriter::rtu();
+            let header = FrameHeader::new_rtu_header(destination);
+            let frame = writer
+                .format_request(header, function, payload, DecodeLevel::nothing())
+                .unwrap();
+            self.io.read(frame);
+            assert_eq!(self.io.next_event().await, Event::Read);
+        }
+
+        async fn change(&mut self, setting: ServerSetting) {
+            if self.commands.send(setting).await.is_err() {
+                panic!("session stopped");
+            }
+            // let the session apply the setting before the next frame is read
+            tokio::task::yield_now().await;
+        }
+
+        async fn read_register(&mut self, unit_id: u8, address: u16) {
+            let range = AddressRange::try_from(address, 1).unwrap();
+            self.send(unit_id, FunctionCode::ReadHoldingRegisters, &range)
+                .await;
+        }
+
+        async fn read_counter(&mut self, unit_id: u8, sub_function: u16) -> u16 {
+            self.send(
+                unit_id,
+                FunctionCode::Diagnostics,
+                &DiagnosticsRequest::new(sub_function, 0),
+            )
+            .await;
+            match self.io.next_event().await {
+                Event::Write(bytes) => {
+                    assert_eq!(bytes[0], unit_id);
+                    u16::from_be_bytes([bytes[4], bytes[5]])
+                }
+                x => panic!("unexpected event: {:?}", x),
+            }
+        }
+
+        async fn expect_register(&mut self, unit_id: u8, value: u16) {
+            match self.io.next_event().await {
+                Event::Write(bytes) => {
+                    assert_eq!(&bytes[0..3], &[unit_id, 0x03, 0x02]);
+                    assert_eq!(u16::from_be_bytes([bytes[3], bytes[4]]), value);
+                }
+                x => panic!("unexpected event: {:?}", x),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn units_are_answered_using_their_settings() {
+        let delay = Duration::from_millis(50);
+        let mut fixture = Fixture::new(&[
+            (1, UnitSettings::new(delay, UnitState::Online)),
+            (2, UnitSettings::new(Duration::ZERO, UnitState::Silent)),
+            (3, UnitSettings::new(Duration::ZERO, UnitState::Offline)),
+        ]);
+
+        let start = tokio::time::Instant::now();
+        fixture.read_register(1, 7).await;
+        fixture.expect_register(1, 7).await;
+        assert!(start.elapsed() >= delay);
+
+        // neither unit answers, so the next write is the response to unit 1
+        fixture.read_register(2, 8).await;
+        fixture.read_register(3, 9).await;
+        fixture.read_register(1, 10).await;
+        fixture.expect_register(1, 10).await;
+        assert!(fixture.io.pop_event().is_none());
+    }
+
+    #[tokio::test]
+    async fn each_unit_has_its_own_diagnostic_counters() {
+        let mut fixture = Fixture::new(&[
+            (2, UnitSettings::new(Duration::ZERO, UnitState::Silent)),
+            (3, UnitSettings::new(Duration::ZERO, UnitState::Offline)),
+        ]);
+
+        fixture.read_register(1, 0).await;
+        fixture.expect_register(1, 0).await;
+        fixture.read_register(2, 0).await;
+        fixture.read_register(3, 0).await;
+
+        // every unit on the bus counts all of the messages, including the current one
+        assert_eq!(
+            fixture
+                .read_counter(1, sub_function::BUS_MESSAGE_COUNT)
+                .await,
+            4
+        );
+        assert_eq!(
+            fixture
+                .read_counter(1, sub_function::SERVER_MESSAGE_COUNT)
+                .await,
+            3
+        );
+
+        for unit_id in [2, 3] {
+            fixture
+                .change(ServerSetting::ConfigureUnit(
+                    UnitId::new(unit_id),
+                    UnitSettings::default(),
+                ))
+                .await;
+        }
+
+        assert_eq!(
+            fixture
+                .read_counter(2, sub_function::SERVER_NO_RESPONSE_COUNT)
+                .await,
+            1
+        );
+        // the offline unit didn't count the messages that preceded this request
+        assert_eq!(
+            fixture
+                .read_counter(3, sub_function::BUS_MESSAGE_COUNT)
+                .await,
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn broadcasts_skip_offline_and_listen_only_units() {
+        let mut fixture =
+            Fixture::new(&[(3, UnitSettings::new(Duration::ZERO, UnitState::Offline))]);
+
+        fixture
+            .send(
+                2,
+                FunctionCode::Diagnostics,
+                &DiagnosticsRequest::new(sub_function::FORCE_LISTEN_ONLY_MODE, 0),
+            )
+            .await;
+        fixture
+            .send_to(
+                FrameDestination::Broadcast,
+                FunctionCode::WriteSingleRegister,
+                &Indexed::new(4, 0xCAFE),
+            )
+            .await;
+
+        // the session processes frames in order, so the broadcast was executed once unit 1 answers
+        fixture.read_register(1, 0).await;
+        fixture.expect_register(1, 0).await;
+        assert_eq!(fixture.written(1), Some(Indexed::new(4, 0xCAFE)));
+        assert_eq!(fixture.written(2), None);
+        assert_eq!(fixture.written(3), None);
+        assert!(fixture.io.pop_event().is_none());
+    }
+
+    #[tokio::test]
+    async fn units_can_be_added_and_removed() {
+        let mut fixture = Fixture::new(&[]);
+
+        fixture
+            .change(ServerSetting::RemoveUnit(UnitId::new(1)))
+            .await;
+        // handlers of another type are ignored
+        fixture
+            .change(ServerSetting::AddUnit(
+                UnitId::new(4),
+                UnitHandler::new(Arc::new(0u8)),
+            ))
+            .await;
+        fixture.read_register(1, 0).await;
+        fixture.read_register(4, 0).await;
+
+        fixture
+            .change(ServerSetting::AddUnit(
+                UnitId::new(5),
+                UnitHandler::new(Addresses::default().wrap()),
+            ))
+            .await;
+        fixture.read_register(5, 3).await;
+        fixture.expect_register(5, 3).await;
+        assert!(fixture.io.pop_event().is_none());
+    }
+}
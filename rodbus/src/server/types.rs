@@ -1,4 +1,5 @@
-use crate::types::{AddressRange, BitIterator, RegisterIterator};
+use crate::device_id::ReadDeviceIdCode;
+use crate::types::{AddressRange, BitIterator, ReadRegistersRange, RegisterIterator};
 
 /// Request to write coils received by the server
 #[derive(Debug, Copy, Clone)]
@@ -29,3 +30,161 @@ impl<'a> WriteRegisters<'a> {
         Self { range, iterator }
     }
 }
+
+/// Request to write and then read registers received by the server
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ReadWriteRegisters<'a> {
+    /// range of holding registers to read after the write
+    pub(crate) read_range: ReadRegistersRange,
+    /// registers to write
+    pub(crate) write: WriteRegisters<'a>,
+}
+
+impl<'a> ReadWriteRegisters<'a> {
+    pub(crate) fn new(read_range: ReadRegistersRange, write: WriteRegisters<'a>) -> Self {
+        Self { read_range, write }
+    }
+}
+
+/// Read device identification request received by the server
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct ReadDeviceId {
+    /// access type
+    pub(crate) code: ReadDeviceIdCode,
+    /// first object to stream or the individual object to read
+    pub(crate) object_id: u8,
+}
+
+impl ReadDeviceId {
+    pub(crate) fn new(code: ReadDeviceIdCode, object_id: u8) -> Self {
+        Self { code, object_id }
+    }
+}
+
+/// State of a unit id answered by a server
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum UnitState {
+    /// Requests are processed and answered
+    #[default]
+    Online,
+    /// Requests are processed but never answered, e.g. to test the timeouts of the client
+    Silent,
+    /// The unit id behaves as if it wasn't present on the bus. Requests are ignored and
+    /// the diagnostic counters of the unit aren't updated.
+    Offline,
+}
+
+/// Settings of a unit id answered by a server, see [`ServerHandle::configure_unit`](crate::server::ServerHandle::configure_unit)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct UnitSettings {
+    /// Delay before the response is sent, e.g. to emulate the processing time of a serial device
+    pub response_delay: std::time::Duration,
+    /// Whether the unit id answers requests
+    pub state: UnitState,
+}
+
+impl UnitSettings {
+    /// Create settings from a response delay and a state
+    pub fn new(response_delay: std::time::Duration, state: UnitState) -> Self {
+        Self {
+            response_delay,
+            state,
+        }
+    }
+}
+
+/// Identifier assigned by a TCP or TLS server to each connection it accepts
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SessionId(u128);
+
+impl SessionId {
+    pub(crate) fn new(value: u128) -> Self {
+        Self(value)
+    }
+
+    /// Value of the identifier, as it appears in the logs of the server
+    pub fn value(&self) -> u128 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for SessionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Active session of a TCP or TLS server, see [`ServerHandle::sessions`](crate::server::ServerHandle::sessions)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    /// Identifier of the session
+    pub id: SessionId,
+    /// Address of the client
+    pub peer: std::net::SocketAddr,
+}
+
+/// Transport over which a server receives requests
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TransportKind {
+    /// TCP connection, using either the MBAP header or RTU framing
+    Tcp,
+    /// TLS connection
+    Tls,
+    /// UDP datagrams
+    Udp,
+    /// Serial port
+    Serial,
+}
+
+/// Identity of the client of a TLS session taken from its certificate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsIdentity {
+    /// Modbus role of the client, `None` if the certificate doesn't contain the role extension
+    pub role: Option<String>,
+    /// Subject of the certificate, e.g. `O=Acme, CN=client`
+    pub subject: Option<String>,
+}
+
+impl TlsIdentity {
+    /// Create an identity from a role and a subject
+    pub fn new(role: Option<String>, subject: Option<String>) -> Self {
+        Self { role, subject }
+    }
+}
+
+/// Details of the client that sent a request
+///
+/// See [`RequestHandler::begin_request`](crate::server::RequestHandler::begin_request) and
+/// [`AsyncRequestHandler`](crate::server::AsyncRequestHandler).
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    /// Unit id the request is addressed to, [`UnitId::broadcast`](crate::UnitId::broadcast) for broadcast requests
+    pub unit_id: crate::UnitId,
+    /// Session of the TCP or TLS server that received the request
+    pub session: Option<SessionId>,
+    /// Address of the client, `None` for serial servers
+    pub peer: Option<std::net::SocketAddr>,
+    /// Transport over which the request was received
+    pub transport: TransportKind,
+    /// Identity of the client of a TLS session
+    pub tls: Option<std::sync::Arc<TlsIdentity>>,
+}
+
+impl RequestContext {
+    pub(crate) fn new(
+        unit_id: crate::UnitId,
+        session: Option<SessionId>,
+        peer: Option<std::net::SocketAddr>,
+        transport: TransportKind,
+        tls: Option<std::sync::Arc<TlsIdentity>>,
+    ) -> Self {
+        Self {
+            unit_id,
+            session,
+            peer,
+            transport,
+            tls,
+        }
+    }
+}
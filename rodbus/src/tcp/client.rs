@@ -6,7 +6,7 @@ use crate::decode::DecodeLevel;
 
 use crate::client::message::Command;
 use crate::client::task::{ClientLoop, SessionError, StateChange};
-use crate::common::frame::{FrameWriter, FramedReader};
+use crate::common::frame::TcpFraming;
 use crate::error::Shutdown;
 use crate::retry::RetryStrategy;
 
@@ -17,11 +17,20 @@ pub(crate) fn spawn_tcp_channel(
     host: HostAddr,
     max_queued_requests: usize,
     connect_retry: Box<dyn RetryStrategy>,
+    framing: TcpFraming,
+    max_in_flight: usize,
     decode: DecodeLevel,
     listener: Box<dyn Listener<ClientState>>,
 ) -> Channel {
-    let (handle, task) =
-        create_tcp_channel(host, max_queued_requests, connect_retry, decode, listener);
+    let (handle, task) = create_tcp_channel(
+        host,
+        max_queued_requests,
+        connect_retry,
+        framing,
+        max_in_flight,
+        decode,
+        listener,
+    );
     tokio::spawn(task);
     handle
 }
@@ -30,6 +39,8 @@ pub(crate) fn create_tcp_channel(
     host: HostAddr,
     max_queued_requests: usize,
     connect_retry: Box<dyn RetryStrategy>,
+    framing: TcpFraming,
+    max_in_flight: usize,
     decode: DecodeLevel,
     listener: Box<dyn Listener<ClientState>>,
 ) -> (Channel, impl std::future::Future<Output = ()>) {
@@ -40,6 +51,8 @@ pub(crate) fn create_tcp_channel(
             rx,
             TcpTaskConnectionHandler::Tcp,
             connect_retry,
+            framing,
+            max_in_flight,
             decode,
             listener,
         )
@@ -79,19 +92,29 @@ pub(crate) struct TcpChannelTask {
 }
 
 impl TcpChannelTask {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         host: HostAddr,
         rx: Receiver<Command>,
         connection_handler: TcpTaskConnectionHandler,
         connect_retry: Box<dyn RetryStrategy>,
+        framing: TcpFraming,
+        max_in_flight: usize,
         decode: DecodeLevel,
         listener: Box<dyn Listener<ClientState>>,
     ) -> Self {
+        // only MBAP has a transaction id to match responses to requests
+        let max_in_flight = match framing {
+            TcpFraming::Mbap => max_in_flight,
+            #[cfg(feature = "serial")]
+            TcpFraming::Rtu => 1,
+        };
         Self {
             host,
             connect_retry,
             connection_handler,
-            client_loop: ClientLoop::new(rx, FrameWriter::tcp(), FramedReader::tcp(), decode),
+            client_loop: ClientLoop::new(rx, framing.writer(), framing.response_reader(), decode)
+                .with_max_in_flight(max_in_flight),
             listener,
         }
     }
@@ -2,13 +2,13 @@ use std::collections::BTreeMap;
 
 use tracing::Instrument;
 
-use crate::common::frame::{FrameWriter, FramedReader};
+use crate::common::frame::TcpFraming;
 use crate::common::phys::PhysLayer;
 use crate::decode::DecodeLevel;
-use crate::server::handler::{RequestHandler, ServerHandlerMap};
-use crate::server::task::{AuthorizationType, ServerSetting};
+use crate::server::task::{AuthorizationType, ServerSetting, SessionHandlers};
 
-use crate::server::AddressFilter;
+use crate::server::{AddressFilter, SessionId, SessionInfo, TlsIdentity, UnitSettings};
+use crate::UnitId;
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
 
@@ -16,55 +16,102 @@ use tokio::net::TcpListener;
 use crate::server::AuthorizationHandler;
 
 /// event sent back to the server task when a session ends
-struct SessionClose(u128);
+struct SessionClose(SessionId);
+
+struct SessionRecord {
+    peer: SocketAddr,
+    sender: tokio::sync::mpsc::Sender<ServerSetting>,
+}
 
 struct SessionTracker {
     max_sessions: usize,
     id: u128,
-    sessions: BTreeMap<u128, tokio::sync::mpsc::Sender<ServerSetting>>,
+    sessions: BTreeMap<SessionId, SessionRecord>,
 }
 
 impl SessionTracker {
     fn new(max_sessions: usize) -> SessionTracker {
-        let max_sessions = if max_sessions == 0 {
+        Self {
+            max_sessions: Self::check_max_sessions(max_sessions),
+            id: 0,
+            sessions: BTreeMap::new(),
+        }
+    }
+
+    fn check_max_sessions(max_sessions: usize) -> usize {
+        if max_sessions == 0 {
             tracing::warn!("Max sessions to 0, defaulting to 1");
             1
         } else {
             max_sessions
-        };
-        Self {
-            max_sessions,
-            id: 0,
-            sessions: BTreeMap::new(),
         }
     }
 
-    fn get_next_id(&mut self) -> u128 {
+    fn get_next_id(&mut self) -> SessionId {
         let ret = self.id;
         self.id += 1;
-        ret
+        SessionId::new(ret)
     }
 
-    pub(crate) fn add(&mut self, sender: tokio::sync::mpsc::Sender<ServerSetting>) -> u128 {
-        if self.sessions.len() >= self.max_sessions {
-            if let Some(oldest) = self.sessions.keys().next().copied() {
+    pub(crate) fn add(
+        &mut self,
+        peer: SocketAddr,
+        sender: tokio::sync::mpsc::Sender<ServerSetting>,
+    ) -> SessionId {
+        self.close_oldest(self.max_sessions - 1);
+
+        let id = self.get_next_id();
+        self.sessions.insert(id, SessionRecord { peer, sender });
+        id
+    }
+
+    pub(crate) fn set_max_sessions(&mut self, max_sessions: usize) {
+        self.max_sessions = Self::check_max_sessions(max_sessions);
+        self.close_oldest(self.max_sessions);
+    }
+
+    /// close the oldest sessions until no more than `count` sessions remain
+    fn close_oldest(&mut self, count: usize) {
+        while self.sessions.len() > count {
+            if let Some((oldest, _)) = self.sessions.pop_first() {
+                // when the record drops, and there are no more senders,
+                // the other end will stop the task
                 tracing::warn!(
                     "exceeded max connections, closing oldest session: {}",
                     oldest
                 );
-                // when the record drops, and there are no more senders,
-                // the other end will stop the task
-                self.sessions.remove(&oldest);
             }
         }
+    }
 
-        let id = self.get_next_id();
-        self.sessions.insert(id, sender);
-        id
+    /// close the sessions of the clients that no longer match the filter
+    pub(crate) fn close_unmatched(&mut self, filter: &AddressFilter) {
+        self.sessions.retain(|id, record| {
+            let matches = filter.matches(record.peer.ip());
+            if !matches {
+                tracing::warn!(
+                    "IP address {:?} does not match filter {:?}, closing session: {}",
+                    record.peer.ip(),
+                    filter,
+                    id
+                );
+            }
+            matches
+        });
+    }
+
+    pub(crate) fn sessions(&self) -> Vec<SessionInfo> {
+        self.sessions
+            .iter()
+            .map(|(id, record)| SessionInfo {
+                id: *id,
+                peer: record.peer,
+            })
+            .collect()
     }
 
-    pub(crate) fn remove(&mut self, id: u128) {
-        self.sessions.remove(&id);
+    pub(crate) fn remove(&mut self, id: SessionId) -> bool {
+        self.sessions.remove(&id).is_some()
     }
 }
 
@@ -82,9 +129,9 @@ impl TcpServerConnectionHandler {
     async fn handle(
         &mut self,
         socket: tokio::net::TcpStream,
-    ) -> Result<(PhysLayer, AuthorizationType), String> {
+    ) -> Result<(PhysLayer, AuthorizationType, Option<TlsIdentity>), String> {
         match self {
-            Self::Tcp => Ok((PhysLayer::new_tcp(socket), AuthorizationType::None)),
+            Self::Tcp => Ok((PhysLayer::new_tcp(socket), AuthorizationType::None, None)),
             #[cfg(feature = "tls")]
             Self::Tls(config, auth_handler) => {
                 let res = config.handle_connection(socket, auth_handler.clone()).await;
@@ -97,26 +144,30 @@ impl TcpServerConnectionHandler {
     }
 }
 
-pub(crate) struct ServerTask<T: RequestHandler> {
+pub(crate) struct ServerTask<H: SessionHandlers> {
     listener: TcpListener,
-    handlers: ServerHandlerMap<T>,
+    handlers: H,
     tracker: SessionTracker,
     connection_handler: TcpServerConnectionHandler,
+    framing: TcpFraming,
     filter: AddressFilter,
     decode: DecodeLevel,
+    /// settings of the unit ids applied to new sessions
+    units: BTreeMap<UnitId, UnitSettings>,
     tx: tokio::sync::mpsc::Sender<SessionClose>,
     rx: tokio::sync::mpsc::Receiver<SessionClose>,
 }
 
-impl<T> ServerTask<T>
+impl<H> ServerTask<H>
 where
-    T: RequestHandler,
+    H: SessionHandlers,
 {
     pub(crate) fn new(
         max_sessions: usize,
         listener: TcpListener,
-        handlers: ServerHandlerMap<T>,
+        handlers: H,
         connection_handler: TcpServerConnectionHandler,
+        framing: TcpFraming,
         filter: AddressFilter,
         decode: DecodeLevel,
     ) -> Self {
@@ -127,8 +178,10 @@ where
             handlers,
             tracker: SessionTracker::new(max_sessions),
             connection_handler,
+            framing,
             filter,
             decode,
+            units: BTreeMap::new(),
             tx,
             rx,
         }
@@ -136,17 +189,73 @@ where
 
     async fn change_setting(&mut self, setting: ServerSetting) {
         // first, change it locally so that it is applied to new sessions
-        match setting {
+        match &setting {
+            ServerSetting::ChangeFilter(filter) => {
+                tracing::info!("changed address filter to {:?}", filter);
+                self.tracker.close_unmatched(filter);
+                self.filter = filter.clone();
+            }
+            ServerSetting::ChangeMaxSessions(max_sessions) => {
+                tracing::info!("changed max sessions to {}", max_sessions);
+                self.tracker.set_max_sessions(*max_sessions);
+            }
+            #[cfg(feature = "tls")]
+            ServerSetting::ChangeTlsConfig(config) => match &mut self.connection_handler {
+                TcpServerConnectionHandler::Tcp => {
+                    tracing::warn!("setting doesn't apply to this type of server");
+                }
+                TcpServerConnectionHandler::Tls(current, _) => {
+                    tracing::info!("changed TLS configuration of new sessions");
+                    *current = config.clone();
+                }
+            },
+            ServerSetting::ListSessions(_) | ServerSetting::CloseSession(_, _) => {}
             ServerSetting::ChangeDecoding(level) => {
                 tracing::info!("changed decoding level to {:?}", level);
-                self.decode = level;
+                self.decode = *level;
+            }
+            ServerSetting::AddUnit(unit_id, handler) => {
+                if !self.handlers.add_unit(*unit_id, handler) {
+                    tracing::warn!(
+                        "handler of unit id {} doesn't match the type of the server's handlers",
+                        unit_id
+                    );
+                    return;
+                }
+                tracing::info!("added unit id {}", unit_id);
+            }
+            ServerSetting::RemoveUnit(unit_id) => {
+                self.handlers.remove_unit(*unit_id);
+                self.units.remove(unit_id);
+                tracing::info!("removed unit id {}", unit_id);
+            }
+            ServerSetting::ConfigureUnit(unit_id, settings) => {
+                tracing::info!("changed settings of unit id {} to {:?}", unit_id, settings);
+                self.units.insert(*unit_id, *settings);
             }
         }
 
-        for sender in self.tracker.sessions.values_mut() {
-            // best effort to send the setting to each session this isn't critical so we wouldn't
-            // want to slow the server down by awaiting it
-            let _ = sender.send(setting).await;
+        match setting {
+            ServerSetting::ListSessions(reply) => {
+                let _ = reply.send(self.tracker.sessions());
+            }
+            ServerSetting::CloseSession(id, reply) => {
+                // the session stops once its sender is dropped
+                let closed = self.tracker.remove(id);
+                if closed {
+                    tracing::info!("closed session: {}", id);
+                }
+                let _ = reply.send(closed);
+            }
+            setting => {
+                for record in self.tracker.sessions.values_mut() {
+                    // best effort to send the setting to each session this isn't critical so we wouldn't
+                    // want to slow the server down by awaiting it
+                    if let Some(setting) = setting.for_sessions() {
+                        let _ = record.sender.send(setting).await;
+                    }
+                }
+            }
         }
     }
 
@@ -192,7 +301,7 @@ where
 
     async fn handle(&mut self, socket: tokio::net::TcpStream, addr: SocketAddr) {
         let (tx, rx) = tokio::sync::mpsc::channel(8); // all we do is change settings, so a constant is fine
-        let id = self.tracker.add(tx);
+        let id = self.tracker.add(addr, tx);
         tracing::info!(
             "accepted connection from: {} - assigned session id: {}",
             addr,
@@ -202,7 +311,9 @@ where
         #[allow(unused_mut)]
         let mut notify_close = self.tx.clone();
         let connection_handler = self.connection_handler.clone();
+        let framing = self.framing;
         let handler_map = self.handlers.clone();
+        let units = self.units.clone();
         let decode_level = self.decode;
 
         let session = async move {
@@ -210,9 +321,12 @@ where
                 socket,
                 addr,
                 connection_handler,
+                framing,
                 decode_level,
                 handler_map,
+                units,
                 rx,
+                id,
             )
             .await;
 
@@ -230,27 +344,34 @@ where
     }
 }
 
-async fn run_session<T: RequestHandler>(
+#[allow(clippy::too_many_arguments)]
+async fn run_session<H: SessionHandlers>(
     socket: tokio::net::TcpStream,
     addr: SocketAddr,
     mut handler: TcpServerConnectionHandler,
+    framing: TcpFraming,
     decode: DecodeLevel,
-    handlers: ServerHandlerMap<T>,
+    handlers: H,
+    units: BTreeMap<UnitId, UnitSettings>,
     commands: tokio::sync::mpsc::Receiver<ServerSetting>,
+    id: SessionId,
 ) {
     match handler.handle(socket).await {
         Err(err) => {
             tracing::warn!("error from {}: {}", addr, err);
         }
-        Ok((mut phys, auth)) => {
+        Ok((mut phys, auth, tls)) => {
             let _ = crate::server::task::SessionTask::new(
                 handlers,
                 auth,
-                FrameWriter::tcp(),
-                FramedReader::tcp(),
+                framing.writer(),
+                framing.request_reader(),
                 commands,
                 decode,
+                false,
             )
+            .with_units(units)
+            .with_session(id, tls)
             .run(&mut phys)
             .await;
         }
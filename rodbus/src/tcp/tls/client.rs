@@ -8,6 +8,7 @@ use tokio_rustls::{rustls, webpki};
 use tracing::Instrument;
 
 use crate::client::{Channel, ClientState, HostAddr, Listener, RetryStrategy};
+use crate::common::frame::TcpFraming;
 use crate::common::phys::PhysLayer;
 use crate::tcp::client::{TcpChannelTask, TcpTaskConnectionHandler};
 use crate::tcp::tls::{load_certs, load_private_key, CertificateMode, MinTlsVersion, TlsError};
@@ -20,11 +21,14 @@ pub struct TlsClientConfig {
     config: Arc<rustls::ClientConfig>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn spawn_tls_channel(
     host: HostAddr,
     max_queued_requests: usize,
     connect_retry: Box<dyn RetryStrategy>,
     tls_config: TlsClientConfig,
+    framing: TcpFraming,
+    max_in_flight: usize,
     decode: DecodeLevel,
     listener: Box<dyn Listener<ClientState>>,
 ) -> Channel {
@@ -33,6 +37,8 @@ pub(crate) fn spawn_tls_channel(
         max_queued_requests,
         connect_retry,
         tls_config,
+        framing,
+        max_in_flight,
         decode,
         listener,
     );
@@ -40,11 +46,14 @@ pub(crate) fn spawn_tls_channel(
     handle
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn create_tls_channel(
     host: HostAddr,
     max_queued_requests: usize,
     connect_retry: Box<dyn RetryStrategy>,
     tls_config: TlsClientConfig,
+    framing: TcpFraming,
+    max_in_flight: usize,
     decode: DecodeLevel,
     listener: Box<dyn Listener<ClientState>>,
 ) -> (Channel, impl std::future::Future<Output = ()>) {
@@ -55,6 +64,8 @@ pub(crate) fn create_tls_channel(
             rx,
             TcpTaskConnectionHandler::Tls(tls_config),
             connect_retry,
+            framing,
+            max_in_flight,
             decode,
             listener,
         )
@@ -140,7 +151,7 @@ impl TlsClientConfig {
         let dns_name = rustls::ServerName::try_from(name).map_err(|_| TlsError::InvalidDnsName)?;
 
         Ok(Self {
-            config: std::sync::Arc::new(config),
+            config: Arc::new(config),
             dns_name,
         })
     }
@@ -8,7 +8,7 @@ use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
 
 use crate::common::phys::PhysLayer;
 use crate::server::task::AuthorizationType;
-use crate::server::AuthorizationHandler;
+use crate::server::{AuthorizationHandler, TlsIdentity};
 use crate::tcp::tls::{load_certs, load_private_key, CertificateMode, MinTlsVersion, TlsError};
 
 /// TLS configuration
@@ -75,27 +75,42 @@ impl TlsServerConfig {
         &mut self,
         socket: TcpStream,
         auth_handler: Option<Arc<dyn AuthorizationHandler>>,
-    ) -> Result<(PhysLayer, AuthorizationType), String> {
+    ) -> Result<(PhysLayer, AuthorizationType, Option<TlsIdentity>), String> {
         let connector = tokio_rustls::TlsAcceptor::from(self.inner.clone());
         match connector.accept(socket).await {
             Err(err) => Err(format!("failed to establish TLS session: {}", err)),
             Ok(stream) => {
+                // get the peer cert data
+                let parsed = stream
+                    .get_ref()
+                    .1
+                    .peer_certificates()
+                    .and_then(|x| x.first())
+                    .map(|x| rx509::x509::Certificate::parse(x.0.as_slice()));
+
+                let identity = match &parsed {
+                    Some(Ok(cert)) => Some(TlsIdentity::new(
+                        extract_modbus_role(cert).ok(),
+                        extract_subject(cert),
+                    )),
+                    _ => None,
+                };
+
                 let auth_type = match auth_handler {
                     // bare TLS mode without authz
-                    None => AuthorizationType::None,
+                    None => {
+                        if let Some(Err(err)) = &parsed {
+                            tracing::warn!(
+                                "unable to parse the client certificate, its identity isn't available: {}",
+                                err
+                            );
+                        }
+                        AuthorizationType::None
+                    }
                     // full secure modbus requires the client certificate contain a role
                     Some(handler) => {
-                        // get the peer cert data
-                        let peer_cert = stream
-                            .get_ref()
-                            .1
-                            .peer_certificates()
-                            .and_then(|x| x.first())
+                        let parsed = parsed
                             .ok_or_else(|| "No peer certificate".to_string())?
-                            .0
-                            .as_slice();
-
-                        let parsed = rx509::x509::Certificate::parse(peer_cert)
                             .map_err(|err| format!("ASNError: {}", err))?;
                         let role =
                             extract_modbus_role(&parsed).map_err(|err| format!("{}", err))?;
@@ -107,7 +122,7 @@ impl TlsServerConfig {
 
                 let layer = PhysLayer::new_tls(tokio_rustls::TlsStream::from(stream));
 
-                Ok((layer, auth_type))
+                Ok((layer, auth_type, identity))
             }
         }
     }
@@ -243,6 +258,23 @@ fn build_server_config(
     Ok(config)
 }
 
+/// format the subject of a certificate, e.g. "O=Acme, CN=client"
+fn extract_subject(cert: &rx509::x509::Certificate) -> Option<String> {
+    let name = cert.tbs_certificate.value.subject.parse().ok()?;
+    let components: Vec<String> = [
+        ("C", name.country_name),
+        ("ST", name.state_or_province_unit_name),
+        ("L", name.locality_name),
+        ("O", name.organization),
+        ("OU", name.organizational_unit_name),
+        ("CN", name.common_name),
+    ]
+    .iter()
+    .filter_map(|(key, value)| value.map(|value| format!("{}={}", key, value)))
+    .collect();
+    Some(components.join(", "))
+}
+
 fn extract_modbus_role(cert: &rx509::x509::Certificate) -> Result<String, rustls::Error> {
     // Parse the extensions
     let extensions = cert
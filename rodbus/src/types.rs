@@ -1,5 +1,6 @@
+use crate::codec::{ByteOrder, RegisterValue};
 use crate::decode::AppDecodeLevel;
-use crate::error::{AduParseError, InvalidRange};
+use crate::error::{AduParseError, CodecError, InvalidRange};
 
 use scursor::ReadCursor;
 
@@ -59,6 +60,19 @@ pub struct Indexed<T> {
     pub value: T,
 }
 
+/// Mask write register request
+///
+/// The resulting register value is `(current & and_mask) | (or_mask & !and_mask)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaskWriteRegister {
+    /// Address of the register
+    pub address: u16,
+    /// Mask ANDed with the current value of the register
+    pub and_mask: u16,
+    /// Mask ORed with the result for the bits cleared in `and_mask`
+    pub or_mask: u16,
+}
+
 /// Zero-copy type used to iterate over a collection of bits
 #[derive(Debug, Copy, Clone)]
 pub struct BitIterator<'a> {
@@ -85,6 +99,16 @@ pub(crate) struct RegisterIteratorDisplay<'a> {
     level: AppDecodeLevel,
 }
 
+pub(crate) struct FifoQueueDisplay<'a> {
+    values: &'a [u16],
+    level: AppDecodeLevel,
+}
+
+pub(crate) struct CustomFunctionDisplay<'a> {
+    data: &'a [u8],
+    level: AppDecodeLevel,
+}
+
 impl std::fmt::Display for UnitId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:#04X}", self.value)
@@ -126,7 +150,43 @@ impl std::fmt::Display for BitIteratorDisplay<'_> {
     }
 }
 
+impl RegisterIterator<'_> {
+    /// Decode consecutive values from the registers
+    ///
+    /// Each value is indexed by the address of its first register. Fails if the number of
+    /// registers is not a multiple of the registers used by each value.
+    pub fn decode<T: RegisterValue>(self, order: ByteOrder) -> Result<Vec<Indexed<T>>, CodecError> {
+        let start = self.range.start;
+        let registers: Vec<u16> = self.map(|x| x.value).collect();
+        let values = crate::codec::decode_values::<T>(&registers, order)?;
+        Ok((0..)
+            .step_by(T::REGISTER_COUNT as usize)
+            .zip(values)
+            .map(|(offset, value)| Indexed::new(start + offset, value))
+            .collect())
+    }
+
+    /// Decode an ASCII string stored two characters per register
+    pub fn decode_string(self, order: ByteOrder) -> Result<String, CodecError> {
+        let registers: Vec<u16> = self.map(|x| x.value).collect();
+        crate::codec::decode_string(&registers, order)
+    }
+}
+
 impl<'a> RegisterIterator<'a> {
+    /// parse the registers of the range, leaving any bytes that follow in the cursor
+    pub(crate) fn parse(
+        range: AddressRange,
+        cursor: &mut ReadCursor<'a>,
+    ) -> Result<Self, RequestError> {
+        let bytes = cursor.read_bytes(2 * (range.count as usize))?;
+        Ok(Self {
+            bytes,
+            range,
+            pos: 0,
+        })
+    }
+
     pub(crate) fn parse_all(
         range: AddressRange,
         cursor: &'a mut ReadCursor,
@@ -161,6 +221,44 @@ impl std::fmt::Display for RegisterIteratorDisplay<'_> {
     }
 }
 
+impl<'a> FifoQueueDisplay<'a> {
+    pub(crate) fn new(level: AppDecodeLevel, values: &'a [u16]) -> Self {
+        Self { values, level }
+    }
+}
+
+impl std::fmt::Display for FifoQueueDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "count: {}", self.values.len())?;
+
+        if self.level.data_values() {
+            for x in self.values {
+                write!(f, "\nvalue: {:#06X}", x)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> CustomFunctionDisplay<'a> {
+    pub(crate) fn new(level: AppDecodeLevel, data: &'a [u8]) -> Self {
+        Self { data, level }
+    }
+}
+
+impl std::fmt::Display for CustomFunctionDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "count: {}", self.data.len())?;
+
+        if self.level.data_values() {
+            crate::common::phys::format_bytes(f, self.data)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl<'a> Iterator for BitIterator<'a> {
     type Item = Indexed<bool>;
 
@@ -242,6 +340,12 @@ pub(crate) fn coil_to_u16(value: bool) -> u16 {
     }
 }
 
+/// Shorthand for the valid ranges used throughout the tests
+#[cfg(test)]
+pub(crate) fn range(start: u16, count: u16) -> AddressRange {
+    AddressRange::try_from(start, count).unwrap()
+}
+
 impl AddressRange {
     /// Create a new address range
     pub fn try_from(start: u16, count: u16) -> Result<Self, InvalidRange> {
@@ -281,12 +385,23 @@ impl AddressRange {
         })
     }
 
-    fn limited_count(self, limit: u16) -> Result<Self, InvalidRange> {
+    pub(crate) fn limited_count(self, limit: u16) -> Result<Self, InvalidRange> {
         if self.count > limit {
             return Err(InvalidRange::CountTooLargeForType(self.count, limit));
         }
         Ok(self)
     }
+
+    /// split into consecutive ranges that contain at most `max_count` addresses
+    pub(crate) fn split(self, max_count: u16) -> impl Iterator<Item = AddressRange> {
+        let end = self.start as u32 + self.count as u32;
+        (self.start as u32..end)
+            .step_by(max_count as usize)
+            .map(move |start| AddressRange {
+                start: start as u16,
+                count: std::cmp::min(max_count as u32, end - start) as u16,
+            })
+    }
 }
 
 impl std::fmt::Display for AddressRange {
@@ -329,6 +444,32 @@ impl<T> Indexed<T> {
     }
 }
 
+impl MaskWriteRegister {
+    /// Create a new mask write register request
+    pub fn new(address: u16, and_mask: u16, or_mask: u16) -> Self {
+        Self {
+            address,
+            and_mask,
+            or_mask,
+        }
+    }
+
+    /// Apply the masks to the current value of the register
+    pub fn apply(&self, current: u16) -> u16 {
+        (current & self.and_mask) | (self.or_mask & !self.and_mask)
+    }
+}
+
+impl std::fmt::Display for MaskWriteRegister {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "idx: {:#06X} and_mask: {:#06X} or_mask: {:#06X}",
+            self.address, self.and_mask, self.or_mask
+        )
+    }
+}
+
 impl std::fmt::Display for Indexed<bool> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "idx: {:#06X} value: {}", self.index, self.value as i32)
@@ -394,6 +535,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mask_write_register_applies_masks() {
+        // example from the specification
+        let request = MaskWriteRegister::new(4, 0x00F2, 0x0025);
+        assert_eq!(request.apply(0x0012), 0x0017);
+    }
+
     #[test]
     fn correctly_iterates_over_low_order_bits() {
         let mut cursor = ReadCursor::new(&[0x03]);
@@ -426,6 +574,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decodes_typed_values_from_registers() {
+        let mut cursor = ReadCursor::new(&[0x00, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04]);
+        let iterator =
+            RegisterIterator::parse_all(AddressRange::try_from(10, 4).unwrap(), &mut cursor)
+                .unwrap();
+
+        assert_eq!(
+            iterator.decode::<u32>(ByteOrder::Cdab),
+            Ok(vec![
+                Indexed::new(10, 0x0002_0001),
+                Indexed::new(12, 0x0004_0003)
+            ])
+        );
+        assert_eq!(
+            iterator.decode::<u64>(ByteOrder::Abcd),
+            Ok(vec![Indexed::new(10, 0x0001_0002_0003_0004)])
+        );
+    }
+
+    #[test]
+    fn splits_address_ranges() {
+        let range = AddressRange::try_from(0xFFF0, 0x10).unwrap();
+        assert_eq!(
+            range.split(7).collect::<Vec<_>>(),
+            vec![
+                AddressRange::try_from(0xFFF0, 7).unwrap(),
+                AddressRange::try_from(0xFFF7, 7).unwrap(),
+                AddressRange::try_from(0xFFFE, 2).unwrap(),
+            ]
+        );
+        assert_eq!(range.split(0x10).collect::<Vec<_>>(), vec![range]);
+    }
+
     #[test]
     fn broadcast_address() {
         assert_eq!(UnitId::broadcast(), UnitId::new(0x00));
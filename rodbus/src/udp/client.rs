@@ -0,0 +1,126 @@
+use tracing::Instrument;
+
+use crate::client::{Channel, ClientState, HostAddr, Listener};
+use crate::common::frame::{FrameWriter, FramedReader};
+use crate::common::phys::PhysLayer;
+use crate::decode::DecodeLevel;
+
+use crate::client::message::Command;
+use crate::client::task::{ClientLoop, SessionError, StateChange};
+use crate::error::Shutdown;
+use crate::retry::RetryStrategy;
+
+use tokio::sync::mpsc::Receiver;
+
+pub(crate) fn spawn_udp_channel(
+    host: HostAddr,
+    max_queued_requests: usize,
+    retry: Box<dyn RetryStrategy>,
+    retransmissions: usize,
+    decode: DecodeLevel,
+    listener: Box<dyn Listener<ClientState>>,
+) -> Channel {
+    let (tx, rx) = tokio::sync::mpsc::channel(max_queued_requests);
+    let task = async move {
+        UdpChannelTask::new(host.clone(), rx, retry, retransmissions, decode, listener)
+            .run()
+            .instrument(tracing::info_span!("Modbus-Client-UDP", endpoint = ?host))
+            .await;
+    };
+    tokio::spawn(task);
+    Channel { tx }
+}
+
+pub(crate) struct UdpChannelTask {
+    host: HostAddr,
+    retry: Box<dyn RetryStrategy>,
+    client_loop: ClientLoop,
+    listener: Box<dyn Listener<ClientState>>,
+}
+
+impl UdpChannelTask {
+    pub(crate) fn new(
+        host: HostAddr,
+        rx: Receiver<Command>,
+        retry: Box<dyn RetryStrategy>,
+        retransmissions: usize,
+        decode: DecodeLevel,
+        listener: Box<dyn Listener<ClientState>>,
+    ) -> Self {
+        Self {
+            host,
+            retry,
+            client_loop: ClientLoop::new(rx, FrameWriter::tcp(), FramedReader::tcp(), decode)
+                .with_retransmissions(retransmissions),
+            listener,
+        }
+    }
+
+    // runs until it is shut down
+    pub(crate) async fn run(&mut self) -> Shutdown {
+        self.listener.update(ClientState::Disabled).get().await;
+        let ret = self.run_inner().await;
+        self.listener.update(ClientState::Shutdown).get().await;
+        ret
+    }
+
+    async fn run_inner(&mut self) -> Shutdown {
+        loop {
+            if let Err(Shutdown) = self.client_loop.wait_for_enabled().await {
+                return Shutdown;
+            }
+
+            if let Err(StateChange::Shutdown) = self.try_open_and_run().await {
+                return Shutdown;
+            }
+
+            if !self.client_loop.is_enabled() {
+                self.listener.update(ClientState::Disabled).get().await;
+            }
+        }
+    }
+
+    async fn try_open_and_run(&mut self) -> Result<(), StateChange> {
+        self.listener.update(ClientState::Connecting).get().await;
+        match self.host.connect_udp().await {
+            Err(err) => {
+                let delay = self.retry.after_failed_connect();
+                tracing::warn!(
+                    "failed to open socket to {}: {} - waiting {} ms before next attempt",
+                    self.host,
+                    err,
+                    delay.as_millis()
+                );
+                self.listener
+                    .update(ClientState::WaitAfterFailedConnect(delay))
+                    .get()
+                    .await;
+                self.client_loop.fail_requests_for(delay).await
+            }
+            Ok(socket) => {
+                if let Ok(addr) = socket.peer_addr() {
+                    tracing::info!("sending datagrams to: {}", addr);
+                }
+                self.retry.reset();
+                self.listener.update(ClientState::Connected).get().await;
+                let mut phys = PhysLayer::new_udp(socket);
+                match self.client_loop.run(&mut phys).await {
+                    // the mpsc was closed, end the task
+                    SessionError::Shutdown => Err(StateChange::Shutdown),
+                    // don't wait, we're disabled
+                    SessionError::Disabled => Ok(()),
+                    // open a new socket after waiting
+                    SessionError::IoError(_) | SessionError::BadFrame => {
+                        let delay = self.retry.after_disconnect();
+                        tracing::warn!("waiting {:?} to open a new socket", delay);
+                        self.listener
+                            .update(ClientState::WaitAfterDisconnect(delay))
+                            .get()
+                            .await;
+                        self.client_loop.fail_requests_for(delay).await
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::net::SocketAddr;
+
+use tokio::net::UdpSocket;
+
+use crate::common::phys::PhysLayer;
+use crate::server::task::{SessionHandlers, SessionTask};
+use crate::server::AddressFilter;
+use crate::{RequestError, Shutdown};
+
+/// Socket that answers each request to the address it was received from
+pub(crate) struct UdpServerSocket {
+    socket: UdpSocket,
+    filter: AddressFilter,
+    /// address of the last accepted datagram
+    peer: Option<SocketAddr>,
+}
+
+impl UdpServerSocket {
+    pub(crate) fn new(socket: UdpSocket, filter: AddressFilter) -> Self {
+        Self {
+            socket,
+            filter,
+            peer: None,
+        }
+    }
+
+    pub(crate) fn peer(&self) -> Option<SocketAddr> {
+        self.peer
+    }
+
+    pub(crate) fn set_filter(&mut self, filter: AddressFilter) {
+        self.filter = filter;
+    }
+
+    pub(crate) async fn recv(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let (count, addr) = self.socket.recv_from(buffer).await?;
+            if self.filter.matches(addr.ip()) {
+                self.peer = Some(addr);
+                return Ok(count);
+            }
+            tracing::warn!(
+                "IP address {:?} does not match filter {:?}, discarding datagram",
+                addr.ip(),
+                self.filter
+            );
+        }
+    }
+
+    pub(crate) async fn send(&mut self, data: &[u8]) -> std::io::Result<()> {
+        match self.peer {
+            Some(addr) => {
+                self.socket.send_to(data, addr).await?;
+                Ok(())
+            }
+            None => Err(std::io::ErrorKind::NotConnected.into()),
+        }
+    }
+}
+
+pub(crate) struct UdpServerTask<H>
+where
+    H: SessionHandlers,
+{
+    pub(crate) phys: PhysLayer,
+    pub(crate) session: SessionTask<H>,
+}
+
+impl<H> UdpServerTask<H>
+where
+    H: SessionHandlers,
+{
+    pub(crate) async fn run(&mut self) -> Shutdown {
+        loop {
+            // datagrams are independent, so errors only affect the datagram that caused them
+            if let RequestError::Shutdown = self.session.run(&mut self.phys).await {
+                return Shutdown;
+            }
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::str::FromStr;
 use std::time::Duration;
@@ -11,8 +12,11 @@ use tokio::runtime::Runtime;
 struct Handler {
     pub coils: [bool; 10],
     pub discrete_inputs: [bool; 10],
-    pub holding_registers: [u16; 10],
-    pub input_registers: [u16; 10],
+    pub holding_registers: [u16; 300],
+    pub input_registers: [u16; 300],
+    pub fifo: Vec<u16>,
+    pub device_id: DeviceIdentificationStore,
+    pub files: BTreeMap<u16, [u16; 10]>,
 }
 
 impl Handler {
@@ -20,8 +24,11 @@ impl Handler {
         Self {
             coils: [false; 10],
             discrete_inputs: [false; 10],
-            holding_registers: [0; 10],
-            input_registers: [0; 10],
+            holding_registers: [0; 300],
+            input_registers: [0; 300],
+            fifo: Vec::new(),
+            device_id: DeviceIdentificationStore::basic("vendor", "product", "1.0"),
+            files: BTreeMap::from([(4, [0; 10])]),
         }
     }
 }
@@ -55,6 +62,25 @@ impl RequestHandler for Handler {
         }
     }
 
+    fn read_fifo_queue(&self, address: u16) -> Result<Vec<u16>, ExceptionCode> {
+        match address {
+            0 => Ok(self.fifo.clone()),
+            _ => Err(ExceptionCode::IllegalDataAddress),
+        }
+    }
+
+    fn device_identification(&self) -> Option<&DeviceIdentificationStore> {
+        Some(&self.device_id)
+    }
+
+    fn read_exception_status(&self) -> Result<u8, ExceptionCode> {
+        Ok(0x6D)
+    }
+
+    fn report_server_id(&self) -> Result<Vec<u8>, ExceptionCode> {
+        Ok(vec![0x2A, 0xFF, 0x01])
+    }
+
     fn write_single_coil(&mut self, value: Indexed<bool>) -> Result<(), ExceptionCode> {
         match self.coils.get_mut(value.index as usize) {
             Some(x) => {
@@ -94,6 +120,42 @@ impl RequestHandler for Handler {
         }
         Ok(())
     }
+
+    fn read_file_record(&self, file_number: u16, record_number: u16) -> Result<u16, ExceptionCode> {
+        match self.files.get(&file_number) {
+            Some(file) => file.get(record_number as usize).to_result(),
+            None => Err(ExceptionCode::IllegalDataAddress),
+        }
+    }
+
+    fn write_file_record(
+        &mut self,
+        file_number: u16,
+        values: WriteRegisters,
+    ) -> Result<(), ExceptionCode> {
+        let file = self
+            .files
+            .get_mut(&file_number)
+            .ok_or(ExceptionCode::IllegalDataAddress)?;
+        for x in values.iterator {
+            match file.get_mut(x.index as usize) {
+                Some(c) => *c = x.value,
+                None => return Err(ExceptionCode::IllegalDataAddress),
+            }
+        }
+        Ok(())
+    }
+
+    fn custom_function(
+        &mut self,
+        function_code: u8,
+        data: &[u8],
+    ) -> Result<Vec<u8>, ExceptionCode> {
+        match function_code {
+            0x41 => Ok(data.iter().rev().copied().collect()),
+            _ => Err(ExceptionCode::IllegalFunction),
+        }
+    }
 }
 
 async fn test_requests_and_responses() {
@@ -110,12 +172,17 @@ async fn test_requests_and_responses() {
     .await
     .unwrap();
 
+    let mut poller = Poller::new(PollerConfig::new(
+        Duration::from_secs(1),
+        Duration::from_millis(10),
+    ));
+
     let mut channel = spawn_tcp_client_task(
         HostAddr::ip(addr.ip(), addr.port()),
         10,
         default_retry_strategy(),
         DecodeLevel::default(),
-        None,
+        Some(poller.client_listener(None)),
     );
 
     channel.enable().await.unwrap();
@@ -126,6 +193,11 @@ async fn test_requests_and_responses() {
         let mut guard = handler.lock().unwrap();
         guard.discrete_inputs[0] = true;
         guard.input_registers[0] = 0xCAFE;
+        guard.fifo = vec![0x01B8, 0x1284];
+        // extended objects that don't fit in a single response
+        for id in 0x80..0x84 {
+            guard.device_id.set(id, vec![id; 100]).unwrap();
+        }
     }
 
     assert_eq!(
@@ -144,6 +216,132 @@ async fn test_requests_and_responses() {
         vec![Indexed::new(0, 0xCAFE), Indexed::new(1, 0x0000)]
     );
 
+    assert_eq!(
+        channel.read_fifo_queue(params, 0).await.unwrap(),
+        vec![0x01B8, 0x1284]
+    );
+
+    // the server rejects queues longer than the maximum allowed
+    handler.lock().unwrap().fifo = vec![0; 32];
+    assert_eq!(
+        channel.read_fifo_queue(params, 0).await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataValue))
+    );
+
+    let basic = channel
+        .read_device_identification(params, ReadDeviceIdCode::Basic, 0)
+        .await
+        .unwrap();
+    assert_eq!(basic.conformity_level, 0x83);
+    assert_eq!(
+        basic.objects.iter().map(|x| x.as_str()).collect::<Vec<_>>(),
+        vec![Some("vendor"), Some("product"), Some("1.0")]
+    );
+
+    // the extended objects are read using multiple requests
+    let extended = channel
+        .read_device_identification(params, ReadDeviceIdCode::Extended, 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        extended.objects.iter().map(|x| x.id).collect::<Vec<_>>(),
+        vec![0x00, 0x01, 0x02, 0x80, 0x81, 0x82, 0x83]
+    );
+    assert_eq!(extended.get(0x83).unwrap().value, vec![0x83; 100]);
+
+    assert_eq!(
+        channel
+            .read_device_identification(params, ReadDeviceIdCode::Individual, 0x81)
+            .await
+            .unwrap()
+            .objects,
+        vec![DeviceIdObject::new(0x81, vec![0x81; 100])]
+    );
+    assert_eq!(
+        channel
+            .read_device_identification(params, ReadDeviceIdCode::Individual, 0x05)
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataAddress))
+    );
+
+    assert_eq!(channel.read_exception_status(params).await.unwrap(), 0x6D);
+    assert_eq!(
+        channel.report_server_id(params).await.unwrap(),
+        vec![0x2A, 0xFF, 0x01]
+    );
+
+    assert_eq!(
+        channel
+            .send_custom_function(params, 0x41, vec![0x01, 0x02, 0x03])
+            .await
+            .unwrap(),
+        vec![0x03, 0x02, 0x01]
+    );
+    assert_eq!(
+        channel.send_custom_function(params, 0x64, vec![]).await,
+        Err(RequestError::Exception(ExceptionCode::IllegalFunction))
+    );
+    assert_eq!(
+        channel.send_custom_function(params, 0x03, vec![]).await,
+        Err(RequestError::BadRequest(
+            InvalidRequest::InvalidCustomFunctionCode(0x03)
+        ))
+    );
+
+    assert_eq!(
+        channel
+            .write_file_records(
+                params,
+                vec![
+                    FileRecordData::new(4, 1, vec![0xCAFE, 0xBEEF]),
+                    FileRecordData::new(4, 8, vec![0x1234]),
+                ],
+            )
+            .await,
+        Ok(())
+    );
+    assert_eq!(
+        channel
+            .read_file_records(
+                params,
+                vec![FileRecord::new(4, 0, 3), FileRecord::new(4, 8, 2)],
+            )
+            .await
+            .unwrap(),
+        vec![
+            FileRecordData::new(4, 0, vec![0x0000, 0xCAFE, 0xBEEF]),
+            FileRecordData::new(4, 8, vec![0x1234, 0x0000]),
+        ]
+    );
+    assert_eq!(
+        channel
+            .read_file_records(params, vec![FileRecord::new(5, 0, 1)])
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataAddress))
+    );
+    assert_eq!(
+        channel
+            .write_file_records(params, vec![FileRecordData::new(4, 9, vec![0x01, 0x02])])
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataAddress))
+    );
+
+    // diagnostic counters are only maintained on serial lines
+    assert_eq!(
+        channel
+            .read_diagnostic_counter(params, DiagnosticCounter::BusMessage)
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalFunction))
+    );
+    assert_eq!(
+        channel.get_comm_event_counter(params).await,
+        Err(RequestError::Exception(ExceptionCode::IllegalFunction))
+    );
+    assert_eq!(
+        channel.get_comm_event_log(params).await,
+        Err(RequestError::Exception(ExceptionCode::IllegalFunction))
+    );
+
     // do a single coil write and verify that it was written by reading it
     assert_eq!(
         channel
@@ -222,6 +420,183 @@ async fn test_requests_and_responses() {
             Indexed::new(2, 0x0506)
         ]
     );
+
+    // modify a register with masks and verify the result
+    assert_eq!(
+        channel
+            .mask_write_register(params, MaskWriteRegister::new(0, 0xFF00, 0x00AB))
+            .await
+            .unwrap(),
+        MaskWriteRegister::new(0, 0xFF00, 0x00AB)
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(0, 1).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(0, 0x01AB)]
+    );
+
+    // write registers and read back an overlapping range in a single request
+    assert_eq!(
+        channel
+            .read_write_multiple_registers(
+                params,
+                ReadWriteMultiple::new(
+                    AddressRange::try_from(1, 3).unwrap(),
+                    3,
+                    vec![0x0708, 0x090A]
+                )
+                .unwrap()
+            )
+            .await
+            .unwrap(),
+        vec![
+            Indexed::new(1, 0x0304),
+            Indexed::new(2, 0x0506),
+            Indexed::new(3, 0x0708)
+        ]
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(4, 1).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(4, 0x090A)]
+    );
+
+    // write and read back typed values in a swapped byte order
+    assert_eq!(
+        channel
+            .write_values(params, 0, &[123.456f32, -1.5f32], ByteOrder::Cdab)
+            .await
+            .unwrap(),
+        AddressRange::try_from(0, 4).unwrap()
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(0, 2).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(0, 0xE979), Indexed::new(1, 0x42F6)]
+    );
+    assert_eq!(
+        channel
+            .read_holding_f32(params, 0, 2, ByteOrder::Cdab)
+            .await
+            .unwrap(),
+        vec![123.456, -1.5]
+    );
+    assert_eq!(
+        channel
+            .write_values(params, 4, &[-2i64], ByteOrder::Dcba)
+            .await
+            .unwrap(),
+        AddressRange::try_from(4, 4).unwrap()
+    );
+    assert_eq!(
+        channel
+            .read_holding_i64(params, 4, 1, ByteOrder::Dcba)
+            .await
+            .unwrap(),
+        vec![-2]
+    );
+    assert_eq!(
+        channel
+            .write_string(params, 0, "rodbus", 4, ByteOrder::Abcd)
+            .await
+            .unwrap(),
+        AddressRange::try_from(0, 4).unwrap()
+    );
+    assert_eq!(
+        channel
+            .read_holding_string(params, 0, 4, ByteOrder::Abcd)
+            .await
+            .unwrap(),
+        "rodbus"
+    );
+    assert_eq!(
+        channel
+            .write_string(params, 0, "rodbus", 2, ByteOrder::Abcd)
+            .await,
+        Err(RequestError::BadRequest(InvalidRequest::InvalidValue(
+            CodecError::StringTooLong(6, 2)
+        )))
+    );
+
+    // write and read back ranges that exceed the limits of a single request
+    let values: Vec<u16> = (0..300).collect();
+    assert_eq!(
+        channel
+            .write_multiple_registers_chunked(
+                params,
+                WriteMultiple::from(0, values.clone()).unwrap(),
+                ChunkMode::Sequential
+            )
+            .await
+            .unwrap(),
+        AddressRange::try_from(0, 300).unwrap()
+    );
+    for mode in [ChunkMode::Sequential, ChunkMode::Pipelined] {
+        assert_eq!(
+            channel
+                .read_holding_registers_chunked(
+                    params,
+                    AddressRange::try_from(0, 300).unwrap(),
+                    mode
+                )
+                .await
+                .unwrap(),
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, x)| Indexed::new(i as u16, *x))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            channel
+                .read_input_registers_chunked(
+                    params,
+                    AddressRange::try_from(100, 250).unwrap(),
+                    mode
+                )
+                .await,
+            Err(ChunkError {
+                index: 1,
+                range: AddressRange::try_from(225, 125).unwrap(),
+                error: RequestError::Exception(ExceptionCode::IllegalDataAddress),
+            })
+        );
+    }
+
+    // poll adjacent ranges, which are read using a single request
+    let mut group = ScanGroup::new(Duration::from_secs(60), 0);
+    for (start, count) in [(10, 5), (0, 10)] {
+        group
+            .add(
+                UnitId::new(0x01),
+                ScanType::HoldingRegisters,
+                AddressRange::try_from(start, count).unwrap(),
+            )
+            .unwrap();
+    }
+    let id = poller.add_group(group);
+    let (_poller, mut results) = poller.spawn_stream(channel.clone(), 10);
+    for (start, count) in [(0, 10), (10, 5)] {
+        let range = AddressRange::try_from(start, count).unwrap();
+        assert_eq!(
+            results.recv().await.unwrap(),
+            ScanResult {
+                group: id,
+                unit_id: UnitId::new(0x01),
+                scan_type: ScanType::HoldingRegisters,
+                range,
+                result: Ok(ScanValues::Registers(
+                    (start..start + count).map(|x| Indexed::new(x, x)).collect()
+                )),
+            }
+        );
+    }
 }
 
 #[test]
@@ -229,3 +604,731 @@ fn can_read_and_write_values() {
     let rt = Runtime::new().unwrap();
     rt.block_on(test_requests_and_responses())
 }
+
+#[cfg(feature = "serial")]
+async fn test_rtu_over_tcp() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40001").unwrap();
+
+    let _server = spawn_rtu_over_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_rtu_over_tcp_client_task(
+        HostAddr::ip(addr.ip(), addr.port()),
+        10,
+        default_retry_strategy(),
+        DecodeLevel::default(),
+        None,
+    );
+
+    channel.enable().await.unwrap();
+
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .write_single_register(params, Indexed::new(1, 0xABCD))
+            .await
+            .unwrap(),
+        Indexed::new(1, 0xABCD)
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(0, 2).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(0, 0x0000), Indexed::new(1, 0xABCD)]
+    );
+    assert_eq!(
+        channel
+            .read_coils(params, AddressRange::try_from(10, 1).unwrap())
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataAddress))
+    );
+}
+
+#[cfg(feature = "serial")]
+#[test]
+fn can_read_and_write_values_using_rtu_over_tcp() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_rtu_over_tcp())
+}
+
+async fn test_udp() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40002").unwrap();
+
+    let _server = spawn_udp_server_task(
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_udp_client_task(
+        HostAddr::ip(addr.ip(), addr.port()),
+        10,
+        default_retry_strategy(),
+        2,
+        DecodeLevel::default(),
+        None,
+    );
+
+    channel.enable().await.unwrap();
+
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .write_multiple_registers(
+                params,
+                WriteMultiple::from(0, vec![0x0102, 0x0304]).unwrap()
+            )
+            .await
+            .unwrap(),
+        AddressRange::try_from(0, 2).unwrap()
+    );
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(0, 2).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(0, 0x0102), Indexed::new(1, 0x0304)]
+    );
+    assert_eq!(
+        channel
+            .read_coils(params, AddressRange::try_from(10, 1).unwrap())
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataAddress))
+    );
+}
+
+#[test]
+fn can_read_and_write_values_using_udp() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_udp())
+}
+
+async fn test_pipelined_tcp() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40003").unwrap();
+
+    let _server = spawn_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_pipelined_tcp_client_task(
+        HostAddr::ip(addr.ip(), addr.port()),
+        10,
+        4,
+        default_retry_strategy(),
+        DecodeLevel::default(),
+        None,
+    );
+
+    channel.enable().await.unwrap();
+
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+    let mut first = channel.clone();
+    let mut second = channel.clone();
+
+    let (write, read) = tokio::join!(
+        first.write_single_register(params, Indexed::new(2, 0xCAFE)),
+        second.read_holding_registers(params, AddressRange::try_from(0, 1).unwrap()),
+    );
+    assert_eq!(write.unwrap(), Indexed::new(2, 0xCAFE));
+    assert_eq!(read.unwrap(), vec![Indexed::new(0, 0x0000)]);
+
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(2, 1).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(2, 0xCAFE)]
+    );
+}
+
+#[test]
+fn can_pipeline_requests_over_tcp() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_pipelined_tcp())
+}
+
+/// Answers reads of register 0 after a delay and all other reads immediately
+struct SlowHandler;
+
+impl AsyncRequestHandler for SlowHandler {
+    fn read_holding_registers(
+        &self,
+        _context: &RequestContext,
+        range: AddressRange,
+    ) -> MaybeAsync<Result<Vec<u16>, ExceptionCode>> {
+        if range.start == 0 {
+            MaybeAsync::asynchronous(async move {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+                Ok((range.start..range.start + range.count).collect())
+            })
+        } else {
+            MaybeAsync::ready(Ok((range.start..range.start + range.count).collect()))
+        }
+    }
+}
+
+async fn test_async_tcp_server() {
+    let addr = SocketAddr::from_str("127.0.0.1:40004").unwrap();
+
+    let _server = spawn_async_tcp_server_task(
+        1,
+        addr,
+        AsyncServerHandlerMap::single(UnitId::new(1), std::sync::Arc::new(SlowHandler)),
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_pipelined_tcp_client_task(
+        HostAddr::ip(addr.ip(), addr.port()),
+        10,
+        4,
+        default_retry_strategy(),
+        DecodeLevel::default(),
+        None,
+    );
+
+    channel.enable().await.unwrap();
+
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(5));
+
+    let mut slow_channel = channel.clone();
+    let slow = tokio::spawn(async move {
+        slow_channel
+            .read_holding_registers(params, AddressRange::try_from(0, 2).unwrap())
+            .await
+    });
+    // give the slow request time to be sent first
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // the second request is answered before the first one
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(5, 1).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(5, 5)]
+    );
+    assert!(!slow.is_finished());
+
+    assert_eq!(
+        slow.await.unwrap().unwrap(),
+        vec![Indexed::new(0, 0), Indexed::new(1, 1)]
+    );
+    assert_eq!(
+        channel
+            .read_coils(params, AddressRange::try_from(0, 1).unwrap())
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalFunction))
+    );
+}
+
+#[test]
+fn async_tcp_server_replies_out_of_order() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_async_tcp_server())
+}
+
+async fn test_async_udp_server_with_sync_handler() {
+    let handler = Handler::new().wrap();
+    let addr = SocketAddr::from_str("127.0.0.1:40005").unwrap();
+
+    let _server = spawn_async_udp_server_task(
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()).into(),
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_udp_client_task(
+        HostAddr::ip(addr.ip(), addr.port()),
+        10,
+        default_retry_strategy(),
+        2,
+        DecodeLevel::default(),
+        None,
+    );
+
+    channel.enable().await.unwrap();
+
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .write_multiple_coils(
+                params,
+                WriteMultiple::from(0, vec![true, false, true]).unwrap()
+            )
+            .await
+            .unwrap(),
+        AddressRange::try_from(0, 3).unwrap()
+    );
+    assert_eq!(
+        channel
+            .read_coils(params, AddressRange::try_from(0, 3).unwrap())
+            .await
+            .unwrap(),
+        vec![
+            Indexed::new(0, true),
+            Indexed::new(1, false),
+            Indexed::new(2, true)
+        ]
+    );
+    assert_eq!(
+        channel
+            .read_write_multiple_registers(
+                params,
+                ReadWriteMultiple::new(
+                    AddressRange::try_from(0, 3).unwrap(),
+                    1,
+                    vec![0xCAFE, 0xBEEF]
+                )
+                .unwrap()
+            )
+            .await
+            .unwrap(),
+        vec![
+            Indexed::new(0, 0x0000),
+            Indexed::new(1, 0xCAFE),
+            Indexed::new(2, 0xBEEF)
+        ]
+    );
+    assert_eq!(handler.lock().unwrap().holding_registers[1], 0xCAFE);
+    assert_eq!(
+        channel
+            .read_input_registers(params, AddressRange::try_from(300, 1).unwrap())
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataAddress))
+    );
+}
+
+#[test]
+fn async_udp_server_can_use_sync_handlers() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_async_udp_server_with_sync_handler())
+}
+
+async fn test_gateway() {
+    let downstream_addr = SocketAddr::from_str("127.0.0.1:40006").unwrap();
+    let gateway_addr = SocketAddr::from_str("127.0.0.1:40007").unwrap();
+    // nothing listens on this address
+    let unreachable_addr = SocketAddr::from_str("127.0.0.1:40008").unwrap();
+
+    let handler = Handler::new().wrap();
+    let _downstream = spawn_tcp_server_task(
+        1,
+        downstream_addr,
+        ServerHandlerMap::single(UnitId::new(1), handler.clone()),
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let downstream = spawn_tcp_client_task(
+        HostAddr::ip(downstream_addr.ip(), downstream_addr.port()),
+        10,
+        default_retry_strategy(),
+        DecodeLevel::default(),
+        None,
+    );
+    downstream.enable().await.unwrap();
+
+    let unreachable = spawn_tcp_client_task(
+        HostAddr::ip(unreachable_addr.ip(), unreachable_addr.port()),
+        10,
+        default_retry_strategy(),
+        DecodeLevel::default(),
+        None,
+    );
+    unreachable.enable().await.unwrap();
+
+    let timeout = Duration::from_millis(200);
+    let mut handlers = AsyncServerHandlerMap::new();
+    handlers.add(
+        UnitId::new(5),
+        std::sync::Arc::new(GatewayHandler::new(
+            downstream.clone(),
+            RequestParam::new(UnitId::new(1), timeout),
+        )),
+    );
+    // the downstream server doesn't answer this unit id
+    handlers.add(
+        UnitId::new(6),
+        std::sync::Arc::new(GatewayHandler::new(
+            downstream,
+            RequestParam::new(UnitId::new(2), timeout),
+        )),
+    );
+    handlers.add(
+        UnitId::new(7),
+        std::sync::Arc::new(GatewayHandler::new(
+            unreachable,
+            RequestParam::new(UnitId::new(1), timeout),
+        )),
+    );
+
+    let _gateway = spawn_async_tcp_server_task(
+        1,
+        gateway_addr,
+        handlers,
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tcp_client_task(
+        HostAddr::ip(gateway_addr.ip(), gateway_addr.port()),
+        10,
+        default_retry_strategy(),
+        DecodeLevel::default(),
+        None,
+    );
+    channel.enable().await.unwrap();
+
+    let params = RequestParam::new(UnitId::new(5), Duration::from_secs(1));
+
+    assert_eq!(
+        channel
+            .write_multiple_registers(
+                params,
+                WriteMultiple::from(3, vec![0xCAFE, 0xBEEF]).unwrap()
+            )
+            .await
+            .unwrap(),
+        AddressRange::try_from(3, 2).unwrap()
+    );
+    assert_eq!(handler.lock().unwrap().holding_registers[3], 0xCAFE);
+    assert_eq!(
+        channel
+            .read_holding_registers(params, AddressRange::try_from(3, 2).unwrap())
+            .await
+            .unwrap(),
+        vec![Indexed::new(3, 0xCAFE), Indexed::new(4, 0xBEEF)]
+    );
+    assert_eq!(
+        channel
+            .read_coils(params, AddressRange::try_from(10, 1).unwrap())
+            .await,
+        Err(RequestError::Exception(ExceptionCode::IllegalDataAddress))
+    );
+    assert_eq!(
+        channel
+            .read_coils(
+                RequestParam::new(UnitId::new(6), Duration::from_secs(1)),
+                AddressRange::try_from(0, 1).unwrap()
+            )
+            .await,
+        Err(RequestError::Exception(
+            ExceptionCode::GatewayTargetDeviceFailedToRespond
+        ))
+    );
+    assert_eq!(
+        channel
+            .read_coils(
+                RequestParam::new(UnitId::new(7), Duration::from_secs(1)),
+                AddressRange::try_from(0, 1).unwrap()
+            )
+            .await,
+        Err(RequestError::Exception(
+            ExceptionCode::GatewayPathUnavailable
+        ))
+    );
+}
+
+#[test]
+fn gateway_forwards_requests_to_downstream_channels() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_gateway())
+}
+
+async fn test_server_reconfiguration() {
+    let addr = SocketAddr::from_str("127.0.0.1:40009").unwrap();
+
+    let mut server = spawn_tcp_server_task(
+        4,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), Handler::new().wrap()),
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+    let range = AddressRange::try_from(0, 1).unwrap();
+    let mut channels = Vec::new();
+    for _ in 0..2 {
+        let mut channel = spawn_tcp_client_task(
+            HostAddr::ip(addr.ip(), addr.port()),
+            10,
+            default_retry_strategy(),
+            DecodeLevel::default(),
+            None,
+        );
+        channel.enable().await.unwrap();
+        channel.read_coils(params, range).await.unwrap();
+        channels.push(channel);
+    }
+
+    let sessions = server.sessions().await.unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert!(sessions.iter().all(|x| x.peer.ip() == addr.ip()));
+
+    // units are added to the active sessions
+    server
+        .add_unit(UnitId::new(2), Handler::new().wrap())
+        .await
+        .unwrap();
+    channels[1]
+        .read_coils(
+            RequestParam::new(UnitId::new(0x02), Duration::from_secs(1)),
+            range,
+        )
+        .await
+        .unwrap();
+
+    assert!(server.close_session(sessions[0].id).await.unwrap());
+    assert!(!server.close_session(sessions[0].id).await.unwrap());
+    assert_eq!(server.sessions().await.unwrap(), vec![sessions[1]]);
+
+    // the remaining session no longer matches the filter
+    server
+        .set_address_filter(AddressFilter::Exact("127.0.0.2".parse().unwrap()))
+        .await
+        .unwrap();
+    assert!(server.sessions().await.unwrap().is_empty());
+}
+
+#[test]
+fn server_can_be_reconfigured_while_running() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_server_reconfiguration())
+}
+
+async fn test_database_changes() {
+    let addr = SocketAddr::from_str("127.0.0.1:40010").unwrap();
+
+    let mut database = Database::new();
+    database.holding_registers_mut().add_range(0, &[0; 4]);
+    let mut changes = database.subscribe();
+
+    let _server = spawn_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), database.wrap()),
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tcp_client_task(
+        HostAddr::ip(addr.ip(), addr.port()),
+        10,
+        default_retry_strategy(),
+        DecodeLevel::default(),
+        None,
+    );
+    channel.enable().await.unwrap();
+
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+    channel
+        .write_multiple_registers(
+            params,
+            WriteMultiple::from(1, vec![0xCAFE, 0xBEEF]).unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let changes = changes.recv().await.unwrap();
+    assert_eq!(changes.source, ChangeSource::Remote);
+    assert_eq!(
+        changes.holding_registers,
+        vec![Indexed::new(1, 0xCAFE), Indexed::new(2, 0xBEEF)]
+    );
+    let context = changes.context.unwrap();
+    assert_eq!(context.unit_id, UnitId::new(1));
+    assert!(context.session.is_some());
+    assert_eq!(context.peer.map(|x| x.ip()), Some(addr.ip()));
+    assert_eq!(context.transport, TransportKind::Tcp);
+    assert_eq!(context.tls, None);
+}
+
+#[test]
+fn database_reports_the_points_written_by_clients() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_database_changes())
+}
+
+async fn test_address_filter_replacement() {
+    let addr = SocketAddr::from_str("127.0.0.1:40011").unwrap();
+
+    let mut server = spawn_tcp_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), Handler::new().wrap()),
+        "deny 127.0.0.0/8, allow *".parse().unwrap(),
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tcp_client_task(
+        HostAddr::ip(addr.ip(), addr.port()),
+        10,
+        default_retry_strategy(),
+        DecodeLevel::default(),
+        None,
+    );
+    channel.enable().await.unwrap();
+
+    let params = RequestParam::new(UnitId::new(0x01), Duration::from_secs(1));
+    let range = AddressRange::try_from(0, 1).unwrap();
+    assert!(channel.read_coils(params, range).await.is_err());
+    assert!(server.sessions().await.unwrap().is_empty());
+
+    server
+        .set_address_filter("deny 10.0.0.0/8, allow 127.0.0.0/8".parse().unwrap())
+        .await
+        .unwrap();
+
+    // the client reconnects once the filter accepts it
+    while server.sessions().await.unwrap().is_empty() {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(
+        channel.read_coils(params, range).await,
+        Ok(vec![Indexed::new(0, false)])
+    );
+    assert_eq!(server.sessions().await.unwrap().len(), 1);
+}
+
+#[test]
+fn address_filter_can_be_replaced_while_running() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_address_filter_replacement())
+}
+
+/// records the context of the requests it receives
+#[cfg(feature = "tls")]
+#[derive(Default)]
+struct ContextRecorder {
+    contexts: Vec<RequestContext>,
+}
+
+#[cfg(feature = "tls")]
+impl RequestHandler for ContextRecorder {
+    fn begin_request(&mut self, context: &RequestContext) {
+        self.contexts.push(context.clone());
+    }
+
+    fn read_coil(&self, _address: u16) -> Result<bool, ExceptionCode> {
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "tls")]
+async fn test_tls_request_context() {
+    use std::path::Path;
+
+    let addr = SocketAddr::from_str("127.0.0.1:40012").unwrap();
+    let certs = Path::new(env!("CARGO_MANIFEST_DIR")).join("../certs/ca_chain");
+
+    let recorder = ContextRecorder::default().wrap();
+    let _server = spawn_tls_server_task(
+        1,
+        addr,
+        ServerHandlerMap::single(UnitId::new(1), recorder.clone()),
+        TlsServerConfig::new(
+            &certs.join("ca_cert.pem"),
+            &certs.join("server_cert.pem"),
+            &certs.join("server_key.pem"),
+            None,
+            MinTlsVersion::V1_2,
+            CertificateMode::AuthorityBased,
+        )
+        .unwrap(),
+        AddressFilter::Any,
+        DecodeLevel::default(),
+    )
+    .await
+    .unwrap();
+
+    let mut channel = spawn_tls_client_task(
+        HostAddr::ip(addr.ip(), addr.port()),
+        10,
+        default_retry_strategy(),
+        TlsClientConfig::new(
+            "test.com",
+            &certs.join("ca_cert.pem"),
+            &certs.join("client_cert.pem"),
+            &certs.join("client_key.pem"),
+            None,
+            MinTlsVersion::V1_2,
+            CertificateMode::AuthorityBased,
+        )
+        .unwrap(),
+        DecodeLevel::default(),
+        None,
+    );
+    channel.enable().await.unwrap();
+
+    assert_eq!(
+        channel
+            .read_coils(
+                RequestParam::new(UnitId::new(1), Duration::from_secs(1)),
+                AddressRange::try_from(0, 1).unwrap()
+            )
+            .await,
+        Ok(vec![Indexed::new(0, true)])
+    );
+
+    let contexts = recorder.lock().unwrap().contexts.clone();
+    assert_eq!(contexts.len(), 1);
+    let context = &contexts[0];
+    assert_eq!(context.unit_id, UnitId::new(1));
+    assert_eq!(context.transport, TransportKind::Tls);
+    assert!(context.session.is_some());
+    assert_eq!(context.peer.map(|x| x.ip()), Some(addr.ip()));
+    assert_eq!(
+        context.tls.as_deref(),
+        Some(&TlsIdentity::new(
+            Some("operator".to_string()),
+            Some("C=US, ST=Oregon, L=Bend, O=Test, CN=DO NOT USE".to_string())
+        ))
+    );
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn tls_handlers_receive_the_identity_of_the_client() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(test_tls_request_context())
+}